@@ -15,6 +15,7 @@ use crate::*;
 use std::sync::mpsc;
 use std::thread;
 use crate::grid::GridLayout;
+use crate::progress::{Progress, ConsoleProgress};
 
 const PERCOLATION_THRESHOLD_EXPECTED: f64 = 0.592746;
 
@@ -24,7 +25,7 @@ pub fn main() {
     // find_mismatch();
     // find_largest_possible();
     // find_largest_practical_for_parallel();
-    find_percolation_threshold();
+    find_percolation_threshold(&mut ConsoleProgress);
 }
 
 pub struct GenericPercolation<T>
@@ -226,8 +227,7 @@ impl <T> GenericPercolation<T>
         Err("Ran out of pairs before percolation.".to_string())
     }
 
-    pub fn get_steps_to_percolation_generate_pairs(&mut self) -> usize {
-        let mut rng = rand::thread_rng();
+    pub fn get_steps_to_percolation_generate_pairs<R: Rng>(&mut self, rng: &mut R) -> usize {
         let zero = T::zero();
         let mut step_count = 0;
         loop {
@@ -275,35 +275,35 @@ impl <T> GenericPercolation<T>
     }
 
     #[inline]
-    pub(crate) fn block_color_index(&self, largest_roots: &Vec<T>, x: T, y: T, is_last_frame: bool) -> usize
+    pub(crate) fn block_color_index(&self, slots: &PercolationSlots, largest_roots: &Vec<T>, x: T, y: T, is_last_frame: bool) -> usize
     {
         match self.block_state(x, y) {
-            PercolationBlockState::Blocked => COLOR_INDEX_BLACK,
+            PercolationBlockState::Blocked => slots.blocked,
             PercolationBlockState::Open => {
                 let node_index = self.x_y_to_index(x, y);
                 let node_root_index = self.union.root(node_index);
                 if self.union.is_connected(self.end_node_index, node_index) {
-                    COLOR_INDEX_RED
+                    slots.percolating
                 } else {
                     if !is_last_frame {
                         for i in 0..largest_roots.len() {
                             //if perc.connections.is_connected(largest_roots[i], node_root_index) {
                             if node_root_index == largest_roots[i] {
-                                return COLOR_INDEX_FIRST_EXTRA + i;
+                                return slots.first_extra + i;
                             }
                         }
                     }
-                    COLOR_INDEX_WHITE
+                    slots.open
                 }
             },
-            PercolationBlockState::Filled => COLOR_INDEX_BLUE,
+            PercolationBlockState::Filled => slots.filled,
         }
     }
 
-    pub fn run_to_completion(&mut self, max_seconds: usize) -> Vec<(T, T)> {
+    pub fn run_to_completion<R: Rng>(&mut self, rng: &mut R, max_seconds: usize, progress: &mut dyn Progress) -> Vec<(T, T)> {
+        progress.phase_start("run_to_completion");
         let start_time = Instant::now();
         let stop_time = start_time + Duration::from_secs_f64(max_seconds as f64);
-        let mut rng = rand::thread_rng();
         let mut unions = vec![];
         let zero = T::zero();
         while !self.percolates() && Instant::now() < stop_time {
@@ -313,8 +313,11 @@ impl <T> GenericPercolation<T>
                 unions.push((x, y));
             }
         }
-        let time_msg = if self.percolates() { "complete" } else { "RAN OUT OF TIME" };
-        println!("run_to_completion(): [{}]; duration = {:?}; unions = {}", time_msg, Instant::now() - start_time, unions.len());
+        if !self.percolates() {
+            progress.message("run_to_completion(): RAN OUT OF TIME");
+        }
+        progress.counter("unions", unions.len() as f64);
+        progress.phase_end("run_to_completion", Instant::now() - start_time);
         unions
     }
 
@@ -355,7 +358,7 @@ fn test_correctness() {
     perc.wrap_top_bottom = false;
     perc.wrap_left_right = false;
     let mut perc32 = GenericPercolation::new(width32, height32);
-    let pairs= random_x_y_pairs(width, width * height);
+    let pairs= random_x_y_pairs(&mut rand::thread_rng(), width, width * height);
 
     let start_time = Instant::now();
     let result = perc.get_steps_to_percolation(&pairs);
@@ -385,7 +388,7 @@ fn find_mismatch() {
     perc.wrap_left_right = false;
     let mut perc32 = GenericPercolation::new(width32, height32);
     compare_perc_perc32(0, 0, &perc, &perc32);
-    let pairs= random_x_y_pairs(width, width * height);
+    let pairs= random_x_y_pairs(&mut rand::thread_rng(), width, width * height);
     for (x, y) in pairs.iter() {
         perc.open(*x, *y);
         perc32.open_usize(*x, *y);
@@ -454,7 +457,7 @@ fn find_largest_practical_for_parallel() {
         println!("new = {:?}", Instant::now() - start_time);
 
         let start_time = Instant::now();
-        let step_count = perc.get_steps_to_percolation_generate_pairs();
+        let step_count = perc.get_steps_to_percolation_generate_pairs(&mut rand::thread_rng());
         let step_pct = step_count as f64 / size as f64;
         println!("percolate = {:?}; step_count = {}; step_pct = {}", Instant::now() - start_time, fc(step_count), ff(step_pct, 5));
 
@@ -463,8 +466,11 @@ fn find_largest_practical_for_parallel() {
 }
 
 #[allow(dead_code)]
-fn find_percolation_threshold() {
+fn find_percolation_threshold(progress: &mut dyn Progress) {
     // [10,000,000/100,000,000]: expected = 0.592746; actual = 0.592743; diff = -0.000003; pct = -0.000006
+    let phase_name = "find_percolation_threshold";
+    progress.phase_start(phase_name);
+    let start_time = Instant::now();
     let trial_count = 100_000_000;
     let thread_count = 50;
     let trials_per_thread = 50;
@@ -476,6 +482,8 @@ fn find_percolation_threshold() {
     let (tx, rx) = mpsc::channel();
 
     let mut step_count_sum = 0;
+    let mut trials_so_far = 0;
+    let mut calc_threshold = 0.0;
 
     for chunk_index in 0..chunk_count {
         let mut threads = Vec::with_capacity(thread_count);
@@ -485,7 +493,7 @@ fn find_percolation_threshold() {
                 let mut step_count = 0;
                 for _ in 0..trials_per_thread {
                     let mut perc = GenericPercolation::new(width, height);
-                    step_count += perc.get_steps_to_percolation_generate_pairs();
+                    step_count += perc.get_steps_to_percolation_generate_pairs(&mut rand::thread_rng());
                 }
                 thread_tx.send(step_count).unwrap();
             });
@@ -496,20 +504,22 @@ fn find_percolation_threshold() {
             // `recv` will block the current thread if there are no messages available
             step_count_sum += rx.recv().unwrap();
         }
-        let trials_so_far = thread_count * trials_per_thread * (chunk_index + 1);
-        let calc_threshold = step_count_sum as f64 / (size as f64 * trials_so_far as f64);
-        let diff = calc_threshold - PERCOLATION_THRESHOLD_EXPECTED;
-        let pct = diff / PERCOLATION_THRESHOLD_EXPECTED;
-        println!("[{}/{}]: expected = {}; actual = {}; diff = {}; pct = {}",
-                 fc(trials_so_far), fc(trial_count), ff(PERCOLATION_THRESHOLD_EXPECTED, 6),
-                 ff(calc_threshold, 6), ff(diff, 6), ff(pct, 6));
+        trials_so_far = thread_count * trials_per_thread * (chunk_index + 1);
+        calc_threshold = step_count_sum as f64 / (size as f64 * trials_so_far as f64);
+        progress.counter("threshold", calc_threshold);
+        progress.percent_complete(phase_name, trials_so_far as f64 / trial_count as f64);
 
         // Wait for the threads to complete any remaining work.
         for thread in threads {
             thread.join().unwrap();
         }
     }
-    println!("\nDone.");
+    let diff = calc_threshold - PERCOLATION_THRESHOLD_EXPECTED;
+    let pct = diff / PERCOLATION_THRESHOLD_EXPECTED;
+    progress.message(&format!("[{}/{}]: expected = {}; actual = {}; diff = {}; pct = {}",
+             fc(trials_so_far), fc(trial_count), ff(PERCOLATION_THRESHOLD_EXPECTED, 6),
+             ff(calc_threshold, 6), ff(diff, 6), ff(pct, 6)));
+    progress.phase_end(phase_name, Instant::now() - start_time);
 }
 
 #[allow(dead_code)]
@@ -528,7 +538,7 @@ fn find_percolation_threshold_1() {
         let thread_tx = tx.clone();
         let thread = thread::spawn(move || {
             let mut perc = GenericPercolation::new(width, height);
-            let step_count = perc.get_steps_to_percolation_generate_pairs();
+            let step_count = perc.get_steps_to_percolation_generate_pairs(&mut rand::thread_rng());
             thread_tx.send(step_count).unwrap();
         });
         threads.push(thread);
@@ -218,6 +218,7 @@ fn test_performance() {
     let size_mult = 2;
     let size_max = 8_000_000_000;
     let union_mult = 1;
+    let mut rng = rand::thread_rng();
 
     while size < size_max {
         let mut connected_count = 0;
@@ -225,8 +226,8 @@ fn test_performance() {
         println!("\nsize = {}; union_count = {}", fc(size), fc(union_count));
 
         let start_time = Instant::now();
-        let union_pairs = random_x_y_pairs(size, union_count);
-        let is_connected_pairs = random_x_y_pairs(size, union_count);
+        let union_pairs = random_x_y_pairs(&mut rng, size, union_count);
+        let is_connected_pairs = random_x_y_pairs(&mut rng, size, union_count);
         println!("pairs: {:?}", Instant::now() - start_time);
 
         // Non-generic.
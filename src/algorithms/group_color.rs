@@ -5,19 +5,21 @@ use std::ops::AddAssign;
 use std::fmt::{Debug, Display};
 use std::convert::TryFrom;
 use std::hash::Hash;
-use rand::Rng;
 use num_format::ToFormattedStr;
 
 use crate::*;
 use crate::Color1;
+use crate::palette::Palette;
 use super::percolation::*;
 use super::generic_union::GenericUnion;
 use std::collections::btree_set::Difference;
 use itertools::Itertools;
+use rand::Rng;
 
 pub struct GroupColor<T> {
     groups: HashMap<T, usize>,
-    colors: Vec<Color1>,
+    colors: Palette,
+    slots: PercolationSlots,
     color_min: f32,
     color_max: f32,
     open_as_white: bool,
@@ -35,19 +37,22 @@ impl <T> GroupColor<T>
         assert!(color_min <= 1.0);
         assert!(color_max >= 0.0);
         assert!(color_max <= 1.0);
+        // Only the reserved colors until the first groups are set up.
+        let colors = percolation_reserved_palette();
         Self {
             groups: Default::default(),
-            colors: vec![],
+            slots: PercolationSlots::new(&colors),
+            colors,
             color_min,
             color_max,
             open_as_white,
         }
     }
 
-    fn set_up_first_groups(&mut self, roots: Vec<T>) {
-        self.colors = vec![Color1::black(), Color1::white(), Color1::blue(), Color1::red()];
-        let special_color_count = self.colors.len();
-        self.gen_colors(roots.len());
+    fn set_up_first_groups<R: Rng>(&mut self, rng: &mut R, roots: Vec<T>) {
+        self.colors = percolation_palette(rng, 0, self.color_min, self.color_max);
+        let special_color_count = self.colors.reserved_count();
+        self.gen_colors(rng, roots.len());
 
         self.groups = HashMap::with_capacity(roots.len());
         let mut color_index = special_color_count;
@@ -57,9 +62,9 @@ impl <T> GroupColor<T>
         }
     }
 
-    pub fn update(&mut self, roots: Vec<T>, union: &GenericUnion<T>) {
+    pub fn update<R: Rng>(&mut self, rng: &mut R, roots: Vec<T>, union: &GenericUnion<T>) {
         if self.groups.is_empty() {
-            self.set_up_first_groups(roots);
+            self.set_up_first_groups(rng, roots);
         } else {
             //rintln!("\n###########################################################################\n");
             let old_set = BTreeSet::from_iter(self.groups.keys().map(|x| *x));
@@ -110,41 +115,40 @@ impl <T> GroupColor<T>
         }
     }
 
-    fn gen_colors(&mut self, count: usize) {
-        let mut rng = rand::thread_rng();
-        for _ in 0..count {
-            self.colors.push(
-                Color1::from_rgb(rng.gen_range(self.color_min..self.color_max),
-                                 rng.gen_range(self.color_min..self.color_max),
-                                 rng.gen_range(self.color_min..self.color_max)));
-        }
+    // color_min and color_max bound the lightness of the group colors.
+    fn gen_colors<R: Rng>(&mut self, rng: &mut R, count: usize) {
+        self.colors.add_distinct_colors(rng, count, self.color_min, self.color_max);
     }
 
     pub fn get_colors(&self) -> Vec<Color1> {
-        self.colors.clone()
+        self.colors.colors().clone()
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.colors
     }
 
     pub fn block_color_index(&self, node_index: T, end_node_index: T, block_state: PercolationBlockState, is_last_frame: bool, union: &GenericUnion<T>) -> usize
     {
         match block_state {
-            PercolationBlockState::Blocked => COLOR_INDEX_BLACK,
+            PercolationBlockState::Blocked => self.slots.blocked,
             PercolationBlockState::Open => {
                 let node_root_index = union.root(node_index);
                 if union.is_connected(end_node_index, node_index) {
-                    COLOR_INDEX_RED
+                    self.slots.percolating
                 } else {
                     if !is_last_frame {
                         if let Some(color_index) = self.groups.get(&node_root_index) {
                             return *color_index;
                         }
                     }
-                    if self.open_as_white { COLOR_INDEX_WHITE } else { COLOR_INDEX_BLACK }
+                    if self.open_as_white { self.slots.open } else { self.slots.blocked }
                 }
             },
             PercolationBlockState::Filled => {
-                // println!("Color index = COLOR_INDEX_BLUE / {} / {:?}", COLOR_INDEX_BLUE, self.colors[COLOR_INDEX_BLUE]);
+                // println!("Color index = filled / {} / {:?}", self.slots.filled, self.colors.color(self.slots.filled));
                 // panic!()
-                COLOR_INDEX_BLUE
+                self.slots.filled
             },
         }
     }
@@ -2,11 +2,14 @@ use crate::*;
 use crate::algorithms::percolation::*;
 use crate::algorithms::generic_percolation::GenericPercolation;
 use std::convert::TryFrom;
-use rand::Rng;
 use std::time::Duration;
 use crate::grid::{Grid, GridLayout};
 use crate::renderer_3::Renderer;
 use crate::algorithms::group_color::GroupColor;
+use crate::frame_source;
+use crate::progress::ConsoleProgress;
+
+const FRAME_BUFFER_SIZE: usize = 10;
 
 pub fn main() {
     // animate_precalc();
@@ -53,7 +56,7 @@ fn animate_precalc() {
 
     // Precalculate the number of steps.
     let mut perc = GenericPercolation::new(width_typed, height_typed);
-    let unions = perc.run_to_completion(run_to_completion_max_seconds);
+    let unions = perc.run_to_completion(&mut rng, run_to_completion_max_seconds, &mut ConsoleProgress);
     let step_count = unions.len();
     let start_render_step = (step_count as f64 * start_render_threshold) as usize;
     let render_step_count = (step_count - start_render_step) + 1;
@@ -64,65 +67,62 @@ fn animate_precalc() {
              fc(render_step_count), fc(frame_count_max),
              fc(steps_per_frame));
 
-    let mut frames = vec![];
-    let post_precalc_start_time = Instant::now();
-
-    let mut colors = Vec::with_capacity(extra_colors_max);
-    colors.push(Color1::black());
-    colors.push(Color1::white());
-    colors.push(Color1::blue());
-    colors.push(Color1::red());
-    let color_min = 0.0;
-    let color_max = 1.0;
-    for _ in 0..extra_colors_max {
-        colors.push(Color1::from_rgb(rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max)));
-    }
+    // Frames are rendered on a background thread while the animation plays rather than all being
+    // built up front, so count them now in order to know how long to show each one.
+    let is_render_step = move |step_index: usize| step_index == step_count - 1 || (step_index >= start_render_step && (steps_per_frame == 0 || step_index % steps_per_frame == 0));
+    let frame_count = (0..step_count).filter(|step_index| is_render_step(*step_index)).count();
+    let frame_seconds = animation_seconds as f64 / frame_count as f64;
+    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
+
+    let palette = percolation_palette(&mut rng, extra_colors_max, 0.0, 1.0);
 
-    let mut color_grid_elapsed = Duration::zero();
-    let mut frame_elapsed = Duration::zero();
-    let mut largest_roots= vec![];
+    let slots = PercolationSlots::new(&palette);
 
     let mut perc = GenericPercolation::clone_new(&perc);
-    for (step_index, (x, y)) in unions.iter().enumerate() {
-        perc.open(*x, *y);
-        let is_last_frame = step_index == step_count - 1;
-        if is_last_frame || (step_index >= start_render_step && (steps_per_frame == 0 || step_index % steps_per_frame == 0)) {
-            if largest_roots.is_empty() {
-                largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
-            }
+    let source = frame_source::spawn(FRAME_BUFFER_SIZE, move |sender| {
+        let post_precalc_start_time = Instant::now();
+        let mut color_grid_elapsed = Duration::zero();
+        let mut frame_elapsed = Duration::zero();
+        let mut largest_roots= vec![];
+
+        for (step_index, (x, y)) in unions.iter().enumerate() {
+            perc.open(*x, *y);
+            let is_last_frame = step_index == step_count - 1;
+            if is_render_step(step_index) {
+                if largest_roots.is_empty() {
+                    largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
+                }
 
-            let color_grid_start_time = Instant::now();
-            let mut color_grid = Grid::new(width, height, 0);
-            color_grid.record_events = false;
-            for color_y in 0..height_typed {
-                for color_x in 0..width_typed {
-                    color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
-                                      perc.block_color_index(&largest_roots, color_x, color_y, is_last_frame));
+                let color_grid_start_time = Instant::now();
+                let mut color_grid = Grid::new(width, height, 0);
+                color_grid.record_events = false;
+                for color_y in 0..height_typed {
+                    for color_x in 0..width_typed {
+                        color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
+                                          perc.block_color_index(&slots, &largest_roots, color_x, color_y, is_last_frame));
+                    }
                 }
-            }
-            color_grid_elapsed += Instant::now() - color_grid_start_time;
+                color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame_start_time = Instant::now();
+                let frame_start_time = Instant::now();
 
-            let frame= color_grid.as_frame_color_index(display_width, display_height, frame_seconds_min as f64);
-            frames.push(frame);
-            frame_elapsed += Instant::now() - frame_start_time;
+                let frame= color_grid.as_frame_color_index(display_width, display_height, frame_seconds);
+                frame_elapsed += Instant::now() - frame_start_time;
+                if !sender.send(frame) {
+                    return;
+                }
+            }
         }
-    }
-    // Adjust the frame seconds for the actual number of frames.
-    let frame_count = frames.len();
-    let frame_seconds = animation_seconds as f64 / frame_count as f64;
-    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
-    frames.iter_mut().for_each(|frame| frame.seconds_to_next = frame_seconds);
 
-    let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
-    let overall_elapsed= Instant::now() - start_time;
-    println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
-             overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+        let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
+        let overall_elapsed= Instant::now() - start_time;
+        println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
+                 overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+    });
 
     let additive = false;
     let back_color = Color1::black();
-    Renderer::display_additive_with_colors("Percolation", display_width, display_height, back_color, frames, additive, colors.clone());
+    Renderer::display_source("Percolation", display_width, display_height, back_color, source, additive, palette);
 }
 
 #[allow(dead_code)]
@@ -158,7 +158,7 @@ fn try_captions() {
 
     // Precalculate the number of steps.
     let mut perc = GenericPercolation::new(width_typed, height_typed);
-    let unions = perc.run_to_completion(run_to_completion_max_seconds);
+    let unions = perc.run_to_completion(&mut rng, run_to_completion_max_seconds, &mut ConsoleProgress);
     let step_count = unions.len();
     let start_render_step = (step_count as f64 * start_render_threshold) as usize;
     let render_step_count = (step_count - start_render_step) + 1;
@@ -169,67 +169,63 @@ fn try_captions() {
              fc(render_step_count), fc(frame_count_max),
              fc(steps_per_frame));
 
-    let mut frames = vec![];
-    let post_precalc_start_time = Instant::now();
-
-    let mut colors = Vec::with_capacity(extra_colors_max);
-    colors.push(Color1::black());
-    colors.push(Color1::white());
-    colors.push(Color1::blue());
-    colors.push(Color1::red());
-    let color_min = 0.0;
-    let color_max = 1.0;
-    for _ in 0..extra_colors_max {
-        colors.push(Color1::from_rgb(rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max)));
-    }
+    // As in animate_precalc(), count the frames first and then render them on a background thread.
+    let is_render_step = move |step_index: usize| step_index == step_count - 1 || (step_index >= start_render_step && (steps_per_frame == 0 || step_index % steps_per_frame == 0));
+    let frame_count = (0..step_count).filter(|step_index| is_render_step(*step_index)).count();
+    let frame_seconds = animation_seconds as f64 / frame_count as f64;
+    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
 
-    let mut color_grid_elapsed = Duration::zero();
-    let mut frame_elapsed = Duration::zero();
-    let mut largest_roots= vec![];
+    let palette = percolation_palette(&mut rng, extra_colors_max, 0.0, 1.0);
 
-    let mut perc = GenericPercolation::clone_new(&perc);
-    for (step_index, (x, y)) in unions.iter().enumerate() {
-        perc.open(*x, *y);
-        let is_last_frame = step_index == step_count - 1;
-        if is_last_frame || (step_index >= start_render_step && (steps_per_frame == 0 || step_index % steps_per_frame == 0)) {
-            if largest_roots.is_empty() {
-                largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
-            }
+    let slots = PercolationSlots::new(&palette);
 
-            let color_grid_start_time = Instant::now();
-            let mut color_grid = Grid::new(width, height, 0);
-            color_grid.record_events = false;
-            for color_y in 0..height_typed {
-                for color_x in 0..width_typed {
-                    color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
-                                      perc.block_color_index(&largest_roots, color_x, color_y, is_last_frame));
+    let mut perc = GenericPercolation::clone_new(&perc);
+    let source = frame_source::spawn(FRAME_BUFFER_SIZE, move |sender| {
+        let post_precalc_start_time = Instant::now();
+        let mut color_grid_elapsed = Duration::zero();
+        let mut frame_elapsed = Duration::zero();
+        let mut largest_roots= vec![];
+
+        for (step_index, (x, y)) in unions.iter().enumerate() {
+            perc.open(*x, *y);
+            let is_last_frame = step_index == step_count - 1;
+            if is_render_step(step_index) {
+                if largest_roots.is_empty() {
+                    largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
                 }
-            }
-            color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame_start_time = Instant::now();
+                let color_grid_start_time = Instant::now();
+                let mut color_grid = Grid::new(width, height, 0);
+                color_grid.record_events = false;
+                for color_y in 0..height_typed {
+                    for color_x in 0..width_typed {
+                        color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
+                                          perc.block_color_index(&slots, &largest_roots, color_x, color_y, is_last_frame));
+                    }
+                }
+                color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame= color_grid.as_frame_color_index_captioned(display_width, display_height, caption_height, frame_seconds_min as f64);
-            //add_caption(&mut frame, display_width, caption_height, caption);
+                let frame_start_time = Instant::now();
 
-            frames.push(frame);
-            frame_elapsed += Instant::now() - frame_start_time;
+                let mut frame= color_grid.as_frame_color_index_captioned(display_width, display_height, caption_height, frame_seconds);
+                let caption = format!("Step {} of {}", fc(step_index + 1), fc(step_count));
+                frame.add_caption(display_width, caption_height, &caption, Color1::white());
+                frame_elapsed += Instant::now() - frame_start_time;
+                if !sender.send(frame) {
+                    return;
+                }
+            }
         }
-    }
-    // Adjust the frame seconds for the actual number of frames.
-    let frame_count = frames.len();
-    let frame_seconds = animation_seconds as f64 / frame_count as f64;
-    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
-    frames.iter_mut().for_each(|frame| frame.seconds_to_next = frame_seconds);
 
-    let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
-    let overall_elapsed= Instant::now() - start_time;
-    println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
-             overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+        let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
+        let overall_elapsed= Instant::now() - start_time;
+        println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
+                 overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+    });
 
     let additive = false;
     let back_color = Color1::black();
-    Renderer::display_additive_with_colors("Percolation", display_width, display_height + caption_height, back_color, frames, additive, colors.clone());
+    Renderer::display_source("Percolation", display_width, display_height + caption_height, back_color, source, additive, palette);
 }
 
 #[allow(dead_code)]
@@ -267,7 +263,7 @@ fn animate_hex() {
 
     // Precalculate the number of steps.
     let mut perc = GenericPercolation::new_layout(width_typed, height_typed, GridLayout::Hex);
-    let unions = perc.run_to_completion(run_to_completion_max_seconds);
+    let unions = perc.run_to_completion(&mut rng, run_to_completion_max_seconds, &mut ConsoleProgress);
     let step_count = unions.len();
     let start_render_step = (step_count as f64 * start_render_threshold) as usize;
     let render_step_count = (step_count - start_render_step) + 1;
@@ -278,65 +274,60 @@ fn animate_hex() {
              fc(render_step_count), fc(frame_count_max),
              fc(steps_per_frame));
 
-    let mut frames = vec![];
-    let post_precalc_start_time = Instant::now();
-
-    let mut colors = Vec::with_capacity(extra_colors_max);
-    colors.push(Color1::black());
-    colors.push(Color1::white());
-    colors.push(Color1::blue());
-    colors.push(Color1::red());
-    let color_min = 0.0;
-    let color_max = 1.0;
-    for _ in 0..extra_colors_max {
-        colors.push(Color1::from_rgb(rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max)));
-    }
+    let is_render_step = move |step_index: usize| step_index == step_count - 1 || (step_index >= start_render_step && (steps_per_frame == 0 || step_index % steps_per_frame == 0));
+    let frame_count = (0..step_count).filter(|step_index| is_render_step(*step_index)).count();
+    let frame_seconds = animation_seconds as f64 / frame_count as f64;
+    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
+
+    let palette = percolation_palette(&mut rng, extra_colors_max, 0.0, 1.0);
 
-    let mut color_grid_elapsed = Duration::zero();
-    let mut frame_elapsed = Duration::zero();
-    let mut largest_roots= vec![];
+    let slots = PercolationSlots::new(&palette);
 
     let mut perc = GenericPercolation::clone_new(&perc);
-    for (step_index, (x, y)) in unions.iter().enumerate() {
-        perc.open(*x, *y);
-        let is_last_frame = step_index == step_count - 1;
-        if is_last_frame || (step_index >= start_render_step && (steps_per_frame == 0 || step_index % steps_per_frame == 0)) {
-            if largest_roots.is_empty() {
-                largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
-            }
+    let source = frame_source::spawn(FRAME_BUFFER_SIZE, move |sender| {
+        let post_precalc_start_time = Instant::now();
+        let mut color_grid_elapsed = Duration::zero();
+        let mut frame_elapsed = Duration::zero();
+        let mut largest_roots= vec![];
+
+        for (step_index, (x, y)) in unions.iter().enumerate() {
+            perc.open(*x, *y);
+            let is_last_frame = step_index == step_count - 1;
+            if is_render_step(step_index) {
+                if largest_roots.is_empty() {
+                    largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
+                }
 
-            let color_grid_start_time = Instant::now();
-            let mut color_grid = Grid::new(width, height, 0);
-            color_grid.record_events = false;
-            for color_y in 0..height_typed {
-                for color_x in 0..width_typed {
-                    color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
-                                      perc.block_color_index(&largest_roots, color_x, color_y, is_last_frame));
+                let color_grid_start_time = Instant::now();
+                let mut color_grid = Grid::new(width, height, 0);
+                color_grid.record_events = false;
+                for color_y in 0..height_typed {
+                    for color_x in 0..width_typed {
+                        color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
+                                          perc.block_color_index(&slots, &largest_roots, color_x, color_y, is_last_frame));
+                    }
                 }
-            }
-            color_grid_elapsed += Instant::now() - color_grid_start_time;
+                color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame_start_time = Instant::now();
+                let frame_start_time = Instant::now();
 
-            let frame= color_grid.as_frame_color_index_layout(display_width, display_height, GridLayout::Hex, frame_seconds_min as f64);
-            frames.push(frame);
-            frame_elapsed += Instant::now() - frame_start_time;
+                let frame= color_grid.as_frame_color_index_layout(display_width, display_height, GridLayout::Hex, frame_seconds);
+                frame_elapsed += Instant::now() - frame_start_time;
+                if !sender.send(frame) {
+                    return;
+                }
+            }
         }
-    }
-    // Adjust the frame seconds for the actual number of frames.
-    let frame_count = frames.len();
-    let frame_seconds = animation_seconds as f64 / frame_count as f64;
-    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
-    frames.iter_mut().for_each(|frame| frame.seconds_to_next = frame_seconds);
 
-    let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
-    let overall_elapsed= Instant::now() - start_time;
-    println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
-             overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+        let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
+        let overall_elapsed= Instant::now() - start_time;
+        println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
+                 overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+    });
 
     let additive = false;
     let back_color = Color1::black();
-    Renderer::display_additive_with_colors("Percolation", display_width + block_half_width, display_height, back_color, frames, additive, colors.clone());
+    Renderer::display_source("Percolation", display_width + block_half_width, display_height, back_color, source, additive, palette);
 }
 
 #[allow(dead_code)]
@@ -365,7 +356,7 @@ fn animate_decelerate() {
 
     // Precalculate the number of steps.
     let mut perc = GenericPercolation::new(width_typed, height_typed);
-    let unions = perc.run_to_completion(run_to_completion_max_seconds);
+    let unions = perc.run_to_completion(&mut rng, run_to_completion_max_seconds, &mut ConsoleProgress);
     let step_count = unions.len();
     let start_render_step = (step_count as f64 * start_render_threshold) as usize;
     let render_step_count = (step_count - start_render_step) + 1;
@@ -377,76 +368,67 @@ fn animate_decelerate() {
              fc(steps_per_frame));
 
     let frame_step_indexes = group_decelerate(deceleration_ratio, render_step_count, start_render_step, frame_count_max);
+    let render_steps = decelerated_render_steps(&frame_step_indexes, step_count);
+    let frame_count = render_steps.len();
+    let frame_seconds = animation_seconds as f64 / frame_count as f64;
+    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
 
-    let mut frames = vec![];
-    let post_precalc_start_time = Instant::now();
-
-    let mut colors = Vec::with_capacity(extra_colors_max);
-    colors.push(Color1::black());
-    colors.push(Color1::white());
-    colors.push(Color1::blue());
-    colors.push(Color1::red());
-    let color_min = 0.0;
-    let color_max = 1.0;
-    for _ in 0..extra_colors_max {
-        colors.push(Color1::from_rgb(rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max)));
-    }
+    let palette = percolation_palette(&mut rng, extra_colors_max, 0.0, 1.0);
 
-    let mut color_grid_elapsed = Duration::zero();
-    let mut frame_elapsed = Duration::zero();
-    let mut largest_roots= vec![];
+    let slots = PercolationSlots::new(&palette);
 
     let mut perc = GenericPercolation::clone_new(&perc);
-    let mut next_frame_step = frame_step_indexes[0];
-    for (step_index, (x, y)) in unions.iter().enumerate() {
-        perc.open(*x, *y);
-        let is_last_frame = step_index == step_count - 1;
-        if is_last_frame || step_index == next_frame_step {
-            if largest_roots.is_empty() {
-                largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
-            }
-
-            let color_grid_start_time = Instant::now();
-            let mut color_grid = Grid::new(width, height, 0);
-            color_grid.record_events = false;
-            for color_y in 0..height_typed {
-                for color_x in 0..width_typed {
-                    color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
-                                      perc.block_color_index(&largest_roots, color_x, color_y, is_last_frame));
+    let source = frame_source::spawn(FRAME_BUFFER_SIZE, move |sender| {
+        let post_precalc_start_time = Instant::now();
+        let mut color_grid_elapsed = Duration::zero();
+        let mut frame_elapsed = Duration::zero();
+        let mut largest_roots= vec![];
+
+        let mut render_steps = render_steps.iter().peekable();
+        for (step_index, (x, y)) in unions.iter().enumerate() {
+            perc.open(*x, *y);
+            let is_last_frame = step_index == step_count - 1;
+            if render_steps.next_if_eq(&&step_index).is_some() {
+                if largest_roots.is_empty() {
+                    largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
                 }
-            }
-            color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame_start_time = Instant::now();
+                let color_grid_start_time = Instant::now();
+                let mut color_grid = Grid::new(width, height, 0);
+                color_grid.record_events = false;
+                for color_y in 0..height_typed {
+                    for color_x in 0..width_typed {
+                        color_grid.set_xy(usize::try_from(color_x).unwrap(), usize::try_from(color_y).unwrap(),
+                                          perc.block_color_index(&slots, &largest_roots, color_x, color_y, is_last_frame));
+                    }
+                }
+                color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame= color_grid.as_frame_color_index(display_width, display_height, frame_seconds_min as f64);
-            frames.push(frame);
-            frame_elapsed += Instant::now() - frame_start_time;
+                let frame_start_time = Instant::now();
 
-            if frames.len() < frame_step_indexes.len() {
-                next_frame_step = frame_step_indexes[frames.len()];
+                let frame= color_grid.as_frame_color_index(display_width, display_height, frame_seconds);
+                frame_elapsed += Instant::now() - frame_start_time;
+                if !sender.send(frame) {
+                    return;
+                }
             }
         }
-    }
-    // Adjust the frame seconds for the actual number of frames.
-    let frame_count = frames.len();
-    let frame_seconds = animation_seconds as f64 / frame_count as f64;
-    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
-    frames.iter_mut().for_each(|frame| frame.seconds_to_next = frame_seconds);
 
-    let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
-    let overall_elapsed= Instant::now() - start_time;
-    println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
-             overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+        let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
+        let overall_elapsed= Instant::now() - start_time;
+        println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
+                 overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+    });
 
     let additive = false;
     let back_color = Color1::black();
-    Renderer::display_additive_with_colors("Percolation", display_width, display_height, back_color, frames, additive, colors.clone());
+    Renderer::display_source("Percolation", display_width, display_height, back_color, source, additive, palette);
 }
 
 #[allow(dead_code)]
 fn animate_group_color() {
     let start_time = Instant::now();
+    let mut rng = rand::thread_rng();
     let run_to_completion_max_seconds = 30;
     // let extra_colors_max = 200;
     // let extra_colors_max = 400;
@@ -475,7 +457,7 @@ fn animate_group_color() {
 
     // Precalculate the number of steps.
     let mut perc = GenericPercolation::new(width_typed, height_typed);
-    let mut unions = perc.run_to_completion(run_to_completion_max_seconds);
+    let mut unions = perc.run_to_completion(&mut rng, run_to_completion_max_seconds, &mut ConsoleProgress);
     unions.truncate(unions.len() - 1);
 
     let step_count = unions.len();
@@ -490,65 +472,81 @@ fn animate_group_color() {
              fc(steps_per_frame));
 
     let frame_step_indexes = group_decelerate(deceleration_ratio as f64, render_step_count, start_render_step, frame_count_max);
-
-    let mut frames = vec![];
-    let post_precalc_start_time = Instant::now();
-
-    let mut group_color = GroupColor::new(color_min, color_max, open_as_white);
-
-    let mut color_grid_elapsed = Duration::zero();
-    let mut frame_elapsed = Duration::zero();
+    let render_steps = decelerated_render_steps(&frame_step_indexes, step_count);
+    let frame_count = render_steps.len();
+    let frame_seconds = animation_seconds as f64 / frame_count as f64;
+    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
 
     let mut perc = GenericPercolation::clone_new(&perc);
-    let mut next_frame_step = frame_step_indexes[0];
-    for (step_index, (x, y)) in unions.iter().enumerate() {
-        perc.open(*x, *y);
-        let is_last_frame = step_index == step_count - 1;
-        if is_last_frame || step_index == next_frame_step {
-            let largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
-            if step_index >= color_threshold_step {
-                group_color.update(largest_roots, &perc.union);
-            }
-
-            let color_grid_start_time = Instant::now();
-            let mut color_grid = Grid::new(width, height, 0);
-            color_grid.record_events = false;
-            for color_y in 0..height_typed {
-                for color_x in 0..width_typed {
-                    let node_index = perc.x_y_to_index(color_x, color_y);
-                    let block_state = perc.block_state(color_x, color_y);
-                    let color_index = group_color.block_color_index(node_index, perc.end_node_index, block_state, is_last_frame, &perc.union);
-                    let (color_x_usize, color_y_usize) = (perc.to_usize(color_x), perc.to_usize(color_y));
-                    color_grid.set_xy(color_x_usize, color_y_usize, color_index);
+    let source = frame_source::spawn(FRAME_BUFFER_SIZE, move |sender| {
+        let post_precalc_start_time = Instant::now();
+        // The group colors are added as groups appear, so unlike the other animations the palette
+        // isn't known before the first frame. Each frame carries its own colors instead of indexes.
+        let mut rng = rand::thread_rng();
+        let mut group_color = GroupColor::new(color_min, color_max, open_as_white);
+        let mut color_grid_elapsed = Duration::zero();
+        let mut frame_elapsed = Duration::zero();
+
+        let mut render_steps = render_steps.iter().peekable();
+        for (step_index, (x, y)) in unions.iter().enumerate() {
+            perc.open(*x, *y);
+            let is_last_frame = step_index == step_count - 1;
+            if render_steps.next_if_eq(&&step_index).is_some() {
+                let largest_roots = perc.union.get_roots_of_largest_components(extra_colors_max);
+                if step_index >= color_threshold_step {
+                    group_color.update(&mut rng, largest_roots, &perc.union);
                 }
-            }
-            color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame_start_time = Instant::now();
+                let color_grid_start_time = Instant::now();
+                let mut color_grid = Grid::new(width, height, 0);
+                color_grid.record_events = false;
+                for color_y in 0..height_typed {
+                    for color_x in 0..width_typed {
+                        let node_index = perc.x_y_to_index(color_x, color_y);
+                        let block_state = perc.block_state(color_x, color_y);
+                        let color_index = group_color.block_color_index(node_index, perc.end_node_index, block_state, is_last_frame, &perc.union);
+                        let (color_x_usize, color_y_usize) = (perc.to_usize(color_x), perc.to_usize(color_y));
+                        color_grid.set_xy(color_x_usize, color_y_usize, color_index);
+                    }
+                }
+                color_grid_elapsed += Instant::now() - color_grid_start_time;
 
-            let frame= color_grid.as_frame_color_index(display_width, display_height, frame_seconds_min as f64);
-            frames.push(frame);
-            frame_elapsed += Instant::now() - frame_start_time;
+                let frame_start_time = Instant::now();
 
-            if frames.len() < frame_step_indexes.len() {
-                next_frame_step = frame_step_indexes[frames.len()];
+                let palette = group_color.palette();
+                let frame= color_grid.as_frame(display_width, display_height, frame_seconds, &|color_index| palette.color(*color_index));
+                frame_elapsed += Instant::now() - frame_start_time;
+                if !sender.send(frame) {
+                    return;
+                }
             }
         }
-    }
-    // Adjust the frame seconds for the actual number of frames.
-    let frame_count = frames.len();
-    let frame_seconds = animation_seconds as f64 / frame_count as f64;
-    println!("frame_count = {}; frame_seconds = {}", fc(frame_count), ff(frame_seconds, 3));
-    frames.iter_mut().for_each(|frame| frame.seconds_to_next = frame_seconds);
 
-    let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
-    let overall_elapsed= Instant::now() - start_time;
-    println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
-             overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+        let post_precalc_elapsed = Instant::now() - post_precalc_start_time;
+        let overall_elapsed= Instant::now() - start_time;
+        println!("overall = {:?}; post-precalc = {:?}, color grids = {:?}; frames = {:?}",
+                 overall_elapsed, post_precalc_elapsed, color_grid_elapsed, frame_elapsed);
+    });
 
     let additive = false;
     let back_color = Color1::black();
-    Renderer::display_additive_with_colors("Percolation", display_width, display_height, back_color, frames, additive, group_color.get_colors());
+    Renderer::display_source("Percolation", display_width, display_height, back_color, source, additive, vec![]);
+}
+
+// The steps at which animate_decelerate() and animate_group_color() show a frame: each of the
+// frame steps from group_decelerate() in turn, plus the last step.
+fn decelerated_render_steps(frame_step_indexes: &[usize], step_count: usize) -> Vec<usize> {
+    let mut render_steps = vec![];
+    let mut next_frame_step = frame_step_indexes[0];
+    for step_index in 0..step_count {
+        if step_index == step_count - 1 || step_index == next_frame_step {
+            render_steps.push(step_index);
+            if render_steps.len() < frame_step_indexes.len() {
+                next_frame_step = frame_step_indexes[render_steps.len()];
+            }
+        }
+    }
+    render_steps
 }
 /*
 #[inline]
@@ -617,7 +615,7 @@ pub fn group_decelerate(ratio_first_to_second_half: f64, render_step_count: usiz
     counts.reverse();
     //rintln!("group_decelerate: counts = {}", counts.iter().map(|x| fc(*x)).join("\t"));
     assert_eq!(frame_count, counts.len());
-    debug_assert_eq!(render_step_count, counts.iter().sum());
+    debug_assert_eq!(render_step_count, counts.iter().sum::<usize>());
 
     let mut this_index = 0;
     let mut indexes = vec![];
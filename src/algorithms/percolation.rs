@@ -1,17 +1,51 @@
+use serde::{Serialize, Deserialize};
+
 use crate::grid::Grid;
 use crate::algorithms::union_find::WeightedQuickUnion;
 use rand::Rng;
 use crate::renderer_3::Renderer;
 use crate::color::Color1;
+use crate::palette::Palette;
+use crate::output::Output;
+use crate::render_backend::FramePlayer;
+use crate::progress::{Progress, ConsoleProgress};
 use std::time::{Instant, Duration};
 
 use crate::*;
 
-pub const COLOR_INDEX_BLACK: usize = 0;
-pub const COLOR_INDEX_WHITE: usize = 1;
-pub const COLOR_INDEX_BLUE: usize = 2;
-pub const COLOR_INDEX_RED: usize = 3;
-pub const COLOR_INDEX_FIRST_EXTRA: usize = 4;
+// The indexes of the named slots in a percolation palette, looked up once so that coloring each
+// block doesn't have to search the palette.
+#[derive(Clone, Copy, Debug)]
+pub struct PercolationSlots {
+    pub blocked: usize,
+    pub open: usize,
+    pub filled: usize,
+    pub percolating: usize,
+    // The colors for the largest groups start here.
+    pub first_extra: usize,
+}
+
+pub const SLOT_BLOCKED: &str = "blocked";
+pub const SLOT_OPEN: &str = "open";
+pub const SLOT_FILLED: &str = "filled";
+pub const SLOT_PERCOLATING: &str = "percolating";
+
+// Black for blocked, white for open, blue for filled and red for the percolating cluster.
+pub fn percolation_reserved_palette() -> Palette {
+    Palette::with_reserved(&[
+        (SLOT_BLOCKED, Color1::black()),
+        (SLOT_OPEN, Color1::white()),
+        (SLOT_FILLED, Color1::blue()),
+        (SLOT_PERCOLATING, Color1::red()),
+    ])
+}
+
+// The reserved colors followed by random colors for the largest groups.
+pub fn percolation_palette<R: Rng>(rng: &mut R, extra_count: usize, color_min: f32, color_max: f32) -> Palette {
+    let mut palette = percolation_reserved_palette();
+    palette.add_random_colors(rng, extra_count, color_min, color_max);
+    palette
+}
 
 pub fn main() {
     // try_percolation();
@@ -20,6 +54,18 @@ pub fn main() {
     animate_precalc();
 }
 
+impl PercolationSlots {
+    pub fn new(palette: &Palette) -> Self {
+        Self {
+            blocked: palette.index(SLOT_BLOCKED),
+            open: palette.index(SLOT_OPEN),
+            filled: palette.index(SLOT_FILLED),
+            percolating: palette.index(SLOT_PERCOLATING),
+            first_extra: palette.reserved_count(),
+        }
+    }
+}
+
 pub struct PercolationGrid {
     pub width: usize,
     pub height: usize,
@@ -31,13 +77,14 @@ pub struct PercolationGrid {
     pub connections: WeightedQuickUnion,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum PercolationBlockState {
     Blocked,
     Open,
     Filled,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum PercolationType {
     TopBottom,
     TopLeftBottomRight { radius: usize },
@@ -286,10 +333,10 @@ impl PercolationGrid {
         }
     }
 
-    pub fn run_to_completion(&mut self, max_seconds: usize) -> Vec<(usize, usize)> {
+    pub fn run_to_completion<R: Rng>(&mut self, rng: &mut R, max_seconds: usize, progress: &mut dyn Progress) -> Vec<(usize, usize)> {
+        progress.phase_start("run_to_completion");
         let start_time = Instant::now();
         let end_time = start_time + Duration::from_secs_f64(max_seconds as f64);
-        let mut rng = rand::thread_rng();
         let mut unions = vec![];
         while !self.percolates() && Instant::now() < end_time {
             let x = rng.gen_range(0..self.width);
@@ -298,8 +345,11 @@ impl PercolationGrid {
                 unions.push((x, y));
             }
         }
-        let time_msg = if self.percolates() { "complete" } else { "RAN OUT OF TIME" };
-        println!("run_to_completion(): [{}]; duration = {:?}; unions = {}", time_msg, Instant::now() - start_time, unions.len());
+        if !self.percolates() {
+            progress.message("run_to_completion(): RAN OUT OF TIME");
+        }
+        progress.counter("unions", unions.len() as f64);
+        progress.phase_end("run_to_completion", Instant::now() - start_time);
         unions
     }
 
@@ -425,7 +475,6 @@ fn try_animation() {
 
 #[allow(dead_code)]
 fn try_animation_fast() {
-    let mut rng = rand::thread_rng();
     // let percolation_type = PercolationType::TopBottom;
     // let percolation_type = PercolationType::TopLeftBottomRight;
     let percolation_type = PercolationType::CenterOut { radius: 100 };
@@ -442,14 +491,24 @@ fn try_animation_fast() {
     let start_render_threshold = 0.97;
     // let start_render_threshold = 0.0;
     let extra_colors_max = 200;
+    let max_frames = 500;
+    animate(&mut rand::thread_rng(), width, height, percolation_type, display_width_mult, steps_per_frame, start_render_threshold, total_seconds, max_frames, extra_colors_max, &Output::Window, &mut ConsoleProgress).unwrap();
+}
+
+// Open random blocks until the grid percolates, drawing a frame every steps_per_frame openings
+// once start_render_threshold of the expected number of openings have been made. The largest
+// groups get their own colors.
+pub fn animate<R: Rng>(rng: &mut R, width: usize, height: usize, percolation_type: PercolationType, display_width_mult: f64, steps_per_frame: usize, start_render_threshold: f64, total_seconds: f64, max_frames: usize, extra_colors_max: usize, output: &Output, progress: &mut dyn Progress) -> DrawResult<()> {
     let approx_steps = (width * height) as f64 * 0.593;
     let start_render_step = (approx_steps * start_render_threshold) as usize;
     let approx_frames = (approx_steps / steps_per_frame as f64) * (1.0 - start_render_threshold);
     // let max_frames = (approx_frames * 1.5) as usize;
-    let max_frames = 500;
     let frame_seconds = total_seconds / approx_frames;
-    println!("width = {}, height = {}, approx_steps = {}, approx_frames = {}, frame_seconds = {}",
-             fc(width), fc(height), approx_steps, ff(approx_frames, 0), frame_seconds);
+    progress.counter("width", width as f64);
+    progress.counter("height", height as f64);
+    progress.counter("approx_steps", approx_steps);
+    progress.counter("approx_frames", approx_frames.round());
+    progress.counter("frame_seconds", frame_seconds);
     let display_width = width as f64 * display_width_mult;
     let display_height = height as f64 * display_width_mult;
     let mut perc = PercolationGrid::new(width, height, percolation_type);
@@ -461,17 +520,10 @@ fn try_animation_fast() {
     let mut color_grid_union_time = Duration::zero();
     let mut frame_time = Duration::zero();
     let start_time = Instant::now();
+    progress.phase_start("animate");
     let mut step_count = 0;
-    let mut colors = Vec::with_capacity(extra_colors_max);
-    colors.push(Color1::black());
-    colors.push(Color1::white());
-    colors.push(Color1::blue());
-    colors.push(Color1::red());
-    let color_min = 0.0;
-    let color_max = 1.0;
-    for _ in 0..extra_colors_max {
-        colors.push(Color1::from_rgb(rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max)));
-    }
+    let palette = percolation_palette(rng, extra_colors_max, 0.0, 1.0);
+    let slots = PercolationSlots::new(&palette);
     let mut largest_roots= vec![];
     while !perc.percolates() && frames.len() < max_frames {
         let x = rng.gen_range(0..width);
@@ -493,7 +545,7 @@ fn try_animation_fast() {
                     color_grid.record_events = false;
                     for color_y in 0..height {
                         for color_x in 0..width {
-                            color_grid.set_xy(color_x, color_y, block_color_index(&mut perc,&largest_roots, color_x, color_y, is_last_frame));
+                            color_grid.set_xy(color_x, color_y, block_color_index(&mut perc, &slots, &largest_roots, color_x, color_y, is_last_frame));
                         }
                     }
                     let color_grid_end_union_duration = perc.connections.union_time;
@@ -503,25 +555,29 @@ fn try_animation_fast() {
                     frames.push(color_grid.as_frame_color_index(display_width, display_height, frame_seconds));
                     frame_time += Instant::now() - frame_start_time;
                     if frames.len() % 20 == 0 {
-                        println!("frames = {}", fc(frames.len()));
+                        progress.counter("frames", frames.len() as f64);
                     }
                 }
             }
         }
     }
-    println!("step count = {}", step_count);
+    progress.counter("step count", step_count as f64);
     //perc.connections.print_components();
     let back_color = Color1::black();
     let additive = false;
-    println!("overall = {:?}; union = {:?}, connected (build) = {:?}, connected (draw) = {:?}, color grids = {:?}; frames = {:?}",
-             Instant::now() - start_time, perc.connections.union_time - color_grid_union_time, color_grid_union_time, perc.connections.is_connected_time, color_grid_time, frame_time);
-    Renderer::display_additive_with_colors("Percolation", display_width, display_height, back_color, frames, additive, colors.clone());
+    progress.timing("union", perc.connections.union_time - color_grid_union_time);
+    progress.timing("connected (build)", color_grid_union_time);
+    progress.timing("connected (draw)", perc.connections.is_connected_time);
+    progress.timing("color grids", color_grid_time);
+    progress.timing("frames", frame_time);
+    progress.phase_end("animate", Instant::now() - start_time);
+    output.try_display_player("Percolation", display_width, display_height, back_color, FramePlayer::new(back_color, frames, additive, (&palette).into()))
 }
 
 #[allow(dead_code)]
 fn animate_precalc() {
-    let start_time = Instant::now();
     let mut rng = rand::thread_rng();
+    let start_time = Instant::now();
     let run_to_completion_max_seconds = 30;
     let extra_colors_max = 200;
     let animation_seconds = 30;
@@ -549,7 +605,7 @@ fn animate_precalc() {
 
     // Precalculate the number of steps.
     let mut perc = PercolationGrid::new(width, height, percolation_type.clone());
-    let unions = perc.run_to_completion(run_to_completion_max_seconds);
+    let unions = perc.run_to_completion(&mut rng, run_to_completion_max_seconds, &mut ConsoleProgress);
     let step_count = unions.len();
     let start_render_step = (step_count as f64 * start_render_threshold) as usize;
     let render_step_count = (step_count - start_render_step) + 1;
@@ -563,16 +619,9 @@ fn animate_precalc() {
     let mut frames = vec![];
     let post_precalc_start_time = Instant::now();
 
-    let mut colors = Vec::with_capacity(extra_colors_max);
-    colors.push(Color1::black());
-    colors.push(Color1::white());
-    colors.push(Color1::blue());
-    colors.push(Color1::red());
-    let color_min = 0.0;
-    let color_max = 1.0;
-    for _ in 0..extra_colors_max {
-        colors.push(Color1::from_rgb(rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max),rng.gen_range(color_min..color_max)));
-    }
+    let palette = percolation_palette(&mut rng, extra_colors_max, 0.0, 1.0);
+
+    let slots = PercolationSlots::new(&palette);
 
     let mut color_grid_elapsed = Duration::zero();
     let mut frame_elapsed = Duration::zero();
@@ -592,7 +641,7 @@ fn animate_precalc() {
             color_grid.record_events = false;
             for color_y in 0..height {
                 for color_x in 0..width {
-                    color_grid.set_xy(color_x, color_y, block_color_index(&mut perc, &largest_roots, color_x, color_y, is_last_frame));
+                    color_grid.set_xy(color_x, color_y, block_color_index(&mut perc, &slots, &largest_roots, color_x, color_y, is_last_frame));
                 }
             }
             color_grid_elapsed += Instant::now() - color_grid_start_time;
@@ -615,7 +664,7 @@ fn animate_precalc() {
 
     let additive = false;
     let back_color = Color1::black();
-    Renderer::display_additive_with_colors("Percolation", display_width, display_height, back_color, frames, additive, colors.clone());
+    Renderer::display_additive_with_colors("Percolation", display_width, display_height, back_color, frames, additive, &palette);
 }
 
 #[inline]
@@ -642,26 +691,26 @@ fn block_color(perc: &mut PercolationGrid, extra_colors: &Vec<(usize, Color1)>,
 }
 
 #[inline]
-fn block_color_index(perc: &mut PercolationGrid, largest_roots: &Vec<usize>, x: usize, y: usize, is_last_frame: bool) -> usize {
+fn block_color_index(perc: &mut PercolationGrid, slots: &PercolationSlots, largest_roots: &Vec<usize>, x: usize, y: usize, is_last_frame: bool) -> usize {
     match perc.block_state(x, y) {
-        PercolationBlockState::Blocked => COLOR_INDEX_BLACK,
+        PercolationBlockState::Blocked => slots.blocked,
         PercolationBlockState::Open => {
             let node_index = perc.node_index(x, y);
             let node_root_index = perc.connections.root(node_index);
             if perc.connections.is_connected(perc.end_node_index, node_index) {
-                COLOR_INDEX_RED
+                slots.percolating
             } else {
                 if !is_last_frame {
                     for i in 0..largest_roots.len() {
                         //if perc.connections.is_connected(largest_roots[i], node_root_index) {
                         if node_root_index == largest_roots[i] {
-                            return COLOR_INDEX_FIRST_EXTRA + i;
+                            return slots.first_extra + i;
                         }
                     }
                 }
-                COLOR_INDEX_WHITE
+                slots.open
             }
         },
-        PercolationBlockState::Filled => COLOR_INDEX_BLUE,
+        PercolationBlockState::Filled => slots.filled,
     }
 }
@@ -6,6 +6,7 @@ use std::cmp::Reverse;
 use std::sync::{Arc, Mutex};
 
 use crate::*;
+use crate::progress::{Progress, ConsoleProgress};
 
 pub fn main() {
     // try_union_find();
@@ -307,9 +308,18 @@ fn try_union_find() {
 
 #[allow(dead_code)]
 fn compare_performance() {
-    let mut rng = rand::thread_rng();
-    for size in [50_000, 100_000, 200_000, 400_000].iter() {
+    compare_performance_sizes(&mut rand::thread_rng(), &[50_000, 100_000, 200_000, 400_000], &mut ConsoleProgress);
+}
+
+// Time every union-find algorithm with the same random unions and connection checks, as many of
+// each as there are nodes. QuickFind is quadratic so keep the sizes modest. Each size is a phase
+// named like "size = 1,000" with timings and counters for each algorithm.
+pub fn compare_performance_sizes<R: Rng>(rng: &mut R, sizes: &[usize], progress: &mut dyn Progress) {
+    for size in sizes.iter() {
         let size = *size;
+        let phase_name = format!("size = {}", fc(size));
+        progress.phase_start(&phase_name);
+        let start_time = Instant::now();
         let mut connected_count = 0;
         let mut qf = QuickFind::new(size);
         let mut qu = QuickUnion::new(size);
@@ -334,12 +344,18 @@ fn compare_performance() {
         }
         // uf.print_components();
         // qu.print_components();
-        println!("uf union = {:?}; qu union = {:?}; qu con = {:?}, qu depth = {}, wqu union = {:?}; wqu con = {:?}, wqu depth = {}, wqup union = {:?}; wqup con = {:?}, wqup depth = {}, connected_count = {}",
-                 qf.union_time,
-                 qu.union_time, qu.is_connected_time, ff(qu.tree_depth_mean(), 2),
-                 wqu.union_time, wqu.is_connected_time, ff(wqu.tree_depth_mean(), 2),
-                 wqup.union_time, wqup.is_connected_time, ff(wqup.tree_depth_mean(), 2),
-                 connected_count);
+        progress.timing("QuickFind union", qf.union_time);
+        progress.timing("QuickUnion union", qu.union_time);
+        progress.timing("QuickUnion is_connected", qu.is_connected_time);
+        progress.counter("QuickUnion depth", qu.tree_depth_mean() as f64);
+        progress.timing("WeightedQuickUnion union", wqu.union_time);
+        progress.timing("WeightedQuickUnion is_connected", wqu.is_connected_time);
+        progress.counter("WeightedQuickUnion depth", wqu.tree_depth_mean() as f64);
+        progress.timing("WeightedQuickUnion path compression union", wqup.union_time);
+        progress.timing("WeightedQuickUnion path compression is_connected", wqup.is_connected_time);
+        progress.counter("WeightedQuickUnion path compression depth", wqup.tree_depth_mean() as f64);
+        progress.counter("connected_count", connected_count as f64);
+        progress.phase_end(&phase_name, Instant::now() - start_time);
     }
 }
 
@@ -352,30 +368,38 @@ fn compare_performance_fastest() {
         sizes.push(size);
         size *= mult;
     }
+    compare_performance_fastest_sizes(&mut rand::thread_rng(), &sizes, &mut ConsoleProgress);
+}
+
+// Time only WeightedQuickUnion with path compression, which can handle far larger sizes. As with
+// compare_performance_sizes() each size is a phase.
+pub fn compare_performance_fastest_sizes<R: Rng>(rng: &mut R, sizes: &[usize], progress: &mut dyn Progress) {
     for size in sizes.iter() {
         let size = *size;
-        println!("\nsize = {}", fc(size));
+        let phase_name = format!("size = {}", fc(size));
+        progress.phase_start(&phase_name);
+        let phase_start_time = Instant::now();
 
         let mut wqu = WeightedQuickUnion::new(size, true);
 
-        let pairs = random_x_y_pairs(size, size);
+        let pairs = random_x_y_pairs(rng, size, size);
         let start_time = Instant::now();
         for pair in pairs {
             wqu.union(pair.0, pair.1);
         }
         let elapsed = Instant::now() - start_time;
-        let per_union = elapsed / size as u32;
-        println!("per union = {:?}; elapsed = {:?}", per_union, elapsed);
+        progress.timing("union", elapsed);
+        progress.timing("per union", elapsed / size as u32);
 
-        let pairs = random_x_y_pairs(size, size);
+        let pairs = random_x_y_pairs(rng, size, size);
         let start_time = Instant::now();
         for pair in pairs {
             wqu.is_connected(pair.0, pair.1);
         }
         let elapsed = Instant::now() - start_time;
-        let per_union = elapsed / size as u32;
-        println!("per is_connected = {:?}; elapsed = {:?}", per_union, elapsed);
-
+        progress.timing("is_connected", elapsed);
+        progress.timing("per is_connected", elapsed / size as u32);
+        progress.phase_end(&phase_name, Instant::now() - phase_start_time);
     }
     /*
     size = 10; inputs_elapsed = 7.4µs; elapsed = 9.6µs; connected_count = 4
@@ -407,7 +431,7 @@ fn compare_performance_fixed_size() {
     let union_chunk_size = union_count / steps;
     let mut wqu = WeightedQuickUnion::new(size, true);
     for _ in 0..steps {
-        let pairs = random_x_y_pairs(size, union_chunk_size);
+        let pairs = random_x_y_pairs(&mut rand::thread_rng(), size, union_chunk_size);
         let start_time = Instant::now();
         for i in 0..union_chunk_size {
             wqu.union(pairs[i].0, pairs[i].1);
@@ -437,7 +461,7 @@ fn time_mutex() {
     let elapsed_per_item = (Instant::now() - start_time) / size as u32;
     println!("create union (per item) = {:?}", elapsed_per_item);
 
-    let pairs = random_x_y_pairs(size, union_count);
+    let pairs = random_x_y_pairs(&mut rand::thread_rng(), size, union_count);
     let start_time = Instant::now();
     for pair in pairs {
         // 19ns to lock both.
@@ -528,8 +552,7 @@ fn time_partition() {
 }
 
 #[allow(dead_code)]
-pub fn random_x_y_pairs(size: usize, pair_count: usize) -> Vec<(usize, usize)> {
-    let mut rng = rand::thread_rng();
+pub fn random_x_y_pairs<R: Rng>(rng: &mut R, size: usize, pair_count: usize) -> Vec<(usize, usize)> {
     let mut pairs = Vec::with_capacity(pair_count);
     for _ in 0..pair_count {
         pairs.push((rng.gen_range(0..size), rng.gen_range(0..size)));
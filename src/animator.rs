@@ -1,4 +1,6 @@
 use crate::*;
+use crate::easing::Easing;
+use crate::timeline::Timeline;
 
 #[derive(Debug)]
 pub struct Animator {
@@ -10,11 +12,13 @@ pub struct Path {
     from_shape: Shape,
     transitions: Vec<Transition>,
     working_shape: Shape,
+    working_easing: Easing,
 }
 
 pub struct Transition {
     to_shape: Shape,
     frame_count: usize,
+    easing: Easing,
 }
 
 impl Animator {
@@ -35,7 +39,8 @@ impl Animator {
                 self.frames[frame_index].shapes.push(from_shape.clone());
             }
             for local_frame_index in 1..(transition.frame_count) {
-                let one_shape = from_shape.gradiant_one(&transition.to_shape, transition.frame_count, local_frame_index);
+                let t = transition.easing.apply(local_frame_index as f64 / transition.frame_count as f64);
+                let one_shape = from_shape.interpolate(&transition.to_shape, t);
                 //bg!(&one_shape);
                 self.frames[frame_index + local_frame_index].shapes.push(one_shape);
             }
@@ -45,11 +50,37 @@ impl Animator {
         }
     }
 
+    // Sample the timeline at this animator's frame rate, starting at the given frame.
+    pub fn add_timeline(&mut self, start_frame_index: usize, timeline: &Timeline) {
+        let frames = timeline.frames(1.0 / self.frame_seconds);
+        self.confirm_frame_capacity(start_frame_index + frames.len());
+        for (local_frame_index, frame) in frames.into_iter().enumerate() {
+            self.frames[start_frame_index + local_frame_index].shapes.extend(frame.shapes);
+        }
+    }
+
     pub fn show(&mut self, title: &str, width: f64, height: f64, back_color: crate::Color1) {
         let frames = std::mem::replace(&mut self.frames, vec![]);
         renderer_3::Renderer::display(title, width, height, back_color, frames)
     }
 
+    // For paths with *Fast shapes, which take their colors from the list.
+    pub fn show_with_colors(&mut self, title: &str, width: f64, height: f64, back_color: crate::Color1, colors: impl Into<Vec<Color1>>) {
+        let frames = std::mem::replace(&mut self.frames, vec![]);
+        renderer_3::Renderer::display_additive_with_colors(title, width, height, back_color, frames, false, colors)
+    }
+
+    // Moving shapes leave trails that fade by the given fraction each second.
+    pub fn show_persistent(&mut self, title: &str, width: f64, height: f64, back_color: crate::Color1, decay_per_second: f64) {
+        let frames = std::mem::replace(&mut self.frames, vec![]);
+        renderer_3::Renderer::display_persistent(title, width, height, back_color, frames, decay_per_second, vec![])
+    }
+
+    pub fn save_gif(&mut self, file_name: &str, width: f64, height: f64, back_color: crate::Color1) -> crate::DrawResult<()> {
+        let frames = std::mem::replace(&mut self.frames, vec![]);
+        gif_export::save_gif(file_name, width, height, back_color, frames)
+    }
+
     fn confirm_frame_capacity(&mut self, frame_count: usize) {
         if frame_count > self.frames.len() {
             for _ in self.frames.len()..frame_count {
@@ -65,6 +96,7 @@ impl Path {
             from_shape: from_shape.clone(),
             transitions: vec![],
             working_shape: from_shape.clone(),
+            working_easing: Easing::Linear,
         }
     }
 
@@ -88,8 +120,46 @@ impl Path {
         self
     }
 
+    pub fn color_index(mut self, color_index: usize) -> Self {
+        self.working_shape = self.working_shape.with_color_index(color_index);
+        self
+    }
+
+    pub fn thickness(mut self, thickness: f64) -> Self {
+        self.working_shape = self.working_shape.with_thickness(thickness);
+        self
+    }
+
+    pub fn endpoints(mut self, start: PointF64, end: PointF64) -> Self {
+        self.working_shape = self.working_shape.with_endpoints(start, end);
+        self
+    }
+
+    pub fn corners(mut self, top_left: PointF64, bottom_right: PointF64) -> Self {
+        self.working_shape = self.working_shape.with_corners(top_left, bottom_right);
+        self
+    }
+
+    pub fn points(mut self, points: Vec<PointF64>) -> Self {
+        self.working_shape = self.working_shape.with_points(points);
+        self
+    }
+
+    // Morph into a different shape, which may be of another kind. See Shape::interpolate().
+    pub fn shape(mut self, shape: &Shape) -> Self {
+        self.working_shape = shape.clone();
+        self
+    }
+
+    // The easing curve for this and any later transitions until it's changed again. The default is
+    // Easing::Linear.
+    pub fn ease(mut self, easing: Easing) -> Self {
+        self.working_easing = easing;
+        self
+    }
+
     pub fn trans(mut self, frame_count: usize) -> Self {
-        self.transitions.push(Transition::new(&self.working_shape, frame_count));
+        self.transitions.push(Transition::new(&self.working_shape, frame_count).with_easing(self.working_easing));
         self
     }
 }
@@ -99,6 +169,12 @@ impl Transition {
         Self {
             to_shape: to_shape.clone(),
             frame_count,
+            easing: Easing::Linear,
         }
     }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
 }
\ No newline at end of file
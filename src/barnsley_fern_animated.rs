@@ -3,22 +3,25 @@
 use rand::Rng;
 
 use crate::*;
-use crate::renderer_3::Renderer;
+use crate::output::Output;
+use crate::render_backend::FramePlayer;
 
 pub fn main() {
-    let point_count: usize = 100_000;
-    let width = 1000.0;
+    animate(&mut rand::thread_rng(), 100_000, 1000.0, 10.0, 1_000, &Output::Window).unwrap();
+    // animate_persistent(&mut rand::thread_rng(), 100_000, 1000.0, 10.0, 1_000, 0.2, &Output::Window).unwrap();
+}
+
+// Add batch_size points per frame, fading from green for the first points to red for the last.
+pub fn animate<R: Rng>(rng: &mut R, point_count: usize, width: f64, total_seconds: f64, batch_size: usize, output: &Output) -> DrawResult<()> {
     let height = width;
     let back_color = Color1::white();
     let point_radius = 0.5;
     let point_color: Color1 = Color256::from_rgb(50, 205, 50).into();
     let point_color_end = Color1::red();
-    let total_seconds = 10.0;
-    let batch_size = 1_000;
     let frame_count = point_count / batch_size;
     let frame_seconds = total_seconds / frame_count as f64;
 
-    let points = gen_points(width, height, point_count);
+    let points = gen_points(rng, width, height, point_count);
 
     let mut frames = vec![];
     for frame_index in 0..frame_count {
@@ -27,16 +30,39 @@ pub fn main() {
             let (x, y) = points[point_index];
             // let color = point_color.clone();
             let point_frame_index = point_index / batch_size;
-            let color = point_color.gradiant_one(&point_color_end, frame_count, point_frame_index);
+            let color = point_color.gradiant_one_in(&point_color_end, frame_count, point_frame_index, ColorSpace::Oklab);
             shapes.push(Shape::circle_xy(x, y, point_radius, color));
         }
         frames.push(Frame::new(shapes, frame_seconds));
     }
-    Renderer::display("Barnsley Fern", width, height, back_color, frames);
+    output.try_display("Barnsley Fern", width, height, back_color, frames)
+}
+
+// Draw only each new batch of points over the earlier ones as they slowly fade, so the most recent
+// points stand out as the fern builds up.
+pub fn animate_persistent<R: Rng>(rng: &mut R, point_count: usize, width: f64, total_seconds: f64, batch_size: usize, decay_per_second: f64, output: &Output) -> DrawResult<()> {
+    let height = width;
+    let back_color = Color1::white();
+    let point_radius = 0.5;
+    let point_color: Color1 = Color256::from_rgb(50, 205, 50).into();
+    let frame_count = point_count / batch_size;
+    let frame_seconds = total_seconds / frame_count as f64;
+
+    let points = gen_points(rng, width, height, point_count);
+
+    let mut frames = vec![];
+    for frame_index in 0..frame_count {
+        let shapes = points[(frame_index * batch_size)..((frame_index + 1) * batch_size)].iter()
+            .map(|(x, y)| Shape::circle_xy(*x, *y, point_radius, point_color.clone()))
+            .collect();
+        frames.push(Frame::new(shapes, frame_seconds));
+    }
+    let mut player = FramePlayer::new(back_color, frames, false, vec![]);
+    player.set_persistence(Some(decay_per_second));
+    output.try_display_player("Barnsley Fern", width, height, back_color, player)
 }
 
-fn gen_points(height: f64, width: f64, point_count: usize) -> Vec<(f64, f64)> {
-    let mut rng = rand::thread_rng();
+fn gen_points<R: Rng>(rng: &mut R, height: f64, width: f64, point_count: usize) -> Vec<(f64, f64)> {
     let max_iterations = point_count as u32;
     let height = height as i32;
     let width = width as i32;
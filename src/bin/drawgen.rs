@@ -0,0 +1,397 @@
+// Run the generators from the command line instead of editing main.rs. For example:
+//
+//   drawgen carpet --size 400 --mult 680 --output carpet.png
+//   drawgen percolation --width 200 --height 200 --kind center-out --radius 20 --output percolation.gif
+//   drawgen cellauto --rule game-of-life --wrap toroidal --gliders 500 --trail-decay 0.5
+//   drawgen union-find-bench --sizes 1000,100000,10000000
+//   drawgen fern --seed 42 --output fern.png
+//
+// Without --output the animation plays in a window. A .png or .svg file holds the last frame and a
+// .gif file holds the whole animation.
+
+use std::process;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::rngs::StdRng;
+
+use draw::DrawResult;
+use draw::algorithms::percolation::{self, PercolationType};
+use draw::algorithms::union_find;
+use draw::carpet::carpet;
+use draw::cave_cell;
+use draw::cell_auto::{CellAutoAlgorithm, CellAutoGrid, CellAutoStartFill};
+use draw::barnsley_fern_animated;
+use draw::gradient::Gradient;
+use draw::grid::{count_to_color_black_white, GridNeighborType, GridWrappingStyle};
+use draw::output::Output;
+use draw::palette::Colormap;
+use draw::progress::{ConsoleProgress, SilentProgress};
+use draw::try_fractal;
+use draw::voronoi::parabola;
+
+#[derive(Parser)]
+#[command(name = "drawgen", about = "Run the fractal, percolation, automaton and Voronoi generators.")]
+struct Cli {
+    /// A .png, .gif or .svg file to write instead of showing a window.
+    #[arg(short, long, global = true)]
+    output: Option<String>,
+
+    /// Seed the random number generator to repeat an earlier run. Each run prints the seed it used.
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// A Sierpinski-like carpet made by overlapping squares of shrinking sizes.
+    Carpet {
+        /// Width and height of the grid in cells.
+        #[arg(long, value_parser = positive_usize, default_value_t = 400)]
+        size: usize,
+        /// The smallest square to draw.
+        #[arg(long, value_parser = positive_usize, default_value_t = 5)]
+        min_length: usize,
+        /// The size of each square relative to the one before, in thousandths.
+        #[arg(long, value_parser = positive_usize, default_value_t = 680)]
+        mult: usize,
+        /// Keep the counts modulo this number.
+        #[arg(long, value_parser = positive_usize)]
+        modulus: Option<usize>,
+        /// Display pixels per grid cell.
+        #[arg(long, value_parser = positive_f64, default_value_t = 2.0)]
+        scale: f64,
+        /// Color the counts through a colormap instead of black and white for even and odd.
+        #[arg(long, value_enum)]
+        colormap: Option<ColormapArg>,
+    },
+    /// Open random blocks until there's a path across the grid, coloring the largest groups.
+    Percolation {
+        #[arg(long, value_parser = positive_usize, default_value_t = 200)]
+        width: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 200)]
+        height: usize,
+        /// Which sides have to be connected.
+        #[arg(long, value_enum, default_value_t = PercolationArg::TopBottom)]
+        kind: PercolationArg,
+        /// The radius of the open starting areas for top-left-bottom-right and center-out.
+        #[arg(long, default_value_t = 10)]
+        radius: usize,
+        /// Display pixels per grid cell.
+        #[arg(long, value_parser = positive_f64, default_value_t = 4.0)]
+        scale: f64,
+        /// Blocks to open between frames.
+        #[arg(long, value_parser = positive_usize, default_value_t = 25)]
+        steps_per_frame: usize,
+        /// The fraction of the expected openings to make before the first frame, from 0 to 1.
+        #[arg(long, value_parser = fraction, default_value_t = 0.7)]
+        start_threshold: f64,
+        #[arg(long, value_parser = positive_f64, default_value_t = 10.0)]
+        seconds: f64,
+        #[arg(long, value_parser = positive_usize, default_value_t = 500)]
+        max_frames: usize,
+        /// How many of the largest groups get their own colors.
+        #[arg(long, default_value_t = 200)]
+        group_colors: usize,
+    },
+    /// A cellular automaton, by default the rules that carve out caves.
+    Cellauto {
+        #[arg(long, value_parser = positive_usize, default_value_t = 400)]
+        width: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 200)]
+        height: usize,
+        #[arg(long, value_enum, default_value_t = RuleArg::Original)]
+        rule: RuleArg,
+        #[arg(long, value_enum, default_value_t = NeighborhoodArg::Moore)]
+        neighborhood: NeighborhoodArg,
+        /// How many cells away a neighbor can be.
+        #[arg(long, default_value_t = 1)]
+        range: usize,
+        #[arg(long, value_enum, default_value_t = WrapArg::None)]
+        wrap: WrapArg,
+        /// The chance that each cell starts open, from 0 to 1.
+        #[arg(long, value_parser = fraction, default_value_t = 0.4)]
+        open_fraction: f64,
+        /// Start with this many gliders instead of random cells.
+        #[arg(long)]
+        gliders: Option<usize>,
+        #[arg(long, value_parser = positive_usize, default_value_t = 12)]
+        steps: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 30)]
+        seconds: usize,
+        /// Let closed cells fade out, losing this fraction of their brightness each second. Above 0
+        /// and up to 1, where 1 clears them at once.
+        #[arg(long, value_parser = fraction_above_zero)]
+        trail_decay: Option<f64>,
+    },
+    /// Caves from a cellular automaton on a grid that wraps around, one frame per step.
+    Cave {
+        #[arg(long, value_parser = positive_usize, default_value_t = 400)]
+        width: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 200)]
+        height: usize,
+        #[arg(long, value_enum, default_value_t = RuleArg::GameOfLife)]
+        rule: RuleArg,
+        /// The chance that each cell starts open, from 0 to 1.
+        #[arg(long, value_parser = fraction, default_value_t = 0.5)]
+        open_fraction: f64,
+        #[arg(long, value_parser = positive_usize, default_value_t = 600)]
+        steps: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 120)]
+        seconds: usize,
+    },
+    /// The Barnsley fern, built up a batch of points at a time.
+    Fern {
+        #[arg(long, value_parser = positive_usize, default_value_t = 100_000)]
+        points: usize,
+        /// Width and height of the image.
+        #[arg(long, value_parser = positive_f64, default_value_t = 1000.0)]
+        size: f64,
+        #[arg(long, value_parser = positive_f64, default_value_t = 10.0)]
+        seconds: f64,
+        /// Points added per frame.
+        #[arg(long, value_parser = positive_usize, default_value_t = 1_000)]
+        batch: usize,
+        /// Draw only the new points each frame while the older ones fade, losing this fraction of
+        /// their strength each second. Above 0 and up to 1, where 1 clears them at once.
+        #[arg(long, value_parser = fraction_above_zero)]
+        trail_decay: Option<f64>,
+    },
+    /// Jump part of the way toward randomly chosen corners of a polygon, plotting each point.
+    ChaosGame {
+        #[arg(long, value_parser = positive_usize, default_value_t = 50_000)]
+        points: usize,
+        /// Width and height of the image.
+        #[arg(long, value_parser = positive_f64, default_value_t = 1000.0)]
+        size: f64,
+        /// The number of corners.
+        #[arg(long, value_parser = positive_usize, default_value_t = 3)]
+        anchors: usize,
+        /// How far to move toward the chosen corner, as a fraction of the distance above 0 and up
+        /// to 1.
+        #[arg(long, value_parser = fraction_above_zero, default_value_t = 0.5)]
+        distance: f64,
+        #[arg(long, value_parser = positive_f64, default_value_t = 10.0)]
+        seconds: f64,
+        /// Points added per frame.
+        #[arg(long, value_parser = positive_usize, default_value_t = 1_000)]
+        batch: usize,
+        /// Draw only the new points each frame on top of the earlier ones.
+        #[arg(long)]
+        additive: bool,
+    },
+    /// The beach line of Fortune's Voronoi algorithm as the sweep line moves down.
+    Voronoi {
+        #[arg(long, value_parser = positive_usize, default_value_t = 1_600)]
+        width: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 800)]
+        height: usize,
+        /// The number of random sites.
+        #[arg(long, value_parser = positive_usize, default_value_t = 100)]
+        points: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 400)]
+        frames: usize,
+        #[arg(long, value_parser = positive_usize, default_value_t = 30)]
+        seconds: usize,
+    },
+    /// Time union-find with random unions and connection checks, as many of each as nodes.
+    UnionFindBench {
+        /// Comma-separated node counts.
+        #[arg(long, value_delimiter = ',', value_parser = positive_usize, default_values_t = vec![1_000, 10_000, 100_000, 1_000_000])]
+        sizes: Vec<usize>,
+        /// Compare every algorithm instead of only weighted quick union with path compression.
+        /// QuickFind is quadratic, so keep the sizes small.
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColormapArg {
+    Viridis,
+    Magma,
+    Grayscale,
+    Cyclic,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PercolationArg {
+    TopBottom,
+    TopLeftBottomRight,
+    CenterOut,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RuleArg {
+    Original,
+    #[value(name = "b3s1234")]
+    B3S1234,
+    #[value(name = "b3s12345")]
+    B3S12345,
+    GameOfLife,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NeighborhoodArg {
+    Moore,
+    VonNeumann,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WrapArg {
+    None,
+    Horizontal,
+    Vertical,
+    Toroidal,
+}
+
+fn main() {
+    if let Err(error) = run(Cli::parse()) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> DrawResult<()> {
+    let output = Output::from_file_name(cli.output.as_deref())?;
+    let seed = draw::pick_seed(cli.seed);
+    match cli.command {
+        Command::Carpet { size, min_length, mult, modulus, scale, colormap } => {
+            let carpet = carpet::create_one(size, min_length, mult, modulus);
+            match colormap {
+                Some(colormap) => {
+                    let gradient = Gradient::from_colormap(colormap.colormap()).histogram_of(carpet.grid());
+                    carpet.draw_to(scale, &|count: &usize| gradient.color(*count as f64), &output)
+                },
+                None => carpet.draw_to(scale, &|count| count_to_color_black_white(count), &output),
+            }
+        },
+        Command::Percolation { width, height, kind, radius, scale, steps_per_frame, start_threshold, seconds, max_frames, group_colors } => {
+            let percolation_type = match kind {
+                PercolationArg::TopBottom => PercolationType::TopBottom,
+                PercolationArg::TopLeftBottomRight => PercolationType::TopLeftBottomRight { radius },
+                PercolationArg::CenterOut => PercolationType::CenterOut { radius },
+            };
+            percolation::animate(&mut seeded_rng(seed), width, height, percolation_type, scale, steps_per_frame, start_threshold, seconds, max_frames, group_colors, &output, &mut SilentProgress)
+        },
+        Command::Cellauto { width, height, rule, neighborhood, range, wrap, open_fraction, gliders, steps, seconds, trail_decay } => {
+            let neighbor_type = match neighborhood {
+                NeighborhoodArg::Moore => GridNeighborType::Moore { range },
+                NeighborhoodArg::VonNeumann => GridNeighborType::VonNeuman { range },
+            };
+            let wrapping_style = match wrap {
+                WrapArg::None => GridWrappingStyle::None,
+                WrapArg::Horizontal => GridWrappingStyle::Horizontal,
+                WrapArg::Vertical => GridWrappingStyle::Vertical,
+                WrapArg::Toroidal => GridWrappingStyle::Toroidal,
+            };
+            let start_fill = match gliders {
+                Some(count) => CellAutoStartFill::Glider { count },
+                None => CellAutoStartFill::Random { pct: open_fraction },
+            };
+            let mut grid = CellAutoGrid::new(&mut seeded_rng(seed), width, height, rule.cell_auto_algorithm(), neighbor_type, wrapping_style, start_fill);
+            grid.animate_persistent(steps, seconds, trail_decay, &output)
+        },
+        Command::Cave { width, height, rule, open_fraction, steps, seconds } => {
+            cave_cell::run(&mut seeded_rng(seed), width, height, rule.cave_algorithm(), open_fraction, steps, seconds, &output)
+        },
+        Command::Fern { points, size, seconds, batch, trail_decay } => {
+            match trail_decay {
+                Some(decay_per_second) => barnsley_fern_animated::animate_persistent(&mut seeded_rng(seed), points, size, seconds, batch, decay_per_second, &output),
+                None => barnsley_fern_animated::animate(&mut seeded_rng(seed), points, size, seconds, batch, &output),
+            }
+        },
+        Command::ChaosGame { points, size, anchors, distance, seconds, batch, additive } => {
+            let anchors = try_fractal::polygon_anchors(anchors, size, 20.0);
+            try_fractal::chaos_game(&mut seeded_rng(seed), size, anchors, distance, points, batch, seconds, additive, &output)
+        },
+        Command::Voronoi { width, height, points, frames, seconds } => {
+            parabola::draw_parabolas(&mut seeded_rng(seed), width, height, points, seconds, frames, &output)
+        },
+        Command::UnionFindBench { sizes, all } => {
+            if output != Output::Window {
+                eprintln!("union-find-bench only prints timings, so --output is ignored.");
+            }
+            if all {
+                union_find::compare_performance_sizes(&mut seeded_rng(seed), &sizes, &mut ConsoleProgress);
+            } else {
+                union_find::compare_performance_fastest_sizes(&mut seeded_rng(seed), &sizes, &mut ConsoleProgress);
+            }
+            Ok(())
+        },
+    }
+}
+
+// clap's ranged parsers don't cover usize, so zero is turned away here along with non-numbers.
+fn positive_usize(text: &str) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(0) => Err("must be greater than zero".to_string()),
+        Ok(value) => Ok(value),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn positive_f64(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(value),
+        Ok(_) => Err("must be greater than zero".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn fraction(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(value) if (0.0..=1.0).contains(&value) => Ok(value),
+        Ok(_) => Err("must be from 0 to 1".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Above zero and up to one, as FramePlayer::set_persistence() requires of a decay.
+fn fraction_above_zero(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(value) if value > 0.0 && value <= 1.0 => Ok(value),
+        Ok(_) => Err("must be above 0 and no more than 1".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Shows the seed so that a run without --seed can be repeated.
+fn seeded_rng(seed: u64) -> StdRng {
+    println!("seed = {}", seed);
+    draw::seeded_rng(Some(seed))
+}
+
+impl ColormapArg {
+    fn colormap(&self) -> Colormap {
+        match self {
+            ColormapArg::Viridis => Colormap::Viridis,
+            ColormapArg::Magma => Colormap::Magma,
+            ColormapArg::Grayscale => Colormap::Grayscale,
+            ColormapArg::Cyclic => Colormap::Cyclic,
+        }
+    }
+}
+
+impl RuleArg {
+    fn cell_auto_algorithm(&self) -> CellAutoAlgorithm {
+        match self {
+            RuleArg::Original => CellAutoAlgorithm::Original,
+            RuleArg::B3S1234 => CellAutoAlgorithm::B3S1234,
+            RuleArg::B3S12345 => CellAutoAlgorithm::B3S12345,
+            RuleArg::GameOfLife => CellAutoAlgorithm::GameOfLife,
+        }
+    }
+
+    fn cave_algorithm(&self) -> cave_cell::Algorithm {
+        match self {
+            RuleArg::Original => cave_cell::Algorithm::Original,
+            RuleArg::B3S1234 => cave_cell::Algorithm::B3S1234,
+            RuleArg::B3S12345 => cave_cell::Algorithm::B3S12345,
+            RuleArg::GameOfLife => cave_cell::Algorithm::GameOfLife,
+        }
+    }
+}
+
@@ -0,0 +1,230 @@
+// A world-to-screen transform applied when frames are drawn, so that frames can be built in
+// whatever coordinates suit the model and then viewed at any position, zoom, rotation and output
+// size. The transform is
+//
+//   screen = (scale * rotate(rotation) * world) + offset
+//
+// with a uniform scale so that circles stay circles and line thicknesses scale with everything else.
+
+use crate::*;
+use crate::render_backend::RenderBackend;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    offset_x: f64,
+    offset_y: f64,
+    scale: f64,
+    rotation: f64,
+}
+
+// Wraps another backend, transforming every shape with the camera before passing it on.
+pub struct CameraBackend<'a, B: RenderBackend> {
+    backend: &'a mut B,
+    camera: Camera,
+}
+
+pub fn main() {
+    try_camera();
+}
+
+impl Camera {
+    // World coordinates are screen coordinates.
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 1.0, 0.0)
+    }
+
+    pub fn new(offset_x: f64, offset_y: f64, scale: f64, rotation: f64) -> Self {
+        assert!(scale > 0.0);
+        Self {
+            offset_x,
+            offset_y,
+            scale,
+            rotation,
+        }
+    }
+
+    // Show the given rectangle of the world as large as possible on a screen of the given size,
+    // centered and keeping its proportions. With the world rectangle set to the size the frames
+    // were made for, this renders the same frames at a different resolution.
+    pub fn view(world_x: f64, world_y: f64, world_width: f64, world_height: f64, screen_width: f64, screen_height: f64) -> Self {
+        let scale = (screen_width / world_width).min(screen_height / world_height);
+        let offset_x = ((screen_width - (world_width * scale)) / 2.0) - (world_x * scale);
+        let offset_y = ((screen_height - (world_height * scale)) / 2.0) - (world_y * scale);
+        Self::new(offset_x, offset_y, scale, 0.0)
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.offset_x == 0.0 && self.offset_y == 0.0 && self.scale == 1.0 && self.rotation == 0.0
+    }
+
+    // Move the view by a distance in screen units.
+    pub fn pan(&mut self, screen_dx: f64, screen_dy: f64) {
+        self.offset_x += screen_dx;
+        self.offset_y += screen_dy;
+    }
+
+    // Zoom in (factor > 1.0) or out while keeping the world point under the given screen point in
+    // place, as when zooming with the mouse wheel.
+    pub fn zoom_at(&mut self, screen_x: f64, screen_y: f64, factor: f64) {
+        assert!(factor > 0.0);
+        self.offset_x = screen_x + ((self.offset_x - screen_x) * factor);
+        self.offset_y = screen_y + ((self.offset_y - screen_y) * factor);
+        self.scale *= factor;
+    }
+
+    // Rotate clockwise on the screen around the given screen point.
+    pub fn rotate_at(&mut self, screen_x: f64, screen_y: f64, radians: f64) {
+        let (sin, cos) = radians.sin_cos();
+        let (dx, dy) = (self.offset_x - screen_x, self.offset_y - screen_y);
+        self.offset_x = screen_x + ((dx * cos) - (dy * sin));
+        self.offset_y = screen_y + ((dx * sin) + (dy * cos));
+        self.rotation += radians;
+    }
+
+    #[inline]
+    pub fn to_screen(&self, world_x: f64, world_y: f64) -> (f64, f64) {
+        if self.rotation == 0.0 {
+            ((world_x * self.scale) + self.offset_x, (world_y * self.scale) + self.offset_y)
+        } else {
+            let (sin, cos) = self.rotation.sin_cos();
+            let x = (((world_x * cos) - (world_y * sin)) * self.scale) + self.offset_x;
+            let y = (((world_x * sin) + (world_y * cos)) * self.scale) + self.offset_y;
+            (x, y)
+        }
+    }
+
+    pub fn to_world(&self, screen_x: f64, screen_y: f64) -> (f64, f64) {
+        let (x, y) = ((screen_x - self.offset_x) / self.scale, (screen_y - self.offset_y) / self.scale);
+        let (sin, cos) = self.rotation.sin_cos();
+        ((x * cos) + (y * sin), (y * cos) - (x * sin))
+    }
+
+    #[inline]
+    pub fn point_to_screen(&self, point: &PointF64) -> PointF64 {
+        let (x, y) = self.to_screen(point.x, point.y);
+        PointF64::new(x, y)
+    }
+}
+
+impl<'a, B: RenderBackend> CameraBackend<'a, B> {
+    pub fn new(backend: &'a mut B, camera: Camera) -> Self {
+        Self {
+            backend,
+            camera,
+        }
+    }
+
+    fn points_to_screen(&self, points: &[PointF64]) -> Vec<PointF64> {
+        points.iter().map(|point| self.camera.point_to_screen(point)).collect()
+    }
+}
+
+impl<B: RenderBackend> RenderBackend for CameraBackend<'_, B> {
+    fn clear(&mut self, color: [f32; 4]) {
+        self.backend.clear(color);
+    }
+
+    fn circle(&mut self, center_x: f64, center_y: f64, radius: f64, color: [f32; 4]) {
+        let (x, y) = self.camera.to_screen(center_x, center_y);
+        self.backend.circle(x, y, radius * self.camera.scale, color);
+    }
+
+    fn line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4]) {
+        let (x0, y0) = self.camera.to_screen(x0, y0);
+        let (x1, y1) = self.camera.to_screen(x1, y1);
+        self.backend.line(x0, y0, x1, y1, thickness * self.camera.scale, color);
+    }
+
+    // A rotated rectangle is no longer axis-aligned, so it becomes a polygon.
+    fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]) {
+        if self.camera.rotation == 0.0 {
+            let (screen_x, screen_y) = self.camera.to_screen(x, y);
+            self.backend.rectangle(screen_x, screen_y, width * self.camera.scale, height * self.camera.scale, color);
+        } else {
+            let corners = [PointF64::new(x, y), PointF64::new(x + width, y), PointF64::new(x + width, y + height), PointF64::new(x, y + height)];
+            self.polygon(&corners, color);
+        }
+    }
+
+    fn polygon(&mut self, points: &[PointF64], color: [f32; 4]) {
+        let points = self.points_to_screen(points);
+        self.backend.polygon(&points, color);
+    }
+
+    // The whole output fades, not just the part the camera is looking at.
+    fn fade(&mut self, color: [f32; 4], amount: f32) {
+        self.backend.fade(color, amount);
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.backend.blend_mode()
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.backend.set_blend_mode(mode);
+    }
+
+    fn polyline(&mut self, points: &[PointF64], thickness: f64, color: [f32; 4]) {
+        let points = self.points_to_screen(points);
+        self.backend.polyline(&points, thickness * self.camera.scale, color);
+    }
+
+    // An affine transform of a Bézier curve's control points gives the transformed curve, so the
+    // path can be passed on without flattening it.
+    fn path(&mut self, start: PointF64, segments: &[PathSegment], style: PathStyle, color: [f32; 4]) {
+        let camera = self.camera;
+        let segments: Vec<PathSegment> = segments.iter()
+            .map(|segment| match segment {
+                PathSegment::LineTo { to } => PathSegment::LineTo { to: camera.point_to_screen(to) },
+                PathSegment::QuadraticTo { control, to } => PathSegment::QuadraticTo {
+                    control: camera.point_to_screen(control),
+                    to: camera.point_to_screen(to),
+                },
+                PathSegment::CubicTo { control_1, control_2, to } => PathSegment::CubicTo {
+                    control_1: camera.point_to_screen(control_1),
+                    control_2: camera.point_to_screen(control_2),
+                    to: camera.point_to_screen(to),
+                },
+            })
+            .collect();
+        let style = match style {
+            PathStyle::Fill => PathStyle::Fill,
+            PathStyle::Stroke(thickness) => PathStyle::Stroke(thickness * camera.scale),
+        };
+        self.backend.path(camera.point_to_screen(&start), &segments, style, color);
+    }
+}
+
+fn try_camera() {
+    // A large grid viewed zoomed into its top left corner and then as a whole at a smaller size.
+    let (grid_size, world_size) = (400, 4_000.0);
+    let mut grid = grid::Grid::new(grid_size, grid_size, 0);
+    for y in 0..grid_size {
+        for x in 0..grid_size {
+            if (x ^ y) % 7 == 0 {
+                grid.set_xy(x, y, 1);
+            }
+        }
+    }
+    let frame = grid.as_frame_color_index(world_size, world_size, 0.1);
+    let colors = vec![Color1::black(), Color1::white()];
+    let screen_size = 400.0;
+
+    let corner = Camera::view(0.0, 0.0, 300.0, 300.0, screen_size, screen_size);
+    let mut rend = software_renderer::SoftwareRenderer::new(screen_size, screen_size, Color1::black());
+    render_backend::draw_shapes(&mut CameraBackend::new(&mut rend, corner), &frame.shapes, &colors);
+    rend.save("Test_camera_corner.png");
+
+    let whole = Camera::view(0.0, 0.0, world_size, world_size, screen_size, screen_size);
+    let mut rend = software_renderer::SoftwareRenderer::new(screen_size, screen_size, Color1::black());
+    render_backend::draw_shapes(&mut CameraBackend::new(&mut rend, whole), &frame.shapes, &colors);
+    rend.save("Test_camera_whole.png");
+}
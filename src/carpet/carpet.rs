@@ -3,9 +3,13 @@ use std::time::Instant;
 use crate::*;
 use renderer_3::*;
 use crate::grid::*;
-// use std::sync::mpsc;
-use std::mem;
-// use std::collections::BTreeMap;
+use crate::gradient::Gradient;
+use crate::output::Output;
+use crate::frame_source;
+use crate::progress::{Progress, ConsoleProgress};
+use std::sync::mpsc;
+use std::{thread, mem};
+use std::collections::{BTreeMap, VecDeque};
 use std::path::Path;
 use bit_vec::BitVec;
 #[allow(unused_imports)]
@@ -180,20 +184,20 @@ impl Carpet {
     pub fn write_grid(&self) {
         let full_file_name = Self::full_file_name(self.size, self.min_length, self.mult,self.modulus,None);
         //let start_time = Instant::now();
-        self.grid.write(&full_file_name);
+        self.grid.write(&full_file_name).unwrap();
         //rintln!("Carpet::write_grid({}): {:?}", full_file_name, Instant::now() - start_time);
     }
 
     pub fn write_grid_labeled(&self, label: &str) {
         let full_file_name = Self::full_file_name(self.size, self.min_length, self.mult, self.modulus, Some(label));
         //let start_time = Instant::now();
-        self.grid.write(&full_file_name);
+        self.grid.write(&full_file_name).unwrap();
         //rintln!("Carpet::write_grid({}): {:?}", full_file_name, Instant::now() - start_time);
     }
 
     pub fn read_grid_optional(size: usize, min_length: usize, mult: usize, modulus: Option<usize>) -> Option<Grid<usize>> {
         let full_file_name = Self::full_file_name(size, min_length, mult, modulus,None);
-        Grid::read_optional(&full_file_name)
+        Grid::read_optional(&full_file_name).unwrap()
     }
 
     pub fn read_or_make_grid(size: usize, min_length: usize, mult: usize, modulus: Option<usize>) -> Grid<usize> {
@@ -206,7 +210,7 @@ impl Carpet {
                 let mut carpet = Carpet::new(size, min_length, mult, modulus);
                 carpet.go();
                 let full_file_name = Self::full_file_name(size, min_length, mult, modulus, None);
-                carpet.grid.write(&full_file_name);
+                carpet.grid.write(&full_file_name).unwrap();
                 carpet.grid
             }
         }
@@ -230,7 +234,18 @@ impl Carpet {
         bits
     }
 
+    pub fn grid(&self) -> &Grid<usize> {
+        &self.grid
+    }
+
     pub fn draw(&self, display_width_mult: f64) {
+        self.draw_to(display_width_mult, &|count| count_to_color_black_white(count), &Output::Window).unwrap();
+    }
+
+    // The background is the color for a count of zero.
+    pub fn draw_to<F>(&self, display_width_mult: f64, color_func: &F, output: &Output) -> DrawResult<()>
+        where F: Fn(&usize) -> Color1
+    {
         // let start_time = Instant::now();
         let display_width = self.size as f64 * display_width_mult;
         let display_height = display_width;
@@ -238,12 +253,11 @@ impl Carpet {
         // let start_time = Instant::now();
         // let frames = carpet.grid.events_to_frames(frame_count, display_width, display_height, frame_seconds, count_to_color_black_white);
         // let func: FnOnce(&usize) -> Color1 = |count| count_to_color_gray(count, min, max);
-        let frames = self.grid.to_final_frame(display_width, display_height, frame_seconds, &|count| count_to_color_black_white(count));
+        let frames = self.grid.to_final_frame(display_width, display_height, frame_seconds, color_func);
         // println!("create frames seconds = {}", (Instant::now() - start_time).as_secs());
 
-        let back_color = count_to_color_black_white(&0);
-        let additive = false;
-        Renderer::display_additive("Carpet", display_width, display_height, back_color, frames, additive);
+        let back_color = color_func(&0);
+        output.try_display("Carpet", display_width, display_height, back_color, frames)
     }
 
 }
@@ -334,6 +348,8 @@ fn try_draw_wedge() {
     draw_one(size, display_width_mult, min_length, mult,&CarpetAlgorithm::Wedge);
 }
 
+*/
+
 #[allow(dead_code)]
 fn try_animation() {
     // animate_mult(200, 4.0, 2.0, 7, 0.675, 0.685, 0.001)
@@ -342,7 +358,7 @@ fn try_animation() {
     // animate_mult_parallel(400, 2.0, 1.0, 3, 0.60, 0.65, 0.001)
     // animate_mult_parallel(800, 2, 1.0, 1.0, 3, 0.63, 0.68, 0.001, 50);
     // animate_mult_parallel(400, 2.0, 1.0, 3, 0.5, 0.60, 0.001)
-    animate_mult_parallel(400, 2, 2.0, 2.0, 3, 650, 800, 1, 50);
+    animate_mult_parallel(400, 2, 2.0, 2.0, 3, 650, 800, 1, 50, Box::new(ConsoleProgress));
     // animate_show_existing(400, 2.0, 2.0, 3, 0.7, 0.9, 0.001);
     // animate_mult_parallel(200, 5, 4.0, 1.0, 7, 0.6, 0.8, 0.002, 1_000);
     // animate_mult_parallel(200, 5, 2.0, 1.0, 7, 0.8, 0.9, 0.002, 1_000);
@@ -356,113 +372,115 @@ fn try_animation() {
 fn animate_mult(size: usize, display_width_mult: f64, frame_seconds: f64, min_length: usize, mult_min: usize, mult_max: usize, mult_step: usize) {
     let display_width = size as f64 * display_width_mult;
     let display_height = display_width;
-    let mut frames = vec![];
-    let mut mults = vec![];
-    let mut mult = mult_min;
-    while mult <= mult_max {
-        mults.push(mult);
-        mult += mult_step;
-    }
-    let mut prev_grid = None;
-    let mut skipped_count = 0;
+    let mults = mult_range(mult_min, mult_max, mult_step);
+    let mult_count = mults.len();
+    // Each carpet is built when the display asks for its frame, skipping any that are the same as
+    // the one before.
+    let mut mults = mults.into_iter();
+    let mut prev_grid: Option<Grid<usize>> = None;
+    let mut frame_count = 0;
     let start_time = Instant::now();
-    for mult in mults.iter() {
-        let carpet = create_one(size, min_length, *mult,&CarpetAlgorithm::Wedge);
-        if prev_grid.is_none() || prev_grid.unwrap() != carpet.grid {
-            frames.push(carpet.grid.as_frame(display_width, display_height, frame_seconds, &|count| count_to_color_black_white(count)));
-        } else {
-            skipped_count += 1;
+    let source = frame_source::from_fn(move || {
+        for mult in mults.by_ref() {
+            let carpet = create_one(size, min_length, mult, None);
+            if prev_grid.as_ref() != Some(&carpet.grid) {
+                let frame = carpet.grid.as_frame(display_width, display_height, frame_seconds, &|count| count_to_color_black_white(count));
+                prev_grid = Some(carpet.grid);
+                frame_count += 1;
+                return Some(frame);
+            }
         }
-        prev_grid = Some(carpet.grid.clone());
-    }
-    dbg!(Instant::now() - start_time);
-    println!("frame count = {}, skipped_count = {}", fc(frames.len()), fc(skipped_count));
+        dbg!(Instant::now() - start_time);
+        println!("frame count = {}, skipped_count = {}", fc(frame_count), fc(mult_count - frame_count));
+        None
+    });
     let back_color = count_to_color_black_white(&0);
     let additive = false;
-    Renderer::display_additive("Carpet", display_width, display_height, back_color, frames, additive);
+    Renderer::display_source("Carpet", display_width, display_height, back_color, source, additive, vec![]);
 }
 
+// The progress is boxed because the frame source, which outlives this call's borrows, keeps
+// reporting as the grids arrive.
 #[allow(dead_code)]
-fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_mult: f64, frame_seconds: f64, min_length: usize, mult_min: usize, mult_max: usize, mult_step: usize, threads_max: usize) {
+fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_mult: f64, frame_seconds: f64, min_length: usize, mult_min: usize, mult_max: usize, mult_step: usize, threads_max: usize, mut progress: Box<dyn Progress>) {
     let display_width = size as f64 * display_width_mult;
     let display_height = display_width;
     let start_time = Instant::now();
     let (tx, rx) = mpsc::channel();
     let mut threads = Vec::new();
-    let mut frame_index = 0;
+    // The frame indexes of the grids that will arrive on the channel, in order.
+    let mut frame_indexes = VecDeque::new();
 
-    let mut mults = vec![];
-    let mut mult = mult_min;
-    while mult <= mult_max {
-        mults.push(mult);
-        mult += mult_step;
-    }
+    let mults = mult_range(mult_min, mult_max, mult_step);
 
     let mut thread_count = 0;
-    for mult in mults.iter() {
+    for (frame_index, mult) in mults.iter().enumerate() {
         let mult = *mult;
-        let grid_exists = Carpet::grid_exists(size, min_length, mult);
+        let grid_exists = Carpet::grid_exists(size, min_length, mult, None);
         if !grid_exists {
             if thread_count < threads_max {
-                let file_name = Carpet::full_file_name(size, min_length, mult, None);
-                println!("Starting to build {}", file_name);
+                let file_name = Carpet::full_file_name(size, min_length, mult, None, None);
+                progress.message(&format!("Starting to build {}", file_name));
             }
         }
         if grid_exists || thread_count < threads_max {
             let thread_tx = tx.clone();
             let thread = thread::spawn(move || {
-                // let carpet = create_one(size, min_length, mult,CarpetAlgorithm::Wedge);
-                // thread_tx.send((frame_index, carpet.grid)).unwrap();
-                let grid = Carpet::read_or_make_grid(size, min_length, mult);
-                thread_tx.send((frame_index, grid)).unwrap();
+                let grid = Carpet::read_or_make_grid(size, min_length, mult, None);
+                // The display may have been closed already.
+                thread_tx.send((frame_index, grid)).ok();
             });
             threads.push(thread);
+            frame_indexes.push_back(frame_index);
         }
-        frame_index += 1;
         if !grid_exists {
             thread_count += 1;
             if thread_count == threads_max {
-                println!("Reached max threads.");
+                progress.message("Reached max threads.");
             }
         }
     }
+    drop(tx);
 
-    // Here, all the messages are collected.
+    // The grids arrive in whatever order the threads finish, so hold on to any that come early and
+    // show each one as soon as the grids before it have been shown.
+    let thread_total = threads.len();
     let mut grids = BTreeMap::new();
-    for i in 0..threads.len() {
-        // The `recv` method picks a message from the channel
-        // `recv` will block the current thread if there are no messages available
-        let (frame_index, grid) = rx.recv().unwrap();
-        grids.insert(frame_index, grid);
-        println!("frame_index = {}; remaining frames = {}", fc(frame_index), fc(mults.len() - (i + 1)));
-    }
-
-    // Wait for the threads to complete any remaining work.
-    for thread in threads {
-        thread.join().unwrap();
-    }
-
-    let mut frames = vec![];
-    let mut prev_grid = None;
+    let mut prev_grid: Option<Grid<usize>> = None;
+    let mut frame_count = 0;
     let mut skipped_count = 0;
-    for grid in grids.values() {
-        if prev_grid.is_none() || prev_grid.unwrap() != *grid {
-            // let (min, max) = grid.min_max();
-            frames.push(grid.as_frame(display_width, display_height, frame_seconds,
-                                      // &|count| count_to_color_black_white(count)));
-                &|count| count_to_color_black_white_mod(count, black_white_modulus)));
-            //&|count| count_to_color_gray(count, min, max)));
-        } else {
+    let source = frame_source::from_fn(move || {
+        while let Some(frame_index) = frame_indexes.front().copied() {
+            while !grids.contains_key(&frame_index) {
+                let (received_frame_index, grid) = rx.recv().unwrap();
+                grids.insert(received_frame_index, grid);
+                progress.counter("frame_index", received_frame_index as f64);
+                progress.counter("remaining frames", (thread_total - (frame_count + skipped_count + grids.len())) as f64);
+            }
+            frame_indexes.pop_front();
+            let grid = grids.remove(&frame_index).unwrap();
+            if prev_grid.as_ref() != Some(&grid) {
+                let frame = grid.as_frame(display_width, display_height, frame_seconds,
+                                          &|count| count_to_color_black_white_mod(count, black_white_modulus));
+                prev_grid = Some(grid);
+                frame_count += 1;
+                return Some(frame);
+            }
             skipped_count += 1;
         }
-        prev_grid = Some(grid.clone());
-    }
-    dbg!(Instant::now() - start_time);
-    println!("frame count = {}, skipped_count = {}", fc(frames.len()), fc(skipped_count));
+        // Every grid has been received, so the threads are done or about to be.
+        for thread in threads.drain(..) {
+            thread.join().unwrap();
+        }
+        progress.timing("read or make grids", Instant::now() - start_time);
+        progress.counter("frame count", frame_count as f64);
+        progress.counter("skipped count", skipped_count as f64);
+        None
+    });
     let back_color = count_to_color_black_white(&0);
     let additive = false;
 
-    Renderer::display_additive("Carpet", display_width, display_height, back_color, frames, additive);
+    Renderer::display_source("Carpet", display_width, display_height, back_color, source, additive, vec![]);
 }
 
 #[allow(dead_code)]
@@ -471,34 +489,42 @@ fn animate_show_existing(size: usize, black_white_modulus: usize, display_width_
     let display_height = display_width;
     let start_time = Instant::now();
 
+    let mults = mult_range(mult_min, mult_max, mult_step);
+    let mult_count = mults.len();
+    // Read each grid when its frame is needed.
+    let mut mults = mults.into_iter();
+    let mut frame_count = 0;
+    let source = frame_source::from_fn(move || {
+        for mult in mults.by_ref() {
+            if let Some(grid) = Carpet::read_grid_optional(size, min_length, mult, None) {
+                frame_count += 1;
+                return Some(grid.as_frame(display_width, display_height, frame_seconds,
+                    // &|count| count_to_color_black_white(count)));
+                    &|count| count_to_color_black_white_mod(count, black_white_modulus)));
+                    //&|count| count_to_color_gray(count, min, max)));
+            }
+        }
+        dbg!(Instant::now() - start_time);
+        println!("frame count = {}, skipped_count = {}", fc(frame_count), fc(mult_count - frame_count));
+        None
+    });
+    let back_color = count_to_color_black_white(&0);
+    let additive = false;
+
+    Renderer::display_source("Carpet", display_width, display_height, back_color, source, additive, vec![]);
+}
+
+fn mult_range(mult_min: usize, mult_max: usize, mult_step: usize) -> Vec<usize> {
     let mut mults = vec![];
     let mut mult = mult_min;
     while mult <= mult_max {
         mults.push(mult);
         mult += mult_step;
     }
-    let mut grids = vec![];
-    for mult in mults.iter() {
-        if let Some(grid) = Carpet::read_grid_optional(size, min_length, *mult) {
-            grids.push(grid);
-        };
-    }
-
-    let mut frames = vec![];
-    for grid in grids.iter() {
-        frames.push(grid.as_frame(display_width, display_height, frame_seconds,
-            // &|count| count_to_color_black_white(count)));
-            &|count| count_to_color_black_white_mod(count, black_white_modulus)));
-            //&|count| count_to_color_gray(count, min, max)));
-    }
-    dbg!(Instant::now() - start_time);
-    println!("frame count = {}, skipped_count = {}", fc(frames.len()), fc(mults.len() - frames.len()));
-    let back_color = count_to_color_black_white(&0);
-    let additive = false;
-
-    Renderer::display_additive("Carpet", display_width, display_height, back_color, frames, additive);
+    mults
 }
 
+/*
 #[allow(dead_code)]
 fn try_write_and_read_grid() {
     /*
@@ -508,7 +534,7 @@ fn try_write_and_read_grid() {
 
     let found_grid = Carpet::read_or_make_grid(400, 5, 0.68);
     assert!(reference_grid == found_grid);
-    found_grid.write(&format!("{}/Test_Grid.txt", PATH_IMAGE_FILES));
+    found_grid.write(&format!("{}/Test_Grid.txt", PATH_IMAGE_FILES)).unwrap();
      */
 
     Carpet::read_or_make_grid(400, 5, 681);
@@ -534,7 +560,7 @@ fn try_algorithms() {
         let mut carpet = Carpet::new(size, min_length, mult, algorithm, false);
         carpet.go();
         println!("{}: {:?}", algorithm.to_name(), Instant::now() - start_time);
-        carpet.grid.write(&format!("T:/Compare/{}", algorithm.to_name()));
+        carpet.grid.write(&format!("T:/Compare/{}", algorithm.to_name())).unwrap();
     }
 }
 
@@ -851,7 +877,7 @@ fn make_gallery_mod_4_combo() {
     let layout_grid = Grid::arrange(col_count, false, margin_size, &grids);
 
     let file_name = format!("{}/carpet_{}_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max, label);
-    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None).unwrap();
 
     layout_grid.draw(display_mult, &|value| bool_to_color_black_white(*value));
 }
@@ -908,7 +934,7 @@ fn debug_corner_algorithm() {
         let carpet = create_one(size, min_length, mult, algorithm);
         println!("{}: {:?}", algorithm.to_name(), Instant::now() - start_time);
         let file_name = format!("{}/carpet_{}_{}_{}_{} {}.txt", PATH_IMAGE_FILES, size, min_length, mult, label, algorithm.to_name().to_lowercase());
-        carpet.grid.write(&file_name);
+        carpet.grid.write(&file_name).unwrap();
         carpets.push(carpet);
     }
 
@@ -938,11 +964,11 @@ fn time_corner_algorithm_vs_read_file() {
 
     let file_name = format!("{}/carpet_{}.txt", PATH_IMAGE_FILES, label);
     let start_time = Instant::now();
-    carpet.grid.write(&file_name);
+    carpet.grid.write(&file_name).unwrap();
     println!("Write: {:?}", Instant::now() - start_time);
 
     let start_time = Instant::now();
-    let read_grid = Grid::read_optional(&file_name).unwrap();
+    let read_grid = Grid::read_optional(&file_name).unwrap().unwrap();
     println!("Read: {:?}", Instant::now() - start_time);
 
     carpet.grid.assert_equal(&read_grid);
@@ -1018,7 +1044,7 @@ fn draw_big_gallery() {
 
     let layout_grid = Grid::arrange(col_count, false, margin_size, &grids);
     let file_name = format!("{}/carpet_big_gallery_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max);
-    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None).unwrap();
 }
 */
 
@@ -1048,7 +1074,7 @@ fn draw_big_gallery_256() {
 
     let layout_grid = Grid::arrange(col_count, 0, margin_size, &grids);
     let file_name = format!("{}/carpet_big_gallery_{}_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max, modulus);
-    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None).unwrap();
 }
 
 /*
@@ -1094,7 +1120,7 @@ fn draw_combo_gallery() {
     let layout_grid = Grid::arrange(col_count, 0, margin_size, &grids);
     let file_name = format!("{}/carpet_combo_gallery.png_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max);
     // image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None);
-    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None).unwrap();
 }
 */
 
@@ -1123,18 +1149,25 @@ fn anim_flow() {
     let ref_grid = Carpet::read_or_make_grid(size, min_length, mult, None);
     let mod_max = ref_grid.max_value() / 40;
 
+    let layout_grid = Grid::arrange(1, 0, margin_size, &vec![ref_grid]);
+
+    // Build each frame when the display gets to it.
     let mut modulus = mod_min;
-    let mut frames = vec![];
-    while modulus <= mod_max {
-        let grid = ref_grid.copy_with_value_function(&|count| count % modulus, 0);
-        let grid = grid.copy_normalize(255);
-        let layout_grid = Grid::arrange(1, 0, margin_size, &vec![grid]);
-        frames.push(layout_grid.as_frame(display_width, display_height, frame_seconds, &|value| grayscale_256_to_color_1(*value)));
+    let source = frame_source::from_fn(move || {
+        if modulus > mod_max {
+            return None;
+        }
+        let gradient = Gradient::grayscale().modular(modulus as f64);
+        let mut frame = layout_grid.as_frame(display_width, display_height, frame_seconds, &gradient.color_func());
+        // Grid::arrange() leaves two margins above the grid.
+        let caption_height = (2 * margin_size) as f64 * display_width_mult;
+        frame.add_caption(display_width, caption_height, &format!("mult = {}, modulus = {}", mult, modulus), Color1::white());
         println!("frame {} / {}", modulus, mod_max);
         modulus += mod_inc;
-    }
+        Some(frame)
+    });
 
     let back_color = count_to_color_black_white(&0);
     let additive = false;
-    Renderer::display_additive("Carpet", display_width, display_height, back_color, frames, additive);
+    Renderer::display_source("Carpet", display_width, display_height, back_color, source, additive, vec![]);
 }
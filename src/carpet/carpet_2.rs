@@ -3,12 +3,14 @@ use std::time::{Instant, Duration};
 use crate::*;
 use renderer_3::*;
 use crate::grid::*;
+use crate::progress::{Progress, ConsoleProgress};
 use std::sync::mpsc;
 use std::{thread, mem};
 use std::collections::BTreeMap;
 use std::path::Path;
 use bit_vec::BitVec;
 use rand::{thread_rng, Rng};
+use serde::{Serialize, Deserialize};
 
 const PATH_IMAGE_FILES: &str = r"C:\Graphics\Carpet";
 
@@ -41,7 +43,7 @@ pub fn main() {
     // anim_flow();
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum CarpetAlgorithm {
     Simple,
     Wedge,
@@ -64,7 +66,6 @@ pub struct Carpet {
     algorithm: CarpetAlgorithm,
     record_events: bool,
     grid: Grid<usize>,
-    cells: Vec<usize>,
     count_square: usize,
     count_side: usize,
     count_touch_rect: usize,
@@ -114,7 +115,6 @@ impl Carpet {
             algorithm: algorithm.clone(),
             record_events,
             grid,
-            cells: vec![],
             count_square: 0,
             count_side: 0,
             count_touch_rect: 0,
@@ -138,7 +138,6 @@ impl Carpet {
             algorithm: self.algorithm.clone(),
             record_events: false,
             grid,
-            cells: vec![],
             count_square: 0,
             count_side: 0,
             count_touch_rect: 0,
@@ -291,11 +290,6 @@ impl Carpet {
 
         // let start_time = Instant::now();
 
-        self.cells.reserve(self.size * self.size);
-        for _ in 0..self.size * self.size {
-            self.cells.push(0);
-        }
-
         let mut square_sizes = vec![];
         let mut one_size = self.size as f32;
         let mut prev_rounded_size = 0;
@@ -309,13 +303,9 @@ impl Carpet {
             one_size *= self.mult as f32 / 1_000.0;
         }
 
+        // The grid starts out all zeroes, so the squares can be counted in its cells directly.
         self.flat_square(0, &square_sizes, false, 0, 0, self.size - 1, self.size - 1);
 
-        for y in 0..self.size {
-            for x in 0..self.size {
-                self.grid.cell_values[y][x] = self.cells[(y * self.size) + x];
-            }
-        }
         self.grid.complete_from_wedge();
 
         //rintln!("Carpet::go_flat_square(): overall = {:?}", Instant::now() - start_time);
@@ -331,15 +321,27 @@ impl Carpet {
                 }
             }
         }
-        // Top and bottom of the square.
-        for x in x1..=x2 {
-            self.cells[(y1 * self.size) + x] += 1;
-            self.cells[(y2 * self.size) + x] += 1;
-        }
-        // Left and right edges of the square.
-        for y in y1..=y2 {
-            self.cells[(y * self.size) + x1] += 1;
-            self.cells[(y * self.size) + x2] += 1;
+        if self.record_events {
+            // Each edge is its own event, as with the sides in side(), so that the corners where
+            // two edges meet are counted twice just as they are below.
+            for &(edge_x1, edge_y1, edge_x2, edge_y2) in [(x1, y1, x2, y1), (x1, y2, x2, y2), (x1, y1, x1, y2), (x2, y1, x2, y2)].iter() {
+                let mut event = GridEvent::new();
+                self.touch_rect(&mut event, GridCoord::new(edge_x1, edge_y1), GridCoord::new(edge_x2, edge_y2));
+                self.grid.add_event(event);
+            }
+        } else {
+            let size = self.size;
+            let cells = self.grid.as_mut_slice();
+            // Top and bottom of the square.
+            for x in x1..=x2 {
+                cells[(y1 * size) + x] += 1;
+                cells[(y2 * size) + x] += 1;
+            }
+            // Left and right edges of the square.
+            for y in y1..=y2 {
+                cells[(y * size) + x1] += 1;
+                cells[(y * size) + x2] += 1;
+            }
         }
 
         let next_depth = depth + 1;
@@ -438,20 +440,20 @@ impl Carpet {
     pub fn write_grid(&self) {
         let full_file_name = Self::full_file_name(self.size, self.min_length, self.mult, None);
         //let start_time = Instant::now();
-        self.grid.write(&full_file_name);
+        self.grid.write(&full_file_name).unwrap();
         //rintln!("Carpet::write_grid({}): {:?}", full_file_name, Instant::now() - start_time);
     }
 
     pub fn write_grid_labeled(&self, label: &str) {
         let full_file_name = Self::full_file_name(self.size, self.min_length, self.mult, Some(label));
         //let start_time = Instant::now();
-        self.grid.write(&full_file_name);
+        self.grid.write(&full_file_name).unwrap();
         //rintln!("Carpet::write_grid({}): {:?}", full_file_name, Instant::now() - start_time);
     }
 
     pub fn read_grid_optional(size: usize, min_length: usize, mult: usize) -> Option<Grid<usize>> {
         let full_file_name = Self::full_file_name(size, min_length, mult, None);
-        Grid::read_optional(&full_file_name)
+        Grid::read_optional(&full_file_name).unwrap()
     }
 
     pub fn read_or_make_grid(size: usize, min_length: usize, mult: usize) -> Grid<usize> {
@@ -467,7 +469,7 @@ impl Carpet {
                 carpet.go();
                 //rintln!("Carpet::read_or_make_grid({}): not found, created carpet: {:?}", full_file_name, Instant::now() - start_time);
                 let full_file_name = Self::full_file_name(size, min_length, mult, None);
-                carpet.grid.write(&full_file_name);
+                carpet.grid.write(&full_file_name).unwrap();
                 carpet.grid
             }
         }
@@ -662,7 +664,7 @@ fn try_animation() {
     // animate_mult_parallel(400, 2.0, 1.0, 3, 0.60, 0.65, 0.001)
     // animate_mult_parallel(800, 2, 1.0, 1.0, 3, 0.63, 0.68, 0.001, 50);
     // animate_mult_parallel(400, 2.0, 1.0, 3, 0.5, 0.60, 0.001)
-    animate_mult_parallel(400, 2, 2.0, 2.0, 3, 650, 800, 1, 50);
+    animate_mult_parallel(400, 2, 2.0, 2.0, 3, 650, 800, 1, 50, &mut ConsoleProgress);
     // animate_show_existing(400, 2.0, 2.0, 3, 0.7, 0.9, 0.001);
     // animate_mult_parallel(200, 5, 4.0, 1.0, 7, 0.6, 0.8, 0.002, 1_000);
     // animate_mult_parallel(200, 5, 2.0, 1.0, 7, 0.8, 0.9, 0.002, 1_000);
@@ -703,7 +705,7 @@ fn animate_mult(size: usize, display_width_mult: f64, frame_seconds: f64, min_le
 }
 
 #[allow(dead_code)]
-fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_mult: f64, frame_seconds: f64, min_length: usize, mult_min: usize, mult_max: usize, mult_step: usize, threads_max: usize) {
+fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_mult: f64, frame_seconds: f64, min_length: usize, mult_min: usize, mult_max: usize, mult_step: usize, threads_max: usize, progress: &mut dyn Progress) {
     let display_width = size as f64 * display_width_mult;
     let display_height = display_width;
     let start_time = Instant::now();
@@ -725,7 +727,7 @@ fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_
         if !grid_exists {
             if thread_count < threads_max {
                 let file_name = Carpet::full_file_name(size, min_length, mult, None);
-                println!("Starting to build {}", file_name);
+                progress.message(&format!("Starting to build {}", file_name));
             }
         }
         if grid_exists || thread_count < threads_max {
@@ -742,7 +744,7 @@ fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_
         if !grid_exists {
             thread_count += 1;
             if thread_count == threads_max {
-                println!("Reached max threads.");
+                progress.message("Reached max threads.");
             }
         }
     }
@@ -754,7 +756,7 @@ fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_
         // `recv` will block the current thread if there are no messages available
         let (frame_index, grid) = rx.recv().unwrap();
         grids.insert(frame_index, grid);
-        println!("frame_index = {}; remaining frames = {}", fc(frame_index), fc(mults.len() - (i + 1)));
+        progress.percent_complete("read or make grids", (i + 1) as f64 / threads.len() as f64);
     }
 
     // Wait for the threads to complete any remaining work.
@@ -777,8 +779,9 @@ fn animate_mult_parallel(size: usize, black_white_modulus: usize, display_width_
         }
         prev_grid = Some(grid.clone());
     }
-    dbg!(Instant::now() - start_time);
-    println!("frame count = {}, skipped_count = {}", fc(frames.len()), fc(skipped_count));
+    progress.timing("read or make grids", Instant::now() - start_time);
+    progress.counter("frame count", frames.len() as f64);
+    progress.counter("skipped count", skipped_count as f64);
     let back_color = count_to_color_black_white(&0);
     let additive = false;
 
@@ -828,7 +831,7 @@ fn try_write_and_read_grid() {
 
     let found_grid = Carpet::read_or_make_grid(400, 5, 0.68);
     assert!(reference_grid == found_grid);
-    found_grid.write(&format!("{}/Test_Grid.txt", PATH_IMAGE_FILES));
+    found_grid.write(&format!("{}/Test_Grid.txt", PATH_IMAGE_FILES)).unwrap();
      */
 
     Carpet::read_or_make_grid(400, 5, 681);
@@ -854,7 +857,7 @@ fn try_algorithms() {
         let mut carpet = Carpet::new(size, min_length, mult, algorithm, false);
         carpet.go();
         println!("{}: {:?}", algorithm.to_name(), Instant::now() - start_time);
-        carpet.grid.write(&format!("T:/Compare/{}", algorithm.to_name()));
+        carpet.grid.write(&format!("T:/Compare/{}", algorithm.to_name())).unwrap();
     }
 }
 
@@ -1169,7 +1172,7 @@ fn make_gallery_mod_4_combo() {
     let layout_grid = Grid::arrange(col_count, false, margin_size, &grids);
 
     let file_name = format!("{}/carpet_{}_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max, label);
-    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None).unwrap();
 
     layout_grid.draw(display_mult, &|value| bool_to_color_black_white(*value));
 }
@@ -1226,7 +1229,7 @@ fn debug_corner_algorithm() {
         let carpet = create_one(size, min_length, mult, algorithm);
         println!("{}: {:?}", algorithm.to_name(), Instant::now() - start_time);
         let file_name = format!("{}/carpet_{}_{}_{}_{} {}.txt", PATH_IMAGE_FILES, size, min_length, mult, label, algorithm.to_name().to_lowercase());
-        carpet.grid.write(&file_name);
+        carpet.grid.write(&file_name).unwrap();
         carpets.push(carpet);
     }
 
@@ -1256,11 +1259,11 @@ fn time_corner_algorithm_vs_read_file() {
 
     let file_name = format!("{}/carpet_{}.txt", PATH_IMAGE_FILES, label);
     let start_time = Instant::now();
-    carpet.grid.write(&file_name);
+    carpet.grid.write(&file_name).unwrap();
     println!("Write: {:?}", Instant::now() - start_time);
 
     let start_time = Instant::now();
-    let read_grid = Grid::read_optional(&file_name).unwrap();
+    let read_grid = Grid::read_optional(&file_name).unwrap().unwrap();
     println!("Read: {:?}", Instant::now() - start_time);
 
     carpet.grid.assert_equal(&read_grid);
@@ -1333,7 +1336,7 @@ fn draw_big_gallery() {
 
     let layout_grid = Grid::arrange(col_count, false, margin_size, &grids);
     let file_name = format!("{}/carpet_big_gallery_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max);
-    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None).unwrap();
 }
 
 #[allow(dead_code)]
@@ -1362,7 +1365,7 @@ fn draw_big_gallery_256() {
 
     let layout_grid = Grid::arrange(col_count, 0, margin_size, &grids);
     let file_name = format!("{}/carpet_big_gallery_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max);
-    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None).unwrap();
 }
 
 #[allow(dead_code)]
@@ -1407,7 +1410,7 @@ fn draw_combo_gallery() {
     let layout_grid = Grid::arrange(col_count, 0, margin_size, &grids);
     let file_name = format!("{}/carpet_combo_gallery.png_{}_{}_{}_{}.png", PATH_IMAGE_FILES, size, min_length, mult_min, mult_max);
     // image_util::save_grid(&layout_grid, &file_name, &|value| bool_to_color_256_black_white(*value), 0, None);
-    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None);
+    image_util::save_grid(&layout_grid, &file_name, &|value| grayscale_256_to_color_256(*value), 0, None).unwrap();
 }
 
 #[allow(dead_code)]
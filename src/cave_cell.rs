@@ -4,20 +4,21 @@
 // Orthogonal town generation: https://pvigier.github.io/2020/03/15/vagabond-city-generation.html
 
 use rand::{Rng, thread_rng};
+use serde::{Serialize, Deserialize};
 
 use crate::grid::Grid;
-use crate::Color1;
-use crate::renderer_3::Renderer;
-
-const CELL_CLOSED: usize = 0;
-const CELL_OPEN: usize = 1;
+use crate::{Color1, DrawResult};
+use crate::output::Output;
+use crate::render_backend::FramePlayer;
+use crate::palette::{closed_open_palette, SLOT_CLOSED, SLOT_OPEN};
 
 pub fn main() {
-    run();
+    try_run();
 }
 
 #[allow(dead_code)]
-enum Algorithm {
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Algorithm {
     Original,
     B3S1234,
     B3S12345,
@@ -48,9 +49,7 @@ impl Algorithm {
     }
 }
 
-fn run() {
-    let mut rng = thread_rng();
-    let (death_limit, birth_limit) = (3, 4);
+fn try_run() {
     // let (width, height, steps, animation_seconds) = (400, 200, 4, 30);
     // let algorithm = Algorithm::B3S12345;
     let algorithm = Algorithm::GameOfLife;
@@ -60,6 +59,13 @@ fn run() {
     // let (width, height, steps, animation_seconds) = (40, 20, 120, 30);
     // let initial_chance = 0.4;
     let initial_chance = 0.5;
+    run(&mut thread_rng(), width, height, algorithm, initial_chance, steps, animation_seconds, &Output::Window).unwrap();
+}
+
+// Start with each cell open at random with the given chance, then apply the algorithm's rules for
+// the given number of steps, one frame per step.
+pub fn run<R: Rng>(rng: &mut R, width: usize, height: usize, algorithm: Algorithm, initial_chance: f64, steps: usize, animation_seconds: usize, output: &Output) -> DrawResult<()> {
+    let (death_limit, birth_limit) = (3, 4);
     let frame_seconds = animation_seconds as f64 / steps as f64;
     let display_width_mult = if height >= 800 {
         1.0
@@ -69,27 +75,31 @@ fn run() {
     let display_width = width as f64 * display_width_mult;
     let display_height = height as f64 * display_width_mult;
 
-    let mut grid = Grid::new(width, height, CELL_CLOSED);
+    // The cells hold the palette index of their color.
+    let palette = closed_open_palette();
+    let (closed, open) = (palette.index(SLOT_CLOSED), palette.index(SLOT_OPEN));
+
+    let mut grid = Grid::new(width, height, closed);
     for y in 0..height {
         for x in 0..width {
             if rng.gen_range(0.0..1.0) < initial_chance {
-                grid.set_xy(x, y, CELL_OPEN);
+                grid.set_xy(x, y, open);
             }
         }
     }
 
     /*
-    grid.set_xy(0, 0, CELL_OPEN);
-    grid.set_xy(1, 1, CELL_OPEN);
-    grid.set_xy(2, 1, CELL_OPEN);
-    grid.set_xy(0, 2, CELL_OPEN);
-    grid.set_xy(1, 2, CELL_OPEN);
+    grid.set_xy(0, 0, open);
+    grid.set_xy(1, 1, open);
+    grid.set_xy(2, 1, open);
+    grid.set_xy(0, 2, open);
+    grid.set_xy(1, 2, open);
     */
 
     /*
-    grid.set_xy(1, 0, CELL_OPEN);
-    grid.set_xy(1, 1, CELL_OPEN);
-    grid.set_xy(1, 2, CELL_OPEN);
+    grid.set_xy(1, 0, open);
+    grid.set_xy(1, 1, open);
+    grid.set_xy(1, 2, open);
     */
 
     let mut frames = vec![];
@@ -101,36 +111,21 @@ fn run() {
         // Count open/alive neighbors.
         for y in 0..height {
             for x in 0..width {
-                let count = count_open_neighbors(&grid, x, y);
+                let count = count_open_neighbors(&grid, x, y, open);
+                let is_open = grid.get_xy(x, y) == open;
                 match algorithm {
                     Algorithm::Original => {
-                        match grid.get_xy(x, y) {
-                            CELL_CLOSED => {
-                                if count > birth_limit {
-                                    new_grid.set_xy(x, y, CELL_OPEN);
-                                }
-                            },
-                            CELL_OPEN => {
-                                if count < death_limit {
-                                    new_grid.set_xy(x, y, CELL_CLOSED);
-                                }
-                            },
-                            _ => panic!(),
+                        if !is_open && count > birth_limit {
+                            new_grid.set_xy(x, y, open);
+                        } else if is_open && count < death_limit {
+                            new_grid.set_xy(x, y, closed);
                         }
                     },
                     Algorithm::B3S1234 | Algorithm::B3S12345 | Algorithm::GameOfLife => {
-                        match grid.get_xy(x, y) {
-                            CELL_CLOSED => {
-                                if count == algorithm.born() {
-                                    new_grid.set_xy(x, y, CELL_OPEN);
-                                }
-                            },
-                            CELL_OPEN => {
-                                if count < algorithm.live_min() || count > algorithm.live_max() {
-                                    new_grid.set_xy(x, y, CELL_CLOSED);
-                                }
-                            },
-                            _ => panic!(),
+                        if !is_open && count == algorithm.born() {
+                            new_grid.set_xy(x, y, open);
+                        } else if is_open && (count < algorithm.live_min() || count > algorithm.live_max()) {
+                            new_grid.set_xy(x, y, closed);
                         }
                     },
                 }
@@ -143,11 +138,11 @@ fn run() {
 
     let additive = false;
     let back_color = Color1::black();
-    Renderer::display_additive_with_colors("Cave Cell", display_width, display_height, back_color, frames, additive, vec![Color1::black(), Color1::white()]);
+    output.try_display_player("Cave Cell", display_width, display_height, back_color, FramePlayer::new(back_color, frames, additive, palette.into()))
 }
 
-fn count_open_neighbors(grid: &Grid<usize>, x: usize, y: usize) -> usize {
+fn count_open_neighbors(grid: &Grid<usize>, x: usize, y: usize, open: usize) -> usize {
     // grid.neighbor_values(x, y).iter()
     grid.neighbor_values_moore_toroidal(x, y).iter()
-        .map(|x| if *x == CELL_OPEN { 1 } else { 0 }).sum()
+        .map(|x| if *x == open { 1 } else { 0 }).sum()
 }
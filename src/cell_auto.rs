@@ -2,21 +2,26 @@
 // The B3S1234 and similar algorithms are from https://en.wikipedia.org/wiki/Maze_generation_algorithm
 // Similar approaches here: http://www.roguebasin.com/index.php?title=Cellular_Automata_Method_for_Generating_Random_Cave-Like_Levels
 
+use std::sync::mpsc;
+
 use rand::{Rng, thread_rng};
+use serde::{Serialize, Deserialize};
 
 use crate::grid::{Grid, GridNeighborType, GridWrappingStyle};
-use crate::{Color1, Frame, cell_index_to_x_y_isize, x_y_to_cell_index_isize};
-use crate::renderer_3::Renderer;
+use crate::{Color1, DrawResult, Frame, cell_index_to_x_y_isize, x_y_to_cell_index_isize};
+use crate::frame_source;
+use crate::render_backend::FramePlayer;
+use crate::output::Output;
+use crate::palette::{Palette, closed_open_palette, SLOT_CLOSED, SLOT_OPEN};
 
-const CELL_CLOSED: usize = 0;
-const CELL_OPEN: usize = 1;
+const FRAME_BUFFER_SIZE: usize = 10;
 
 pub fn main() {
     run_animation();
 }
 
-#[derive(Clone)]
-enum CellAutoAlgorithm {
+#[derive(Clone, Serialize, Deserialize)]
+pub enum CellAutoAlgorithm {
     Original,
     B3S1234,
     B3S12345,
@@ -24,13 +29,14 @@ enum CellAutoAlgorithm {
 }
 
 #[allow(dead_code)]
-enum CellAutoStartFill {
+#[derive(Clone, Serialize, Deserialize)]
+pub enum CellAutoStartFill {
     Random { pct: f64 },
     Glider { count: usize },
 }
 
 #[derive(Clone)]
-struct CellAutoGrid {
+pub struct CellAutoGrid {
     width: usize,
     height: usize,
     algorithm: CellAutoAlgorithm,
@@ -69,7 +75,7 @@ impl CellAutoAlgorithm {
 }
 
 impl CellAutoGrid {
-    pub fn new(width: usize, height: usize, algorithm: CellAutoAlgorithm, neighbor_type: GridNeighborType, wrapping_style: GridWrappingStyle, start_fill: CellAutoStartFill) -> Self {
+    pub fn new<R: Rng>(rng: &mut R, width: usize, height: usize, algorithm: CellAutoAlgorithm, neighbor_type: GridNeighborType, wrapping_style: GridWrappingStyle, start_fill: CellAutoStartFill) -> Self {
         let cell_count = width * height;
         let mut cells = Vec::with_capacity(cell_count);
         for cell_index in 0..cell_count {
@@ -86,17 +92,16 @@ impl CellAutoGrid {
         };
         match start_fill {
             CellAutoStartFill::Random { pct } => {
-                grid.open_random(pct);
+                grid.open_random(rng, pct);
             },
             CellAutoStartFill::Glider { count } => {
-                grid.add_gliders(count);
+                grid.add_gliders(rng, count);
             },
         }
         grid
     }
 
-    fn open_random(&mut self, pct: f64) {
-        let mut rng = thread_rng();
+    fn open_random<R: Rng>(&mut self, rng: &mut R, pct: f64) {
         for cell_index in 0..self.cells.len() {
             if rng.gen_range(0.0..1.0) < pct {
                 self.open(cell_index);
@@ -104,16 +109,15 @@ impl CellAutoGrid {
         }
     }
 
-    fn add_gliders(&mut self, count: usize) {
-        self.add_shapes(vec![(0, 0), (1, 1), (2, 1), (0, 2), (1, 2)], count);
+    fn add_gliders<R: Rng>(&mut self, rng: &mut R, count: usize) {
+        self.add_shapes(rng, vec![(0, 0), (1, 1), (2, 1), (0, 2), (1, 2)], count);
     }
 
-    fn add_shapes(&mut self, open_cells: Vec<(usize, usize)>, count: usize) {
+    fn add_shapes<R: Rng>(&mut self, rng: &mut R, open_cells: Vec<(usize, usize)>, count: usize) {
         let shape = CellAutoShape::new(open_cells);
         let shape_variations = shape.grid.get_all_flips_and_rotations();
         debug_assert_eq!(8, shape_variations.len());
 
-        let mut rng = thread_rng();
         let (width, height) = (self.width as isize, self.height as isize);
         for _ in 0..count {
             let cell_index = rng.gen_range(0..self.cells.len());
@@ -241,7 +245,15 @@ impl CellAutoGrid {
         }
     }
 
-    fn animate(&mut self, steps: usize, animation_seconds: usize) {
+    pub fn animate(&mut self, steps: usize, animation_seconds: usize, output: &Output) -> DrawResult<()> {
+        self.animate_persistent(steps, animation_seconds, None, output)
+    }
+
+    // Frames are produced on a background thread while the animation plays so that only a few
+    // of them are held in memory at once. With a persistence decay, cells that close fade out
+    // instead of disappearing, so moving patterns like gliders leave trails. Afterward self holds
+    // the last step that was produced, which is the final step unless the window was closed early.
+    pub fn animate_persistent(&mut self, steps: usize, animation_seconds: usize, decay_per_second: Option<f64>, output: &Output) -> DrawResult<()> {
         let frame_seconds = animation_seconds as f64 / steps as f64;
         let display_width_mult = if self.height >= 800 {
             1.0
@@ -251,18 +263,31 @@ impl CellAutoGrid {
         let display_width = self.width as f64 * display_width_mult;
         let display_height = self.height as f64 * display_width_mult;
 
-        let mut frames = vec![];
-        let frame = self.as_frame_color_index(display_width, display_height, frame_seconds);
-        frames.push(frame);
-
-        for _ in 0..steps {
-            self.step();
-            let frame = self.as_frame_color_index(display_width, display_height, frame_seconds);
-            frames.push(frame);
-        }
+        // The producer thread works on its own copy and hands it back when it's done.
+        let (grid_sender, grid_receiver) = mpsc::channel();
+        let mut grid = self.clone();
+        let palette = closed_open_palette();
+        let producer_palette = palette.clone();
+        let source = frame_source::spawn(FRAME_BUFFER_SIZE, move |sender| {
+            if sender.send(grid.as_frame_color_index(display_width, display_height, frame_seconds, &producer_palette)) {
+                for _ in 0..steps {
+                    grid.step();
+                    if !sender.send(grid.as_frame_color_index(display_width, display_height, frame_seconds, &producer_palette)) {
+                        break;
+                    }
+                }
+            }
+            grid_sender.send(grid).unwrap();
+        });
         let additive = false;
         let back_color = Color1::black();
-        Renderer::display_additive_with_colors("Cave Cell", display_width, display_height, back_color, frames, additive, vec![Color1::black(), Color1::white()]);
+        let mut player = FramePlayer::from_source(back_color, source, additive, palette.into());
+        player.set_persistence(decay_per_second);
+        let result = output.try_display_player("Cave Cell", display_width, display_height, back_color, player);
+        // The player has been dropped by now, so the producer stops at its next send() if it hasn't
+        // finished already.
+        *self = grid_receiver.recv().unwrap();
+        result
     }
 
     fn step(&mut self) {
@@ -282,11 +307,13 @@ impl CellAutoGrid {
         }
     }
 
-    fn as_frame_color_index(&self, display_width: f64, display_height: f64, frame_seconds: f64) -> Frame {
-        let mut color_grid = Grid::new(self.width, self.height, CELL_CLOSED);
+    // The palette needs "closed" and "open" slots, as in closed_open_palette().
+    fn as_frame_color_index(&self, display_width: f64, display_height: f64, frame_seconds: f64, palette: &Palette) -> Frame {
+        let open = palette.index(SLOT_OPEN);
+        let mut color_grid = Grid::new(self.width, self.height, palette.index(SLOT_CLOSED));
         for cell_index in 0..self.cells.len() {
             if self.cells[cell_index].open {
-                color_grid.set_by_index(cell_index, CELL_OPEN)
+                color_grid.set_by_index(cell_index, open)
             }
         }
         color_grid.as_frame_color_index(display_width, display_height, frame_seconds)
@@ -313,7 +340,7 @@ impl CellAutoCell {
             },
         };
         Self {
-            value: CELL_CLOSED,
+            open: false,
             neighbors: Vec::with_capacity(neighbor_count),
             neighbor_open_count: 0,
         }
@@ -336,15 +363,21 @@ impl CellAutoShape {
 }
 
 fn run_animation() {
+    let mut rng = thread_rng();
+
     // Lots of gliders.
-    // let mut grid = CellAutoGrid::new(400, 200, CellAutoAlgorithm::GameOfLife, GridNeighborType::Moore { range: 1 }, GridWrappingStyle::Toroidal, CellAutoStartFill::Glider { count: 500 });
-    // grid.animate(120, 30);
+    // let mut grid = CellAutoGrid::new(&mut rng, 400, 200, CellAutoAlgorithm::GameOfLife, GridNeighborType::Moore { range: 1 }, GridWrappingStyle::Toroidal, CellAutoStartFill::Glider { count: 500 });
+    // grid.animate(120, 30, &Output::Window).unwrap();
+
+    // Gliders with trails.
+    // let mut grid = CellAutoGrid::new(&mut rng, 400, 200, CellAutoAlgorithm::GameOfLife, GridNeighborType::Moore { range: 1 }, GridWrappingStyle::Toroidal, CellAutoStartFill::Glider { count: 500 });
+    // grid.animate_persistent(120, 30, Some(0.99), &Output::Window).unwrap();
 
     // Cave.
-    let mut grid = CellAutoGrid::new(400, 200, CellAutoAlgorithm::Original, GridNeighborType::Moore { range: 1 }, GridWrappingStyle::None, CellAutoStartFill::Random { pct: 0.4 });
-    grid.animate(12, 30);
+    let mut grid = CellAutoGrid::new(&mut rng, 400, 200, CellAutoAlgorithm::Original, GridNeighborType::Moore { range: 1 }, GridWrappingStyle::None, CellAutoStartFill::Random { pct: 0.4 });
+    grid.animate(12, 30, &Output::Window).unwrap();
 
     // Game of Life with random start.
-    // let mut grid = CellAutoGrid::new(400, 200, CellAutoAlgorithm::GameOfLife, GridNeighborType::Moore { range: 1 }, GridWrappingStyle::Toroidal, CellAutoStartFill::Random { pct: 0.5 } );
-    // grid.animate(120, 30);
+    // let mut grid = CellAutoGrid::new(&mut rng, 400, 200, CellAutoAlgorithm::GameOfLife, GridNeighborType::Moore { range: 1 }, GridWrappingStyle::Toroidal, CellAutoStartFill::Random { pct: 0.5 } );
+    // grid.animate(120, 30, &Output::Window).unwrap();
 }
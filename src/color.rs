@@ -1,8 +1,12 @@
-use rand::{Rng, thread_rng};
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
 
 pub type Color = [f32; 4];
 
-#[derive(Clone, Copy, Debug)]
+// Serialized as just the four components, since for_render is the same values again.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(into = "Color1Components", try_from = "Color1Components")]
 pub struct Color1 {
     pub r: f32,
     pub g: f32,
@@ -11,7 +15,7 @@ pub struct Color1 {
     pub for_render: [f32; 4],
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Color256 {
     pub r: u8,
     pub g: u8,
@@ -19,6 +23,38 @@ pub struct Color256 {
     pub a: u8,
 }
 
+#[derive(Serialize, Deserialize)]
+struct Color1Components {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+}
+
+// The color spaces a color can be converted to and interpolated in. Each one has three components,
+// with alpha kept separately:
+//   Srgb and LinearRgb: red, green and blue from 0.0 to 1.0. LinearRgb undoes the sRGB gamma so
+//     that values are proportional to light intensity.
+//   Hsv and Hsl: hue in degrees from 0.0 to 360.0, then saturation and value or lightness from
+//     0.0 to 1.0.
+//   Lab: CIELAB with a D65 white point, lightness from 0.0 to 100.0 and a and b roughly from
+//     -128.0 to 128.0.
+//   Oklab: lightness from 0.0 to 1.0 and a and b roughly from -0.4 to 0.4. Like CIELAB, equal
+//     steps are meant to look like equal differences, but it keeps hues steadier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorSpace {
+    Srgb,
+    LinearRgb,
+    Hsv,
+    Hsl,
+    Lab,
+    Oklab,
+}
+
+// CIELAB reference white for D65.
+const LAB_WHITE: [f32; 3] = [0.95047, 1.0, 1.08883];
+const LAB_DELTA: f32 = 6.0 / 29.0;
+
 impl Color1 {
     pub fn from_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
         debug_assert!(r >= 0.0);
@@ -81,8 +117,7 @@ impl Color1 {
         Self::from_rgb(0.75, 0.75, 0.75)
     }
 
-    pub fn random(a: f32) -> Self {
-        let mut rng = thread_rng();
+    pub fn random<R: Rng>(rng: &mut R, a: f32) -> Self {
         let r = rng.gen_range(0.0..=1.0);
         let g = rng.gen_range(0.0..=1.0);
         let b = rng.gen_range(0.0..=1.0);
@@ -103,12 +138,211 @@ impl Color1 {
         let a = self.a + (a_step_size * step_index);
         Self::from_rgba(r, g, b, a)
     }
+
+    // Like gradiant_one() but interpolating in the given color space. Oklab gives fades that look
+    // even in lightness without passing through muddy or overly dark colors.
+    pub fn gradiant_one_in(&self, other: &Self, step_count: usize, step_index: usize, space: ColorSpace) -> Self {
+        debug_assert!(step_index <= step_count);
+        self.interpolate(other, step_index as f32 / step_count as f32, space)
+    }
+
+    // The color a fraction t of the way from this color to the other one. In Hsv and Hsl the hue
+    // goes the short way around the color wheel.
+    pub fn interpolate(&self, other: &Self, t: f32, space: ColorSpace) -> Self {
+        let (from, to) = (self.to_space(space), other.to_space(space));
+        let lerp = |a: f32, b: f32| a + ((b - a) * t);
+        let components = match space {
+            ColorSpace::Hsv | ColorSpace::Hsl => {
+                // A gray has no meaningful hue, so use the other color's hue to avoid swinging
+                // through unrelated colors.
+                let (from_hue, to_hue) = if from[1] == 0.0 {
+                    (to[0], to[0])
+                } else if to[1] == 0.0 {
+                    (from[0], from[0])
+                } else {
+                    (from[0], to[0])
+                };
+                let mut hue_diff = to_hue - from_hue;
+                if hue_diff > 180.0 {
+                    hue_diff -= 360.0;
+                } else if hue_diff < -180.0 {
+                    hue_diff += 360.0;
+                }
+                [(from_hue + (hue_diff * t)).rem_euclid(360.0), lerp(from[1], to[1]), lerp(from[2], to[2])]
+            },
+            _ => [lerp(from[0], to[0]), lerp(from[1], to[1]), lerp(from[2], to[2])],
+        };
+        Self::from_space(space, components, lerp(self.a, other.a))
+    }
+
+    // The three components of this color in the given space. Alpha is left out since it's the
+    // same in every space.
+    pub fn to_space(&self, space: ColorSpace) -> [f32; 3] {
+        match space {
+            ColorSpace::Srgb => [self.r, self.g, self.b],
+            ColorSpace::LinearRgb => self.to_linear_rgb(),
+            ColorSpace::Hsv => self.to_hsv(),
+            ColorSpace::Hsl => self.to_hsl(),
+            ColorSpace::Lab => self.to_lab(),
+            ColorSpace::Oklab => self.to_oklab(),
+        }
+    }
+
+    // Colors outside the sRGB gamut, as can come out of Lab and Oklab, are clamped.
+    pub fn from_space(space: ColorSpace, components: [f32; 3], a: f32) -> Self {
+        match space {
+            ColorSpace::Srgb => Self::from_rgba_clamped(components, a),
+            ColorSpace::LinearRgb => Self::from_linear_rgba(components, a),
+            ColorSpace::Hsv => Self::from_hsva(components, a),
+            ColorSpace::Hsl => Self::from_hsla(components, a),
+            ColorSpace::Lab => Self::from_laba(components, a),
+            ColorSpace::Oklab => Self::from_oklaba(components, a),
+        }
+    }
+
+    fn from_rgba_clamped(rgb: [f32; 3], a: f32) -> Self {
+        let clamp = |value: f32| value.max(0.0).min(1.0);
+        Self::from_rgba(clamp(rgb[0]), clamp(rgb[1]), clamp(rgb[2]), clamp(a))
+    }
+
+    pub fn to_linear_rgb(&self) -> [f32; 3] {
+        [srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b)]
+    }
+
+    pub fn from_linear_rgba(rgb: [f32; 3], a: f32) -> Self {
+        Self::from_rgba_clamped([linear_to_srgb(rgb[0]), linear_to_srgb(rgb[1]), linear_to_srgb(rgb[2])], a)
+    }
+
+    pub fn to_hsv(&self) -> [f32; 3] {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        [hue, saturation, max]
+    }
+
+    pub fn from_hsva(hsv: [f32; 3], a: f32) -> Self {
+        let [hue, saturation, value] = hsv;
+        let chroma = value * saturation;
+        Self::from_hue_chroma(hue, chroma, value - chroma, a)
+    }
+
+    pub fn to_hsl(&self) -> [f32; 3] {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - ((2.0 * lightness) - 1.0).abs()) };
+        [hue, saturation, lightness]
+    }
+
+    pub fn from_hsla(hsl: [f32; 3], a: f32) -> Self {
+        let [hue, saturation, lightness] = hsl;
+        let chroma = (1.0 - ((2.0 * lightness) - 1.0).abs()) * saturation;
+        Self::from_hue_chroma(hue, chroma, lightness - (chroma / 2.0), a)
+    }
+
+    // The hue in degrees shared by HSV and HSL, along with the largest and smallest channels.
+    fn hue_max_min(&self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / chroma).rem_euclid(6.0)
+        } else if max == self.g {
+            60.0 * (((self.b - self.r) / chroma) + 2.0)
+        } else {
+            60.0 * (((self.r - self.g) / chroma) + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    // Place the chroma in the channels for the hue, then add the smallest channel to all three.
+    fn from_hue_chroma(hue: f32, chroma: f32, min: f32, a: f32) -> Self {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - ((sector % 2.0) - 1.0).abs());
+        let (r, g, b) = match sector as usize {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Self::from_rgba_clamped([r + min, g + min, b + min], a)
+    }
+
+    pub fn to_lab(&self) -> [f32; 3] {
+        let [r, g, b] = self.to_linear_rgb();
+        let x = (0.4124564 * r) + (0.3575761 * g) + (0.1804375 * b);
+        let y = (0.2126729 * r) + (0.7151522 * g) + (0.0721750 * b);
+        let z = (0.0193339 * r) + (0.1191920 * g) + (0.9503041 * b);
+        let f = |t: f32| if t > LAB_DELTA.powi(3) { t.cbrt() } else { (t / (3.0 * LAB_DELTA * LAB_DELTA)) + (4.0 / 29.0) };
+        let (fx, fy, fz) = (f(x / LAB_WHITE[0]), f(y / LAB_WHITE[1]), f(z / LAB_WHITE[2]));
+        [(116.0 * fy) - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+
+    pub fn from_laba(lab: [f32; 3], a: f32) -> Self {
+        let fy = (lab[0] + 16.0) / 116.0;
+        let (fx, fz) = (fy + (lab[1] / 500.0), fy - (lab[2] / 200.0));
+        let f_inverse = |t: f32| if t > LAB_DELTA { t.powi(3) } else { 3.0 * LAB_DELTA * LAB_DELTA * (t - (4.0 / 29.0)) };
+        let (x, y, z) = (f_inverse(fx) * LAB_WHITE[0], f_inverse(fy) * LAB_WHITE[1], f_inverse(fz) * LAB_WHITE[2]);
+        let r = (3.2404542 * x) - (1.5371385 * y) - (0.4985314 * z);
+        let g = (-0.9692660 * x) + (1.8760108 * y) + (0.0415560 * z);
+        let b = (0.0556434 * x) - (0.2040259 * y) + (1.0572252 * z);
+        Self::from_linear_rgba([r, g, b], a)
+    }
+
+    // From https://bottosson.github.io/posts/oklab/
+    pub fn to_oklab(&self) -> [f32; 3] {
+        let [r, g, b] = self.to_linear_rgb();
+        let l = ((0.4122214708 * r) + (0.5363325363 * g) + (0.0514459929 * b)).cbrt();
+        let m = ((0.2119034982 * r) + (0.6806995451 * g) + (0.1073969566 * b)).cbrt();
+        let s = ((0.0883024619 * r) + (0.2817188376 * g) + (0.6299787005 * b)).cbrt();
+        [
+            (0.2104542553 * l) + (0.7936177850 * m) - (0.0040720468 * s),
+            (1.9779984951 * l) - (2.4285922050 * m) + (0.4505937099 * s),
+            (0.0259040371 * l) + (0.7827717662 * m) - (0.8086757660 * s),
+        ]
+    }
+
+    pub fn from_oklaba(lab: [f32; 3], a: f32) -> Self {
+        let [lightness, lab_a, lab_b] = lab;
+        let l = (lightness + (0.3963377774 * lab_a) + (0.2158037573 * lab_b)).powi(3);
+        let m = (lightness - (0.1055613458 * lab_a) - (0.0638541728 * lab_b)).powi(3);
+        let s = (lightness - (0.0894841775 * lab_a) - (1.2914855480 * lab_b)).powi(3);
+        let r = (4.0767416621 * l) - (3.3077115913 * m) + (0.2309699292 * s);
+        let g = (-1.2684380046 * l) + (2.6097574011 * m) - (0.3413193965 * s);
+        let b = (-0.0041960863 * l) - (0.7034186147 * m) + (1.7076147010 * s);
+        Self::from_linear_rgba([r, g, b], a)
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        (1.055 * value.max(0.0).powf(1.0 / 2.4)) - 0.055
+    }
 }
 
 impl From<Color256> for Color1 {
     fn from(color: Color256) -> Self {
         let (r, g, b, a) = color.into();
-        Self::from_rgba(r as f32 / 256.0, g as f32 / 256.0, b as f32 / 256.0, a as f32 / 256.0)
+        Self::from_rgba(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0)
+    }
+}
+
+impl From<Color1> for Color256 {
+    fn from(color: Color1) -> Self {
+        let channel = |value: f32| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+        Self::from_rgba(channel(color.r), channel(color.g), channel(color.b), channel(color.a))
     }
 }
 
@@ -119,6 +353,32 @@ impl Into<[f32; 4]> for Color1 {
     }
 }
 
+impl From<Color1> for Color1Components {
+    fn from(color: Color1) -> Self {
+        Self {
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
+        }
+    }
+}
+
+// Check the range here rather than leave it to the debug_assert!() calls in from_rgba() so that a
+// bad file gives an error instead of a panic.
+impl TryFrom<Color1Components> for Color1 {
+    type Error = String;
+
+    fn try_from(components: Color1Components) -> Result<Self, Self::Error> {
+        let Color1Components { r, g, b, a } = components;
+        if [r, g, b, a].iter().all(|value| (0.0..=1.0).contains(value)) {
+            Ok(Self::from_rgba(r, g, b, a))
+        } else {
+            Err(format!("Color components must be from 0.0 to 1.0 but got r = {}, g = {}, b = {}, a = {}.", r, g, b, a))
+        }
+    }
+}
+
 /*
 impl PartialEq for Color1 {
     fn eq(&self, other: &Self) -> bool {
@@ -151,6 +411,18 @@ impl Color256 {
         Self::from_rgba(255, 255, 255, 255)
     }
 
+    // Converting to a color space and back with from_space() gives the same 8-bit color.
+    pub fn to_space(&self, space: ColorSpace) -> [f32; 3] {
+        Color1::from(*self).to_space(space)
+    }
+
+    pub fn from_space(space: ColorSpace, components: [f32; 3], a: u8) -> Self {
+        Color1::from_space(space, components, a as f32 / 255.0).into()
+    }
+
+    pub fn interpolate(&self, other: &Self, t: f32, space: ColorSpace) -> Self {
+        Color1::from(*self).interpolate(&Color1::from(*other), t, space).into()
+    }
 }
 
 impl Into<(u8, u8, u8, u8)> for Color256 {
@@ -0,0 +1,132 @@
+// Easing curves for transitions. Each one maps the fraction of the transition's time that has
+// passed, from 0.0 to 1.0, to how far the shape has moved between its start and end, starting at
+// 0.0 and ending at 1.0. In between, Elastic and Bounce curves can briefly go outside that range.
+// See https://easings.net for pictures of most of these.
+
+use std::f64::consts::PI;
+
+// How closely to solve for a cubic Bézier curve's parameter. Far finer than a pixel for any
+// transition that fits on a screen.
+const BEZIER_EPSILON: f64 = 1e-7;
+const BEZIER_NEWTON_ITERATIONS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    // Quadratic curves, starting slowly, ending slowly, or both.
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    // Overshoots and springs back into place like a plucked string.
+    ElasticIn,
+    ElasticOut,
+    // Lands and bounces a few times before coming to rest.
+    BounceIn,
+    BounceOut,
+    // A curve like the CSS cubic-bezier() timing function, running from (0, 0) to (1, 1) with
+    // control points (x1, y1) and (x2, y2). The x values must be from 0.0 to 1.0, while the y
+    // values can go outside that range to overshoot.
+    CubicBezier(f64, f64, f64, f64),
+}
+
+impl Easing {
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(2),
+            Easing::EaseInOut => if t < 0.5 { 2.0 * t * t } else { 1.0 - ((-2.0 * t) + 2.0).powi(2) / 2.0 },
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => if t < 0.5 { 4.0 * t.powi(3) } else { 1.0 - ((-2.0 * t) + 2.0).powi(3) / 2.0 },
+            Easing::ElasticIn => 1.0 - elastic_out(1.0 - t),
+            Easing::ElasticOut => elastic_out(t),
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+        }
+    }
+
+    // The same curves as the CSS timing functions of the same names.
+    pub fn css_ease() -> Self {
+        Easing::CubicBezier(0.25, 0.1, 0.25, 1.0)
+    }
+
+    pub fn css_ease_in_out() -> Self {
+        Easing::CubicBezier(0.42, 0.0, 0.58, 1.0)
+    }
+}
+
+fn elastic_out(t: f64) -> f64 {
+    if t == 0.0 || t == 1.0 {
+        return t;
+    }
+    let period = (2.0 * PI) / 3.0;
+    (2.0_f64.powf(-10.0 * t) * (((t * 10.0) - 0.75) * period).sin()) + 1.0
+}
+
+fn bounce_out(t: f64) -> f64 {
+    let (n, d) = (7.5625, 2.75);
+    if t < 1.0 / d {
+        n * t * t
+    } else if t < 2.0 / d {
+        let t = t - (1.5 / d);
+        (n * t * t) + 0.75
+    } else if t < 2.5 / d {
+        let t = t - (2.25 / d);
+        (n * t * t) + 0.9375
+    } else {
+        let t = t - (2.625 / d);
+        (n * t * t) + 0.984375
+    }
+}
+
+// Find the curve parameter where x equals t, then return y at that parameter. Newton's method
+// usually gets there in a few steps; if the slope is too flat for it, fall back to bisection, which
+// works because x always increases along the curve when x1 and x2 are between 0.0 and 1.0.
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, t: f64) -> f64 {
+    debug_assert!((0.0..=1.0).contains(&x1) && (0.0..=1.0).contains(&x2));
+    // Each coordinate is ((a * s + b) * s + c) * s for the curve parameter s.
+    let coefficients = |p1: f64, p2: f64| {
+        let c = 3.0 * p1;
+        let b = (3.0 * (p2 - p1)) - c;
+        (1.0 - c - b, b, c)
+    };
+    let (ax, bx, cx) = coefficients(x1, x2);
+    let (ay, by, cy) = coefficients(y1, y2);
+    let x_at = |s: f64| ((((ax * s) + bx) * s) + cx) * s;
+    let slope_at = |s: f64| (((3.0 * ax * s) + (2.0 * bx)) * s) + cx;
+
+    let mut s = t;
+    let mut solved = false;
+    for _ in 0..BEZIER_NEWTON_ITERATIONS {
+        let error = x_at(s) - t;
+        if error.abs() < BEZIER_EPSILON {
+            solved = true;
+            break;
+        }
+        let slope = slope_at(s);
+        if slope.abs() < BEZIER_EPSILON {
+            break;
+        }
+        s -= error / slope;
+    }
+    if !solved {
+        let (mut low, mut high) = (0.0, 1.0);
+        s = t;
+        while high - low > BEZIER_EPSILON {
+            if x_at(s) < t {
+                low = s;
+            } else {
+                high = s;
+            }
+            s = (low + high) / 2.0;
+        }
+    }
+    ((((ay * s) + by) * s) + cy) * s
+}
@@ -0,0 +1,103 @@
+// What can go wrong when reading and writing grids, saving images or opening a window. Most of the
+// crate still panics, which is fine for the demos, but a caller such as the command-line binary can
+// use these to tell a missing file from a damaged one and report it instead of crashing.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum DrawError {
+    FileNotFound {
+        file_name: String,
+    },
+    // Any other failure to read or write the file, such as a missing directory or no permission.
+    Io {
+        file_name: String,
+        source: io::Error,
+    },
+    // The width and height lines at the top of a grid file are missing or aren't numbers.
+    MalformedHeader {
+        file_name: String,
+        message: String,
+    },
+    // A cell value that isn't a number. Line numbers start at 1 and count the header lines.
+    MalformedValue {
+        file_name: String,
+        line: usize,
+        value: String,
+    },
+    // The cells in a grid file don't match the width and height in its header.
+    DimensionMismatch {
+        file_name: String,
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    Image {
+        file_name: String,
+        message: String,
+    },
+    Serialization {
+        file_name: String,
+        message: String,
+    },
+    // A scene file that's valid JSON but describes something that can't be played, like an unknown
+    // color name.
+    Scene {
+        file_name: String,
+        message: String,
+    },
+    // A file name whose extension doesn't say which of the supported formats to write.
+    UnknownFormat {
+        file_name: String,
+    },
+    // The window or graphics backend couldn't be created.
+    Backend {
+        message: String,
+    },
+}
+
+pub type DrawResult<T> = Result<T, DrawError>;
+
+impl DrawError {
+    // For reads. A NotFound error gets its own variant so that callers can treat it as "nothing
+    // saved yet".
+    pub fn from_io(file_name: &str, source: io::Error) -> Self {
+        if source.kind() == io::ErrorKind::NotFound {
+            DrawError::FileNotFound { file_name: file_name.to_string() }
+        } else {
+            DrawError::Io { file_name: file_name.to_string(), source }
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, DrawError::FileNotFound { .. })
+    }
+}
+
+impl fmt::Display for DrawError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawError::FileNotFound { file_name } => write!(f, "File \"{}\" not found.", file_name),
+            DrawError::Io { file_name, source } => write!(f, "Can't read or write \"{}\": {}", file_name, source),
+            DrawError::MalformedHeader { file_name, message } => write!(f, "Bad header in \"{}\": {}", file_name, message),
+            DrawError::MalformedValue { file_name, line, value } => write!(f, "Bad value \"{}\" on line {} of \"{}\".", value, line, file_name),
+            DrawError::DimensionMismatch { file_name, expected, actual } =>
+                write!(f, "\"{}\" should be {} x {} but has {} x {} cells.", file_name, expected.0, expected.1, actual.0, actual.1),
+            DrawError::Image { file_name, message } => write!(f, "Can't save image \"{}\": {}", file_name, message),
+            DrawError::Serialization { file_name, message } => write!(f, "Can't serialize \"{}\": {}", file_name, message),
+            DrawError::Scene { file_name, message } => write!(f, "Bad scene \"{}\": {}", file_name, message),
+            DrawError::UnknownFormat { file_name } => write!(f, "Can't tell the output format of \"{}\". Use a .png, .gif or .svg file.", file_name),
+            DrawError::Backend { message } => write!(f, "Graphics backend failed: {}", message),
+        }
+    }
+}
+
+impl Error for DrawError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DrawError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
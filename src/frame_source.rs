@@ -0,0 +1,73 @@
+// Frames pulled one at a time instead of a precomputed Vec<Frame>. A FrameSource is any iterator of
+// frames, so a Vec can still be used, but frames can also be generated on demand as the display
+// asks for them, or produced on a background thread with a bounded buffer in between. Either way
+// only a few frames exist at once so long animations of large grids run in constant memory and can
+// start as soon as the first frame is ready.
+
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::thread;
+
+use crate::*;
+
+pub type FrameSource = Box<dyn Iterator<Item = Frame>>;
+
+// The producer side of a threaded source.
+pub struct FrameSender {
+    sender: SyncSender<Frame>,
+}
+
+// The consumer side of a threaded source.
+pub struct FrameReceiver {
+    receiver: Receiver<Frame>,
+}
+
+pub fn from_vec(frames: Vec<Frame>) -> FrameSource {
+    Box::new(frames.into_iter())
+}
+
+// Call the function each time a new frame is needed until it returns None.
+pub fn from_fn<F>(next_frame: F) -> FrameSource
+    where F: FnMut() -> Option<Frame> + 'static
+{
+    Box::new(std::iter::from_fn(next_frame))
+}
+
+// Run the producer on its own thread. It can get up to buffer_size frames ahead of the display
+// before FrameSender::send() blocks.
+pub fn spawn<F>(buffer_size: usize, produce: F) -> FrameSource
+    where F: FnOnce(FrameSender) + Send + 'static
+{
+    let (sender, receiver) = sync_channel(buffer_size);
+    thread::spawn(move || produce(FrameSender { sender }));
+    Box::new(FrameReceiver { receiver })
+}
+
+// Run an existing iterator of frames on its own thread.
+pub fn spawn_iter<I>(buffer_size: usize, frames: I) -> FrameSource
+    where I: Iterator<Item = Frame> + Send + 'static
+{
+    spawn(buffer_size, move |sender| {
+        for frame in frames {
+            if !sender.send(frame) {
+                break;
+            }
+        }
+    })
+}
+
+impl FrameSender {
+    // Returns false if the display has gone away (for instance the window was closed), in which
+    // case the producer should stop.
+    pub fn send(&self, frame: Frame) -> bool {
+        self.sender.send(frame).is_ok()
+    }
+}
+
+impl Iterator for FrameReceiver {
+    type Item = Frame;
+
+    // Blocks until the producer sends another frame or finishes.
+    fn next(&mut self) -> Option<Frame> {
+        self.receiver.recv().ok()
+    }
+}
@@ -1,11 +1,13 @@
+use serde::{Serialize, Deserialize};
 
-#[derive(Clone, Copy, Debug)]
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct PointF64 {
     pub(crate) x: f64,
     pub(crate) y: f64,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Point<T>
     where T: Copy + std::cmp::PartialOrd
 {
@@ -31,6 +33,11 @@ impl PointF64 {
         let y = self.y + (y_step_size * step_index);
         Self::new(x, y)
     }
+
+    // The point a fraction t of the way to the other point. t isn't limited to 0.0 through 1.0.
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Self::new(crate::interpolate_f64(self.x, other.x, t), crate::interpolate_f64(self.y, other.y, t))
+    }
 }
 
 impl <T> Point<T>
@@ -49,6 +56,88 @@ impl <T> Point<T>
     }
 }
 
+// Split a simple polygon (one whose edges don't cross) into triangles by ear clipping. This works
+// for concave polygons, unlike a triangle fan. The points can go in either direction.
+pub fn triangulate(points: &[PointF64]) -> Vec<[PointF64; 3]> {
+    let mut triangles = vec![];
+    if points.len() < 3 {
+        return triangles;
+    }
+    let signed_area: f64 = (0..points.len())
+        .map(|index| {
+            let (a, b) = (points[index], points[(index + 1) % points.len()]);
+            (a.x * b.y) - (b.x * a.y)
+        })
+        .sum();
+    let orientation = if signed_area < 0.0 { -1.0 } else { 1.0 };
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find(|&index| {
+            let (a, b, c) = (points[remaining[(index + count - 1) % count]], points[remaining[index]], points[remaining[(index + 1) % count]]);
+            if cross(a, b, c) * orientation <= 0.0 {
+                // A reflex or flat corner can't be an ear.
+                return false;
+            }
+            remaining.iter()
+                .map(|&other| points[other])
+                .filter(|other| !same_point(*other, a) && !same_point(*other, b) && !same_point(*other, c))
+                .all(|other| !point_in_triangle(other, a, b, c))
+        });
+        // If there's no ear the polygon isn't simple, so take any corner rather than loop forever.
+        let index = ear.unwrap_or(0);
+        triangles.push([points[remaining[(index + count - 1) % count]], points[remaining[index]], points[remaining[(index + 1) % count]]]);
+        remaining.remove(index);
+    }
+    triangles.push([points[remaining[0]], points[remaining[1]], points[remaining[2]]]);
+    triangles
+}
+
+#[inline]
+fn cross(a: PointF64, b: PointF64, c: PointF64) -> f64 {
+    ((b.x - a.x) * (c.y - a.y)) - ((b.y - a.y) * (c.x - a.x))
+}
+
+#[inline]
+fn same_point(a: PointF64, b: PointF64) -> bool {
+    a.x == b.x && a.y == b.y
+}
+
+fn point_in_triangle(point: PointF64, a: PointF64, b: PointF64, c: PointF64) -> bool {
+    let (d1, d2, d3) = (cross(a, b, point), cross(b, c, point), cross(c, a, point));
+    let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_negative && has_positive)
+}
+
+// True if the point is inside the polygon by the even-odd rule.
+pub fn point_in_polygon(x: f64, y: f64, points: &[PointF64]) -> bool {
+    let mut inside = false;
+    let mut previous = match points.last() {
+        Some(point) => *point,
+        None => return false,
+    };
+    for point in points.iter() {
+        if (point.y > y) != (previous.y > y) {
+            let crossing_x = point.x + ((y - point.y) * (previous.x - point.x) / (previous.y - point.y));
+            if x < crossing_x {
+                inside = !inside;
+            }
+        }
+        previous = *point;
+    }
+    inside
+}
+
+// Distance from a point to the segment between a and b.
+pub fn distance_to_segment(x: f64, y: f64, a: PointF64, b: PointF64) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_squared = (dx * dx) + (dy * dy);
+    let t = if length_squared == 0.0 { 0.0 } else { ((((x - a.x) * dx) + ((y - a.y) * dy)) / length_squared).max(0.0).min(1.0) };
+    let (off_x, off_y) = (x - (a.x + (t * dx)), y - (a.y + (t * dy)));
+    ((off_x * off_x) + (off_y * off_y)).sqrt()
+}
+
 /*
 pub fn gradient_point64(from: PointF64, to: PointF64, step_count: usize) -> Vec<PointF64> {
     let x_values = gradient_f64(from.0, to.0, step_count);
@@ -0,0 +1,87 @@
+// Save a list of frames as an animated GIF. The frames are played through a FramePlayer onto a
+// SoftwareRenderer exactly as they would be in renderer_3::Renderer, so the additive flag and the
+// color-index palette mean the same thing here as in a window.
+
+use std::fs::File;
+use std::io::BufWriter;
+use image::{Delay, Frame as ImageFrame};
+use image::codecs::gif::{GifEncoder, Repeat};
+
+use crate::*;
+use crate::render_backend::FramePlayer;
+use crate::software_renderer::SoftwareRenderer;
+
+// Browsers and most viewers treat a delay of 0 or 1 hundredths of a second as a much longer one, so
+// this is the shortest delay we'll write.
+const DELAY_MIN_CENTISECONDS: u32 = 2;
+
+// Quantization speed for the GIF encoder, 1 (best) to 30 (fastest).
+const ENCODER_SPEED: i32 = 10;
+
+pub fn main() {
+    try_save_gif();
+}
+
+pub fn save_gif(file_name: &str, width: f64, height: f64, back_color: Color1, frames: Vec<Frame>) -> DrawResult<()> {
+    save_gif_additive_with_colors(file_name, width, height, back_color, frames, false, vec![])
+}
+
+pub fn save_gif_additive(file_name: &str, width: f64, height: f64, back_color: Color1, frames: Vec<Frame>, additive: bool) -> DrawResult<()> {
+    save_gif_additive_with_colors(file_name, width, height, back_color, frames, additive, vec![])
+}
+
+pub fn save_gif_additive_with_colors(file_name: &str, width: f64, height: f64, back_color: Color1, frames: Vec<Frame>, additive: bool, colors: impl Into<Vec<Color1>>) -> DrawResult<()> {
+    save_gif_player(file_name, width, height, back_color, FramePlayer::new(back_color, frames, additive, colors.into()))
+}
+
+// Draw each frame over a faded copy of the previous output. See FramePlayer::set_persistence().
+pub fn save_gif_persistent(file_name: &str, width: f64, height: f64, back_color: Color1, frames: Vec<Frame>, decay_per_second: f64, colors: impl Into<Vec<Color1>>) -> DrawResult<()> {
+    let mut player = FramePlayer::new(back_color, frames, false, colors.into());
+    player.set_persistence(Some(decay_per_second));
+    save_gif_player(file_name, width, height, back_color, player)
+}
+
+// Write every remaining frame from the player, which may have its own persistence or camera.
+pub fn save_gif_player(file_name: &str, width: f64, height: f64, back_color: Color1, mut player: FramePlayer) -> DrawResult<()> {
+    let file = File::create(file_name).map_err(|e| DrawError::Io { file_name: file_name.to_string(), source: e })?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), ENCODER_SPEED);
+    encoder.set_repeat(Repeat::Infinite).map_err(|e| image_util::image_error(file_name, e))?;
+
+    let mut rend = SoftwareRenderer::new(width, height, back_color);
+
+    // GIF delays are in hundredths of a second. Track the total time so far so that rounding each
+    // frame's delay doesn't make the whole animation drift from its intended length.
+    let mut elapsed_seconds = 0.0;
+    let mut written_centiseconds = 0;
+    // play_all() can't be stopped partway, so keep the first error and skip the remaining frames.
+    let mut result = Ok(());
+    player.play_all(&mut rend, |rend, seconds_to_next| {
+        if result.is_err() {
+            return;
+        }
+        elapsed_seconds += seconds_to_next;
+        let end_centiseconds = (elapsed_seconds * 100.0).round() as u32;
+        let delay_centiseconds = end_centiseconds.saturating_sub(written_centiseconds).max(DELAY_MIN_CENTISECONDS);
+        written_centiseconds += delay_centiseconds;
+        let delay = Delay::from_numer_denom_ms(delay_centiseconds * 10, 1);
+        result = encoder.encode_frame(ImageFrame::from_parts(rend.to_image(), 0, 0, delay)).map_err(|e| image_util::image_error(file_name, e));
+    });
+    result
+}
+
+fn try_save_gif() {
+    let width = 200.0;
+    let height = 200.0;
+    let frame_count = 50;
+    let colors = vec![Color1::black(), Color1::white(), Color1::red()];
+    let mut frames = vec![];
+    for frame_index in 0..frame_count {
+        let x = gradiant_f64_one(20.0, 180.0, frame_count, frame_index);
+        let shapes = vec![
+            Shape::circle_fast(x, 100.0, 15.0, 2),
+            Shape::rectangle_fast(x - 2.0, 140.0, 4.0, 4.0, 1),
+        ];
+        frames.push(Frame::new(shapes, 0.04));
+    }
+    save_gif_additive_with_colors("Test.gif", width, height, Color1::black(), frames, true, colors).unwrap();
+}
@@ -0,0 +1,225 @@
+// A smooth run of colors through any number of stops, along with a mapping from values such as the
+// counts in a grid to positions along the gradient. This takes the place of normalizing a grid with
+// Grid::copy_normalize() and then coloring it with a one-off function.
+//
+// A gradient can be passed anywhere a color function is expected:
+//
+//   let gradient = Gradient::from_colormap(Colormap::Magma).log(0.0, grid.max_value() as f64);
+//   grid.display("Counts", 2, Color1::black(), &gradient.color_func());
+//   image_util::save_grid(&grid, "Counts.png", &gradient.color_func_256(), 0, None);
+
+use num::ToPrimitive;
+
+use crate::*;
+use crate::grid::Grid;
+use crate::palette::Colormap;
+
+// How many stops to take from a colormap. Its own interpolation is finer than this, but with this
+// many stops the difference can't be seen.
+const COLORMAP_STOP_COUNT: usize = 33;
+
+#[derive(Clone, Debug)]
+pub struct Gradient {
+    // Positions from 0.0 to 1.0 in increasing order, with the first at 0.0 and the last at 1.0.
+    stops: Vec<(f64, Color1)>,
+    space: ColorSpace,
+    mapping: GradientMapping,
+}
+
+#[derive(Clone, Debug)]
+pub enum GradientMapping {
+    // min is at the start of the gradient and max at the end. Values outside the range are clamped.
+    Linear { min: f64, max: f64 },
+    // Like Linear but on a log scale, so that the low end of a range with a few very large counts
+    // still gets most of the gradient. Values are offset so that min maps to log(1.0).
+    Log { min: f64, max: f64 },
+    // Each value's position is the fraction of the sample values at or below it, so every part of
+    // the gradient is used by about the same number of cells. Holds the distinct sample values in
+    // increasing order with their positions.
+    Histogram { values: Vec<(f64, f64)> },
+    // The gradient repeats every period, as with counts taken modulo some number. Pair this with
+    // stops that start and end with the same color, such as those from Colormap::Cyclic.
+    Modular { period: f64 },
+}
+
+pub fn main() {
+    try_gradient();
+}
+
+impl Gradient {
+    // The stops don't have to start at 0.0 or end at 1.0; the first and last colors are extended to
+    // fill the rest of the range.
+    pub fn new(stops: Vec<(f64, Color1)>) -> Self {
+        assert!(!stops.is_empty());
+        assert!(stops.windows(2).all(|pair| pair[0].0 <= pair[1].0), "Gradient stops must be in order.");
+        let mut stops = stops;
+        if stops[0].0 > 0.0 {
+            stops.insert(0, (0.0, stops[0].1));
+        }
+        if stops[stops.len() - 1].0 < 1.0 {
+            stops.push((1.0, stops[stops.len() - 1].1));
+        }
+        Self {
+            stops,
+            space: ColorSpace::Srgb,
+            mapping: GradientMapping::Linear { min: 0.0, max: 1.0 },
+        }
+    }
+
+    // Colors spaced evenly from start to end.
+    pub fn from_colors(colors: &[Color1]) -> Self {
+        assert!(!colors.is_empty());
+        let divisor = (colors.len().max(2) - 1) as f64;
+        Self::new(colors.iter().enumerate().map(|(index, color)| (index as f64 / divisor, *color)).collect())
+    }
+
+    pub fn from_colormap(colormap: Colormap) -> Self {
+        let divisor = (COLORMAP_STOP_COUNT - 1) as f64;
+        Self::new((0..COLORMAP_STOP_COUNT).map(|index| {
+            let position = index as f64 / divisor;
+            (position, colormap.color(position))
+        }).collect())
+    }
+
+    pub fn grayscale() -> Self {
+        Self::from_colors(&[Color1::black(), Color1::white()])
+    }
+
+    // The color space to interpolate between stops in. The default is Srgb.
+    pub fn in_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    pub fn linear(mut self, min: f64, max: f64) -> Self {
+        self.mapping = GradientMapping::Linear { min, max };
+        self
+    }
+
+    pub fn log(mut self, min: f64, max: f64) -> Self {
+        self.mapping = GradientMapping::Log { min, max };
+        self
+    }
+
+    pub fn modular(mut self, period: f64) -> Self {
+        assert!(period > 0.0);
+        self.mapping = GradientMapping::Modular { period };
+        self
+    }
+
+    // Equalize using the values that will be colored.
+    pub fn histogram<I>(mut self, sample_values: I) -> Self
+        where I: IntoIterator<Item = f64>
+    {
+        let mut sample_values: Vec<f64> = sample_values.into_iter().collect();
+        assert!(!sample_values.is_empty());
+        sample_values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        // The lowest value goes at the start of the gradient and the highest at the end.
+        let lowest_count = sample_values.iter().take_while(|value| **value == sample_values[0]).count();
+        let divisor = (sample_values.len() - lowest_count).max(1) as f64;
+        let mut values: Vec<(f64, f64)> = vec![];
+        for (index, value) in sample_values.iter().enumerate() {
+            let position = (index + 1).saturating_sub(lowest_count) as f64 / divisor;
+            match values.last_mut() {
+                Some(last) if last.0 == *value => last.1 = position,
+                _ => values.push((*value, position)),
+            }
+        }
+        self.mapping = GradientMapping::Histogram { values };
+        self
+    }
+
+    // Equalize using the values in a grid.
+    pub fn histogram_of<T>(self, grid: &Grid<T>) -> Self
+        where T: Clone + ToPrimitive
+    {
+        let mut sample_values = Vec::with_capacity(grid.width * grid.height);
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                sample_values.push(grid.get_xy(x, y).to_f64().unwrap());
+            }
+        }
+        self.histogram(sample_values)
+    }
+
+    pub fn mapping(&self) -> &GradientMapping {
+        &self.mapping
+    }
+
+    // The position from 0.0 to 1.0 along the gradient for a value.
+    pub fn position(&self, value: f64) -> f64 {
+        let clamp = |position: f64| position.max(0.0).min(1.0);
+        match &self.mapping {
+            GradientMapping::Linear { min, max } => {
+                if max > min { clamp((value - min) / (max - min)) } else { 0.0 }
+            },
+            GradientMapping::Log { min, max } => {
+                if max > min { clamp((value - min).max(0.0).ln_1p() / (max - min).ln_1p()) } else { 0.0 }
+            },
+            GradientMapping::Histogram { values } => {
+                // Values that weren't in the sample take the position of the next lower one.
+                match values.partition_point(|(sample_value, _)| *sample_value <= value) {
+                    0 => 0.0,
+                    count => values[count - 1].1,
+                }
+            },
+            GradientMapping::Modular { period } => value.rem_euclid(*period) / period,
+        }
+    }
+
+    // The color at a position from 0.0 to 1.0 along the gradient, ignoring the mapping.
+    pub fn color_at(&self, position: f64) -> Color1 {
+        let position = position.max(0.0).min(1.0);
+        let upper = self.stops.iter().position(|(stop_position, _)| *stop_position >= position).unwrap();
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+        let ((from_position, from), (to_position, to)) = (self.stops[upper - 1], self.stops[upper]);
+        if to_position == from_position {
+            return to;
+        }
+        let t = ((position - from_position) / (to_position - from_position)) as f32;
+        from.interpolate(&to, t, self.space)
+    }
+
+    pub fn color(&self, value: f64) -> Color1 {
+        self.color_at(self.position(value))
+    }
+
+    pub fn color_256(&self, value: f64) -> Color256 {
+        self.color(value).into()
+    }
+
+    // A color function for Grid::as_frame(), Grid::display() and the like.
+    pub fn color_func<T: ToPrimitive>(&self) -> impl Fn(&T) -> Color1 + '_ {
+        move |value: &T| self.color(value.to_f64().unwrap())
+    }
+
+    // A color function for image_util::save_grid().
+    pub fn color_func_256<T: ToPrimitive>(&self) -> impl Fn(&T) -> Color256 + '_ {
+        move |value: &T| self.color_256(value.to_f64().unwrap())
+    }
+}
+
+fn try_gradient() {
+    // Counts that pile up heavily near one corner, saved with each kind of mapping side by side.
+    let size = 200;
+    let mut grid = Grid::new(size, size, 0);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = ((x * x) + (y * y)) as f64;
+            grid.set_xy(x, y, (1_000_000.0 / (distance + 10.0)) as usize);
+        }
+    }
+    let max = grid.max_value() as f64;
+
+    let magma = Gradient::from_colormap(Colormap::Magma);
+    image_util::save_grid(&grid, "Test_gradient_linear.png", &magma.clone().linear(0.0, max).color_func_256(), 0, None).unwrap();
+    image_util::save_grid(&grid, "Test_gradient_log.png", &magma.clone().log(0.0, max).color_func_256(), 0, None).unwrap();
+    image_util::save_grid(&grid, "Test_gradient_histogram.png", &magma.histogram_of(&grid).color_func_256(), 0, None).unwrap();
+
+    let stops = Gradient::new(vec![(0.0, Color1::blue()), (0.5, Color1::white()), (1.0, Color1::blue())])
+        .in_space(ColorSpace::Oklab)
+        .modular(20.0);
+    image_util::save_grid(&grid, "Test_gradient_modular.png", &stops.color_func_256(), 0, None).unwrap();
+}
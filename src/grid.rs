@@ -1,20 +1,24 @@
 use itertools::Itertools;
-use rand::{Rng, thread_rng};
+use rand::{Rng, SeedableRng, thread_rng};
+use rand::rngs::StdRng;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
 
 use crate::*;
 use crate::renderer_3::Renderer;
 use std::fs;
 use std::cmp::min;
+use std::convert::TryFrom;
 
 pub type GridCoord = Point<usize>;
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum GridLayout {
     Square,
     Hex,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum GridNeighborType {
     // https://en.wikipedia.org/wiki/Cellular_automaton
     Moore {
@@ -27,7 +31,7 @@ pub enum GridNeighborType {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum GridWrappingStyle {
     None,
     Horizontal,
@@ -35,26 +39,67 @@ pub enum GridWrappingStyle {
     Toroidal,
 }
 
-#[derive(Clone)]
+// Deserializing goes through GridData so that a file whose cells or events don't fit its width and
+// height is an error rather than a grid that panics later.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "GridData<T>")]
 pub struct Grid<T>
     where T: Clone + Sized
 {
     pub width: usize,
     pub height: usize,
     pub default_value: T,
-    pub cell_values: Vec<Vec<T>>,
+    // One row after another, so the cell at (x, y) is at (y * width) + x. Use get_ref(), row() and
+    // as_slice() to look at cells without cloning them. This replaced the public cell_values field,
+    // a Vec of rows, on purpose: code that used the field has to move to these or to the copying
+    // cell_values() method, and grids saved before the change can't be read back.
+    cells: Vec<T>,
     pub events: Vec<GridEvent<T>>,
     pub record_events: bool,
 }
 
-#[derive(Clone)]
+// The same fields as Grid, before they've been checked.
+#[derive(Deserialize)]
+struct GridData<T>
+    where T: Clone
+{
+    width: usize,
+    height: usize,
+    default_value: T,
+    cells: Vec<T>,
+    events: Vec<GridEvent<T>>,
+    record_events: bool,
+}
+
+impl <T> TryFrom<GridData<T>> for Grid<T>
+    where T: Clone
+{
+    type Error = String;
+
+    fn try_from(data: GridData<T>) -> Result<Self, Self::Error> {
+        if data.cells.len() != data.width * data.height {
+            return Err(format!("A {} x {} grid needs {} cells, not {}.", data.width, data.height, data.width * data.height, data.cells.len()));
+        }
+        for event_cell in data.events.iter().flat_map(|event| event.cells.iter()) {
+            if event_cell.coord.x >= data.width || event_cell.coord.y >= data.height {
+                return Err(format!("An event sets cell ({}, {}), which is outside the {} x {} grid.", event_cell.coord.x, event_cell.coord.y, data.width, data.height));
+            }
+        }
+        let mut grid = Self::from_cells(data.width, data.height, data.default_value, data.cells);
+        grid.events = data.events;
+        grid.record_events = data.record_events;
+        Ok(grid)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GridEvent<T>
     where T: Clone
 {
     cells: Vec<GridEventCell<T>>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GridEventCell<T>
     where T: Clone
 {
@@ -77,7 +122,7 @@ impl <T> Grid<T>
             width,
             height,
             default_value,
-            cell_values: vec![],
+            cells: vec![],
             events: vec![],
             record_events: false,
         };
@@ -85,17 +130,29 @@ impl <T> Grid<T>
         grid
     }
 
-    fn create_cells(&mut self) {
-        self.cell_values = Vec::with_capacity(self.height);
-        for _ in 0..self.height {
-            let mut row = Vec::with_capacity(self.width);
-            for _ in 0..self.width {
-                row.push(self.default_value.clone());
-            }
-            self.cell_values.push(row);
+    // Build a grid from cells already laid out one row after another.
+    pub fn from_cells(width: usize, height: usize, default_value: T, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "A {} x {} grid needs {} cells, not {}.", width, height, width * height, cells.len());
+        Self {
+            width,
+            height,
+            default_value,
+            cells,
+            events: vec![],
+            record_events: false,
         }
     }
 
+    // A copy of the cells as a Vec of rows, the layout of the old cell_values field. Prefer row() or
+    // as_slice(), which don't copy anything.
+    pub fn cell_values(&self) -> Vec<Vec<T>> {
+        self.rows().map(|row| row.to_vec()).collect()
+    }
+
+    fn create_cells(&mut self) {
+        self.cells = vec![self.default_value.clone(); self.width * self.height];
+    }
+
     pub fn new_from<U>(source_grid: &Grid<U>, default_value: T, value_func: fn(&U) -> T) -> Self
         where U: Clone
     {
@@ -103,14 +160,10 @@ impl <T> Grid<T>
             width: source_grid.width,
             height: source_grid.height,
             default_value,
-            cell_values: vec![],
+            cells: source_grid.cells.iter().map(|x| value_func(x)).collect(),
             events: vec![],
             record_events: source_grid.record_events,
         };
-        for source_row in source_grid.cell_values.iter() {
-            let row = source_row.iter().map(|x| value_func(x)).collect::<Vec<T>>();
-            grid.cell_values.push(row);
-        }
         for source_event in source_grid.events.iter() {
             let mut event: GridEvent<T> = GridEvent::new();
             for source_event_cell in source_event.cells.iter() {
@@ -140,13 +193,7 @@ impl <T> Grid<T>
         where U: Clone,
               F: Fn(&T) -> U
     {
-        let mut grid = Grid::new(self.width, self.height, default_value);
-        for y in 0..grid.height {
-            for x in 0..grid.width {
-                grid.set_xy(x, y, value_func(&self.get_xy(x, y)));
-            }
-        }
-        grid
+        Grid::from_cells(self.width, self.height, default_value, self.cells.iter().map(value_func).collect())
     }
 
     pub fn copy_with_other<O, U, F>(&self, other: &Grid<O>, value_func: &F, default_value: U) -> Grid<U>
@@ -156,13 +203,8 @@ impl <T> Grid<T>
     {
         debug_assert_eq!(self.width, other.width);
         debug_assert_eq!(self.height, other.height);
-        let mut grid = Grid::new(self.width, self.height, default_value);
-        for y in 0..grid.height {
-            for x in 0..grid.width {
-                grid.set_xy(x, y, value_func(&self.get_xy(x, y), &other.get_xy(x, y)));
-            }
-        }
-        grid
+        let cells = self.cells.iter().zip(other.cells.iter()).map(|(a, b)| value_func(a, b)).collect();
+        Grid::from_cells(self.width, self.height, default_value, cells)
     }
 
     pub fn add_event(&mut self, event: GridEvent<T>) {
@@ -174,15 +216,63 @@ impl <T> Grid<T>
     fn apply_event(&mut self, event: &GridEvent<T>) {
         debug_assert!(self.record_events);
         for event_cell in event.cells.iter() {
-            self.cell_values[event_cell.coord.y][event_cell.coord.x] = event_cell.value.clone();
+            let index = self.index_of(event_cell.coord.x, event_cell.coord.y);
+            self.cells[index] = event_cell.value.clone();
         }
     }
 
     #[inline]
-    pub fn get_xy(&self, x: usize, y: usize) -> T {
+    pub fn index_of(&self, x: usize, y: usize) -> usize {
         debug_assert!(x < self.width);
         debug_assert!(y < self.height);
-        self.cell_values[y][x].clone()
+        (y * self.width) + x
+    }
+
+    #[inline]
+    pub fn get_xy(&self, x: usize, y: usize) -> T {
+        self.get_ref(x, y).clone()
+    }
+
+    #[inline]
+    pub fn get_ref(&self, x: usize, y: usize) -> &T {
+        &self.cells[self.index_of(x, y)]
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, x: usize, y: usize) -> &mut T {
+        debug_assert!(!self.record_events);
+        let index = self.index_of(x, y);
+        &mut self.cells[index]
+    }
+
+    #[inline]
+    pub fn row(&self, y: usize) -> &[T] {
+        debug_assert!(y < self.height);
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    #[inline]
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        debug_assert!(!self.record_events);
+        debug_assert!(y < self.height);
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks() panics on a size of zero, which a grid with no columns would give it.
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    // All of the cells, one row after another.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        debug_assert!(!self.record_events);
+        &mut self.cells
     }
 
     #[inline]
@@ -192,22 +282,18 @@ impl <T> Grid<T>
 
     #[inline]
     pub fn set_xy(&mut self, x: usize, y: usize, value: T) {
-        debug_assert!(!self.record_events);
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-        self.cell_values[y][x] = value;
+        *self.get_mut(x, y) = value;
     }
 
     #[inline]
     pub fn set_by_index(&mut self, cell_index: usize, value: T) {
-        let (x, y) = cell_index_to_x_y_usize(self.width, cell_index);
-        self.set_xy(x, y, value);
+        debug_assert!(!self.record_events);
+        self.cells[cell_index] = value;
     }
 
     #[inline]
     pub fn set_coord(&mut self, coord: GridCoord, value: T) {
-        debug_assert!(!self.record_events);
-        self.cell_values[coord.y][coord.x] = value;
+        *self.get_mut(coord.x, coord.y) = value;
     }
 
     #[inline]
@@ -368,14 +454,14 @@ impl <T> Grid<T>
     {
         let block_width = display_width / self.width as f64;
         let block_height = display_height / self.height as f64;
-        let mut shapes = vec![];
+        let mut shapes = Vec::with_capacity(self.cells.len());
         let mut block_x = 0.0;
         let mut block_y = 0.0;
         for y in 0..self.height {
             for x in 0..self.width {
                 let top_left = PointF64::new(block_x, block_y);
                 let bottom_right = PointF64::new(block_x + block_width, block_y + block_width);
-                let color = value_func(&self.get_xy(x, y));
+                let color = value_func(self.get_ref(x, y));
                 shapes.push(Shape::rectangle(top_left, bottom_right, color));
                 block_x += block_width;
             }
@@ -496,7 +582,6 @@ impl <T> Grid<T>
         for y in 1..self.height / 2 {
             for x in 0..self.width {
                 self.set_xy(x, y_last - y, self.get_xy(x, y));
-                //self.cell_values[y_last - y][x] = self.cell_values[y][x].clone();
             }
         }
     }
@@ -520,9 +605,7 @@ impl <T> Grid<T>
         }
     }
 
-    pub fn random_rectangle(&self) -> GridRectangle {
-        let mut rng = thread_rng();
-
+    pub fn random_rectangle<R: Rng>(&self, rng: &mut R) -> GridRectangle {
         let mut x1 = rng.gen_range(0..self.width);
         let mut y1 = rng.gen_range(0..self.height);
         let mut x2 = rng.gen_range(0..self.width);
@@ -538,9 +621,7 @@ impl <T> Grid<T>
         rectangle
     }
 
-    pub fn random_rectangle_limit(&self, max_width: usize, max_height: usize) -> GridRectangle {
-        let mut rng = thread_rng();
-
+    pub fn random_rectangle_limit<R: Rng>(&self, rng: &mut R, max_width: usize, max_height: usize) -> GridRectangle {
         let x1 = rng.gen_range(0..self.width);
         let y1 = rng.gen_range(0..self.height);
         let x_add_max = ((self.width - x1) - 1).min(max_width - 1);
@@ -598,7 +679,7 @@ impl <T> Grid<T>
         let mut v = vec![];
         for y in 0..self.height {
             for x in 0..self.width {
-                if f(self.get_xy(x, y)) {
+                if f(self.get_ref(x, y).clone()) {
                     v.push((x, y));
                 }
             }
@@ -624,9 +705,7 @@ impl <T> Grid<T>
             assert_eq!(one_grid_width, source_grid.width);
             assert_eq!(one_grid_height, source_grid.height);
             for source_y in 0..one_grid_height {
-                for source_x in 0..one_grid_width {
-                    grid.set_xy(x_offset + source_x, y_offset + source_y, source_grid.get_xy(source_x, source_y));
-                }
+                grid.row_mut(y_offset + source_y)[x_offset..x_offset + one_grid_width].clone_from_slice(source_grid.row(source_y));
             }
             col_index += 1;
             if col_index == col_count {
@@ -641,7 +720,7 @@ impl <T> Grid<T>
 impl Grid<char> {
     pub fn print(&self, label: &str) {
         println!("\n{}", label);
-        for row in self.cell_values.iter() {
+        for row in self.rows() {
             let line = row.iter().join("  ");
             println!("{}", line);
         }
@@ -661,20 +740,18 @@ impl Grid<usize> {
 
     #[inline]
     pub fn add_xy(&mut self, x: usize, y: usize, value: usize, modulus: Option<usize>) {
-        debug_assert!(!self.record_events);
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-        let mut new_value = self.get_xy(x, y).checked_add(value).unwrap();
-        if let Some(modulus) = modulus {
-            new_value = new_value % modulus;
-        }
-        self.set_xy(x, y, new_value);
+        let cell = self.get_mut(x, y);
+        *cell = add_mod(*cell, value, modulus);
     }
 
     pub fn add_grid_at_x_y(&mut self, x: usize, y: usize, other: &Self, modulus: Option<usize>) {
+        debug_assert!(!self.record_events);
+        debug_assert!(x + other.width <= self.width);
+        debug_assert!(y + other.height <= self.height);
         for other_y in 0..other.height {
-            for other_x in 0..other.width {
-                self.add_xy(x + other_x, y + other_y, other.get_xy(other_x, other_y), modulus);
+            let row = &mut self.row_mut(y + other_y)[x..x + other.width];
+            for (cell, other_value) in row.iter_mut().zip(other.row(other_y).iter()) {
+                *cell = add_mod(*cell, *other_value, modulus);
             }
         }
     }
@@ -682,11 +759,9 @@ impl Grid<usize> {
     pub fn min_max(&self) -> (usize, usize) {
         let mut min = usize::MAX;
         let mut max = usize::MIN;
-        for row in self.cell_values.iter() {
-            for value in row.iter() {
-                min = min.min(*value);
-                max = max.max(*value);
-            }
+        for value in self.cells.iter() {
+            min = min.min(*value);
+            max = max.max(*value);
         }
         (min, max)
     }
@@ -702,13 +777,35 @@ impl Grid<usize> {
         let mut shapes = vec![];
         let mut block_x = 0.0;
         let mut block_y = 0.0;
+        // Hexagons with their points up, sized so that each row of cells overlaps the one above by a
+        // quarter of a hexagon's height and the rows tile without gaps.
+        let hex_point_height = (block_height * 2.0) / 3.0;
+        let hex_side_height = block_height / 3.0;
         for y in 0..self.height {
             let x_offset = match layout {
                 GridLayout::Square => 0.0,
                 GridLayout::Hex => if y % 2 == 0 { 0.0 } else { block_half_width }
             };
             for x in 0..self.width {
-                shapes.push(Shape::rectangle_fast(block_x + x_offset, block_y, block_width, block_height, self.get_xy(x, y)));
+                let color_index = *self.get_ref(x, y);
+                match layout {
+                    GridLayout::Square => shapes.push(Shape::rectangle_fast(block_x, block_y, block_width, block_height, color_index)),
+                    GridLayout::Hex => {
+                        // As with RectangleFast, color index 0 is the background.
+                        if color_index != 0 {
+                            let (center_x, center_y) = (block_x + x_offset + block_half_width, block_y + (block_height / 2.0));
+                            let points = vec![
+                                PointF64::new(center_x, center_y - hex_point_height),
+                                PointF64::new(center_x + block_half_width, center_y - hex_side_height),
+                                PointF64::new(center_x + block_half_width, center_y + hex_side_height),
+                                PointF64::new(center_x, center_y + hex_point_height),
+                                PointF64::new(center_x - block_half_width, center_y + hex_side_height),
+                                PointF64::new(center_x - block_half_width, center_y - hex_side_height),
+                            ];
+                            shapes.push(Shape::polygon_fast(points, color_index));
+                        }
+                    },
+                }
                 block_x += block_width;
             }
             block_y += block_height;
@@ -726,7 +823,7 @@ impl Grid<usize> {
         let mut block_y = 0.0;
         for y in 0..self.height {
             for x in 0..self.width {
-                shapes.push(Shape::rectangle_fast(block_x, block_y + caption_height, block_width, block_height, self.get_xy(x, y)));
+                shapes.push(Shape::rectangle_fast(block_x, block_y + caption_height, block_width, block_height, *self.get_ref(x, y)));
                 block_x += block_width;
             }
             block_y += block_height;
@@ -736,38 +833,66 @@ impl Grid<usize> {
         frame
     }
 
-    pub fn write(&self, full_file_name: &str) {
+    pub fn write(&self, full_file_name: &str) -> DrawResult<()> {
         // let start_time = Instant::now();
         let content = format!("{}\n{}\n{}", self.width, self.height,
-                              self.cell_values.iter()
+                              self.rows()
                                   .map(|row| row.iter().join("\t"))
                                   .join("\n"));
-        fs::write(full_file_name, content).unwrap();
+        fs::write(full_file_name, content).map_err(|e| DrawError::Io { file_name: full_file_name.to_string(), source: e })
         //rintln!("Grid::write({}): {:?}", full_file_name, Instant::now() - start_time);
     }
 
-    pub fn read_optional(full_file_name: &str) -> Option<Grid<usize>> {
+    pub fn read(full_file_name: &str) -> DrawResult<Grid<usize>> {
         // let start_time = Instant::now();
-        let read_result = fs::read_to_string(full_file_name);
-        match read_result {
-            Ok(content) => {
-                //rintln!("Grid::read_optional({}): read file: {:?}", full_file_name, Instant::now() - start_time);
-                // let start_time = Instant::now();
-                let lines = content.split("\n").collect::<Vec<_>>();
-                let width = lines[0].parse::<usize>().unwrap();
-                let height= lines[1].parse::<usize>().unwrap();
-                let values = lines[2..].iter()
-                    .map(|line| line.split("\t").map(|value| value.parse::<usize>().unwrap()).collect::<Vec<_>>())
-                    .collect::<Vec<_>>();
-                let mut grid = Grid::new(width, height, 0);
-                grid.cell_values = values;
-                //rintln!("Grid::read_optional({}): build grid: {:?}", full_file_name, Instant::now() - start_time);
-                Some(grid)
-            },
-            Err(_) => {
-                //rintln!("Grid::read_optional({}): not found.", full_file_name);
-                None
-            },
+        let content = fs::read_to_string(full_file_name).map_err(|e| DrawError::from_io(full_file_name, e))?;
+        //rintln!("Grid::read({}): read file: {:?}", full_file_name, Instant::now() - start_time);
+        // let start_time = Instant::now();
+        let lines = content.split("\n").map(|line| line.trim_end_matches('\r')).collect::<Vec<_>>();
+        let parse_header = |index: usize, label: &str| -> DrawResult<usize> {
+            let line = lines.get(index).ok_or_else(|| DrawError::MalformedHeader {
+                file_name: full_file_name.to_string(),
+                message: format!("missing the {} line", label),
+            })?;
+            line.parse::<usize>().map_err(|_| DrawError::MalformedHeader {
+                file_name: full_file_name.to_string(),
+                message: format!("the {} \"{}\" isn't a number", label, line),
+            })
+        };
+        let width = parse_header(0, "width")?;
+        let height = parse_header(1, "height")?;
+        // A trailing newline at the end of the file isn't an extra row.
+        let rows = if lines.len() > 2 && lines.last() == Some(&"") { &lines[2..lines.len() - 1] } else { &lines[2..] };
+        let mut values = Vec::with_capacity(rows.len() * width);
+        for (row_index, line) in rows.iter().enumerate() {
+            let row = if line.is_empty() { vec![] } else { line.split("\t").collect::<Vec<_>>() };
+            if row.len() != width {
+                return Err(DrawError::DimensionMismatch { file_name: full_file_name.to_string(), expected: (width, height), actual: (row.len(), rows.len()) });
+            }
+            let row = row.iter()
+                .map(|value| value.parse::<usize>().map_err(|_| DrawError::MalformedValue {
+                    file_name: full_file_name.to_string(),
+                    line: row_index + 3,
+                    value: value.to_string(),
+                }))
+                .collect::<DrawResult<Vec<_>>>()?;
+            values.extend(row);
+        }
+        if rows.len() != height {
+            return Err(DrawError::DimensionMismatch { file_name: full_file_name.to_string(), expected: (width, height), actual: (width, rows.len()) });
+        }
+        let grid = Grid::from_cells(width, height, 0, values);
+        //rintln!("Grid::read({}): build grid: {:?}", full_file_name, Instant::now() - start_time);
+        Ok(grid)
+    }
+
+    // Like read() except that a missing file is Ok(None), for grids that are cached and rebuilt if
+    // they haven't been saved yet. A file that's there but can't be parsed is still an error.
+    pub fn read_optional(full_file_name: &str) -> DrawResult<Option<Grid<usize>>> {
+        match Self::read(full_file_name) {
+            Ok(grid) => Ok(Some(grid)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
         }
     }
 
@@ -790,13 +915,7 @@ impl Grid<usize> {
     }
 
     pub fn max_value(&self) -> usize {
-        let mut max = 0;
-        for y in 0..self.height {
-            for x in 0..self.width {
-                max = max.max(self.get_xy(x, y));
-            }
-        }
-        max
+        self.cells.iter().copied().max().unwrap_or(0)
     }
 
     pub fn assert_equal(&self, other: &Self) {
@@ -822,13 +941,54 @@ impl Grid<bool> {
     }
 }
 
+// Unlike write() and read_optional() these work for any cell type and keep the recorded events.
+impl <T> Grid<T>
+    where T: Clone + Serialize + DeserializeOwned
+{
+    pub fn write_json(&self, full_file_name: &str) -> DrawResult<()> {
+        let content = serde_json::to_string(self).map_err(|e| Self::serialization_error(full_file_name, e))?;
+        fs::write(full_file_name, content).map_err(|e| DrawError::Io { file_name: full_file_name.to_string(), source: e })
+    }
+
+    pub fn read_json_optional(full_file_name: &str) -> DrawResult<Option<Self>> {
+        match fs::read_to_string(full_file_name) {
+            Ok(content) => serde_json::from_str(&content).map(Some).map_err(|e| Self::serialization_error(full_file_name, e)),
+            Err(e) => Self::not_found_as_none(full_file_name, e),
+        }
+    }
+
+    // Much smaller and faster than JSON for large grids.
+    pub fn write_binary(&self, full_file_name: &str) -> DrawResult<()> {
+        let content = bincode::serialize(self).map_err(|e| Self::serialization_error(full_file_name, e))?;
+        fs::write(full_file_name, content).map_err(|e| DrawError::Io { file_name: full_file_name.to_string(), source: e })
+    }
+
+    pub fn read_binary_optional(full_file_name: &str) -> DrawResult<Option<Self>> {
+        match fs::read(full_file_name) {
+            Ok(content) => bincode::deserialize(&content).map(Some).map_err(|e| Self::serialization_error(full_file_name, e)),
+            Err(e) => Self::not_found_as_none(full_file_name, e),
+        }
+    }
+
+    fn serialization_error(full_file_name: &str, error: impl std::fmt::Display) -> DrawError {
+        DrawError::Serialization { file_name: full_file_name.to_string(), message: error.to_string() }
+    }
+
+    fn not_found_as_none(full_file_name: &str, error: std::io::Error) -> DrawResult<Option<Self>> {
+        match DrawError::from_io(full_file_name, error) {
+            e if e.is_not_found() => Ok(None),
+            e => Err(e),
+        }
+    }
+}
+
 impl <T> PartialEq for Grid<T>
     where T: Clone + PartialEq
 {
     fn eq(&self, other: &Self) -> bool {
         self.width == other.width
         && self.height == other.height
-        && self.cell_values == other.cell_values
+        && self.cells == other.cells
     }
 }
 
@@ -884,6 +1044,8 @@ pub fn main() {
     // test_reflect_copy();
     // test_reflect_copy_non_square();
     // test_compare();
+    // test_serialize();
+    // test_read_errors();
 }
 
 #[allow(dead_code)]
@@ -929,10 +1091,11 @@ fn test_rectangle_intersects_wedge() {
     let mut grid = Grid::new(size, size, Color1::white());
     grid.record_events = false;
 
+    let mut rng = thread_rng();
     for _ in 0..rectangle_count {
-        // let rectangle = grid.random_rectangle();
-        let rectangle = grid.random_rectangle_limit(rectangle_max_size, rectangle_max_size);
-        let color = if grid.rectangle_intersects_wedge(&rectangle) { Color1::random(1.0) } else { Color1::random(0.2) };
+        // let rectangle = grid.random_rectangle(&mut rng);
+        let rectangle = grid.random_rectangle_limit(&mut rng, rectangle_max_size, rectangle_max_size);
+        let color = if grid.rectangle_intersects_wedge(&rectangle) { Color1::random(&mut rng, 1.0) } else { Color1::random(&mut rng, 0.2) };
         grid.outline_rectangle(&rectangle, &color);
     }
     grid.display("test_rectangle_intersects_wedge()", block_size, back_color,&|value| *value);
@@ -961,7 +1124,7 @@ fn test_reflect_copy() {
     let mut grid = Grid::new(size, size, Color1::white());
     grid.record_events = false;
 
-    print_elapsed_time("fill_grid_with_shapes", || fill_grid_with_shapes(&mut grid, rectangle_count, rectangle_max_size, rectangle_max_size));
+    print_elapsed_time("fill_grid_with_shapes", || fill_grid_with_shapes(&mut thread_rng(), &mut grid, rectangle_count, rectangle_max_size, rectangle_max_size));
     print_elapsed_time("fill_grid_non_wedge", || fill_grid_non_wedge(&mut grid, &Color1::black()));
     print_elapsed_time("reflect_copy_wedge", || grid.reflect_copy_wedge());
     print_elapsed_time("reflect_copy_top_left_quarter", || grid.reflect_copy_top_left_quarter());
@@ -983,7 +1146,7 @@ fn test_reflect_copy_non_square() {
     let mut grid = Grid::new(width, height, Color1::white());
     grid.record_events = false;
 
-    print_elapsed_time("fill_grid_with_shapes", || fill_grid_with_shapes(&mut grid, rectangle_count, rectangle_max_size, rectangle_max_size));
+    print_elapsed_time("fill_grid_with_shapes", || fill_grid_with_shapes(&mut thread_rng(), &mut grid, rectangle_count, rectangle_max_size, rectangle_max_size));
     print_elapsed_time("reflect_copy_top_left_quarter", || grid.reflect_copy_top_left_quarter());
     print_elapsed_time("reflect_copy_top_half", || grid.reflect_copy_top_half());
 
@@ -993,10 +1156,62 @@ fn test_reflect_copy_non_square() {
 #[allow(dead_code)]
 
 #[allow(dead_code)]
-fn fill_grid_with_shapes(grid: &mut Grid<Color1>, rectangle_count: usize, rectangle_max_width: usize, rectangle_max_height: usize) {
+fn test_serialize() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut grid = Grid::new(200, 100, Color1::white());
+    fill_grid_with_shapes(&mut rng, &mut grid, 20, 50, 50);
+    grid.record_events = true;
+    for index in 0..10 {
+        let mut event = GridEvent::new();
+        event.set_cell(GridCoord::new(index * 10, index * 5), Color1::random(&mut rng, 1.0));
+        event.set_cell(GridCoord::new(index * 20, index * 10), Color1::random(&mut rng, 1.0));
+        grid.add_event(event);
+    }
+
+    let json_file_name = "grid_test.json";
+    let binary_file_name = "grid_test.bin";
+    grid.write_json(json_file_name).unwrap();
+    grid.write_binary(binary_file_name).unwrap();
+    for (label, file_name, read_grid) in [("JSON", json_file_name, Grid::<Color1>::read_json_optional(json_file_name).unwrap().unwrap()),
+                                          ("binary", binary_file_name, Grid::<Color1>::read_binary_optional(binary_file_name).unwrap().unwrap())].iter() {
+        // Color1 doesn't implement PartialEq so compare the components.
+        let same_cells = read_grid.as_slice().iter().zip(grid.as_slice().iter())
+            .all(|(read_value, value)| read_value.for_render == value.for_render);
+        let file_size = fs::metadata(file_name).unwrap().len();
+        println!("{}: {} bytes, same cells = {}, event count = {}", label, file_size, same_cells, read_grid.events.len());
+        assert!(same_cells);
+        assert_eq!(grid.events.len(), read_grid.events.len());
+    }
+    fs::remove_file(json_file_name).unwrap();
+    fs::remove_file(binary_file_name).unwrap();
+}
+
+#[allow(dead_code)]
+fn test_read_errors() {
+    let file_name = "grid_test.txt";
+    let cases = [("good", "3\n2\n1\t2\t3\n4\t5\t6\n"),
+                 ("bad width", "three\n2\n1\t2\t3\n4\t5\t6"),
+                 ("no height", "3"),
+                 ("bad value", "3\n2\n1\t2\t3\n4\tx\t6"),
+                 ("short row", "3\n2\n1\t2\t3\n4\t5"),
+                 ("extra row", "3\n2\n1\t2\t3\n4\t5\t6\n7\t8\t9")];
+    for (label, content) in cases.iter() {
+        fs::write(file_name, content).unwrap();
+        match Grid::read(file_name) {
+            Ok(grid) => println!("{}: {} x {}", label, grid.width, grid.height),
+            Err(e) => println!("{}: {}", label, e),
+        }
+    }
+    fs::remove_file(file_name).unwrap();
+    assert!(Grid::read_optional(file_name).unwrap().is_none());
+    assert!(Grid::read(file_name).err().unwrap().is_not_found());
+}
+
+#[allow(dead_code)]
+fn fill_grid_with_shapes<R: Rng>(rng: &mut R, grid: &mut Grid<Color1>, rectangle_count: usize, rectangle_max_width: usize, rectangle_max_height: usize) {
     for _ in 0..rectangle_count {
-        let rectangle = grid.random_rectangle_limit(rectangle_max_width, rectangle_max_height);
-        grid.outline_rectangle(&rectangle, &Color1::random(0.5));
+        let rectangle = grid.random_rectangle_limit(rng, rectangle_max_width, rectangle_max_height);
+        grid.outline_rectangle(&rectangle, &Color1::random(rng, 0.5));
     }
 }
 
@@ -1011,6 +1226,15 @@ fn fill_grid_non_wedge(grid: &mut Grid<Color1>, value: &Color1) {
     }
 }
 
+#[inline]
+fn add_mod(a: usize, b: usize, modulus: Option<usize>) -> usize {
+    let value = a.checked_add(b).unwrap();
+    match modulus {
+        Some(modulus) => value % modulus,
+        None => value,
+    }
+}
+
 pub fn count_to_char(count: &usize) -> char {
     //bg!(*count, *count as u32);
     match *count {
@@ -1096,3 +1320,25 @@ pub fn count_to_true_for_white(count: &usize) -> bool {
 pub fn true_for_white_to_count(b: bool) -> usize {
     if b { 1 } else { 0 }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_round_trips_a_grid() {
+        let mut grid = Grid::new(3, 2, 0usize);
+        grid.set_xy(2, 1, 7);
+        let read_grid: Grid<usize> = serde_json::from_str(&serde_json::to_string(&grid).unwrap()).unwrap();
+        assert!(read_grid == grid);
+        assert_eq!(vec![vec![0, 0, 0], vec![0, 0, 7]], read_grid.cell_values());
+    }
+
+    #[test]
+    fn deserialize_rejects_cells_that_dont_fit() {
+        let json = r#"{"width":3,"height":2,"default_value":0,"cells":[0,0,0,0,0],"events":[],"record_events":false}"#;
+        assert!(serde_json::from_str::<Grid<usize>>(json).is_err());
+        let json = r#"{"width":1,"height":1,"default_value":0,"cells":[0],"events":[{"cells":[{"coord":{"x":1,"y":0},"value":5}]}],"record_events":true}"#;
+        assert!(serde_json::from_str::<Grid<usize>>(json).is_err());
+    }
+}
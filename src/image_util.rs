@@ -1,12 +1,13 @@
-use image::{RgbImage, Rgb};
+use image::{ImageError, RgbImage, Rgb};
 use crate::*;
 use crate::grid::*;
+use crate::palette::Palette;
 
 pub fn main() {
     try_create_image_file();
 }
 
-pub fn save_grid<T, F>(grid: &Grid<T>, file_name: &str, color_func: &F, border_width: usize, border_color: Option<Color256>)
+pub fn save_grid<T, F>(grid: &Grid<T>, file_name: &str, color_func: &F, border_width: usize, border_color: Option<Color256>) -> DrawResult<()>
     where T: Clone,
           F: Fn(&T) -> Color256
 {
@@ -32,7 +33,19 @@ pub fn save_grid<T, F>(grid: &Grid<T>, file_name: &str, color_func: &F, border_w
         }
     }
 
-    img.save(file_name).unwrap();
+    img.save(file_name).map_err(|e| image_error(file_name, e))
+}
+
+pub fn image_error(file_name: &str, error: ImageError) -> DrawError {
+    match error {
+        ImageError::IoError(e) => DrawError::Io { file_name: file_name.to_string(), source: e },
+        e => DrawError::Image { file_name: file_name.to_string(), message: e.to_string() },
+    }
+}
+
+// Save a grid of color indexes such as those used with the *Fast shapes.
+pub fn save_grid_palette(grid: &Grid<usize>, file_name: &str, palette: &Palette, border_width: usize, border_color: Option<Color256>) -> DrawResult<()> {
+    save_grid(grid, file_name, &|index: &usize| palette.color_256(*index), border_width, border_color)
 }
 
 pub fn color_rgb_to_rgb(color_rgb: &Color256) -> Rgb<u8> {
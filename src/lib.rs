@@ -17,32 +17,52 @@ pub mod animation_test;
 pub mod animator;
 pub mod barnsley_fern_animated;
 pub mod barnsley_fern_raster;
+pub mod bitmap_font;
+pub mod camera;
 pub mod carpet;
 pub mod cave_cell;
 pub mod cell_auto;
 pub mod color;
+pub mod easing;
+pub mod error;
+pub mod frame_source;
 pub mod geometry;
+pub mod gif_export;
+pub mod gradient;
 pub mod grid;
 pub mod image_util;
+pub mod output;
+pub mod palette;
+pub mod progress;
 // pub mod renderer_1;
 // pub mod renderer_2;
 pub mod renderer_3;
+pub mod render_backend;
+pub mod scene;
 pub mod shape;
+pub mod software_renderer;
+pub mod svg_export;
+pub mod timeline;
 pub mod try_fractal;
 pub mod try_transition;
 // pub mod try_renderer;
 pub mod voronoi;
 
 pub use color::*;
+pub use error::{DrawError, DrawResult};
 // pub use grid::*;
 pub use geometry::*;
 pub use shape::*;
 use std::time::Instant;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Frame {
     shapes: ShapeList,
     seconds_to_next: f64,
+    blend_mode: BlendMode,
 }
 
 impl Frame {
@@ -50,8 +70,30 @@ impl Frame {
         Frame {
             shapes,
             seconds_to_next,
+            blend_mode: BlendMode::Normal,
         }
     }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    // The blend mode for every shape in the frame other than those inside a Shape::Blend.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    // Write the caption in the strip of the given height across the top of the frame, such as the
+    // one left by Grid::as_frame_color_index_captioned().
+    pub fn add_caption(&mut self, display_width: f64, caption_height: f64, caption: &str, color: Color1) {
+        // Make the text as large as will fit inside a margin, measuring it at size 1.0 first.
+        let margin = caption_height * 0.2;
+        let size_for_height = (caption_height - (margin * 2.0)) / bitmap_font::text_height(caption, 1.0);
+        let size_for_width = (display_width - (margin * 2.0)) / bitmap_font::text_width(caption, 1.0).max(1.0);
+        let size = size_for_height.min(size_for_width);
+        let y = (caption_height - bitmap_font::text_height(caption, size)) / 2.0;
+        self.shapes.push(Shape::text_xy(margin, y, size, color, caption));
+    }
 }
 
 pub fn gradiant_f64_one(from: f64, to: f64, step_count: usize, step_index: usize) -> f64 {
@@ -62,6 +104,11 @@ pub fn gradiant_f64_one(from: f64, to: f64, step_count: usize, step_index: usize
     from + (step_size * step_index)
 }
 
+#[inline]
+pub fn interpolate_f64(from: f64, to: f64, t: f64) -> f64 {
+    from + ((to - from) * t)
+}
+
 pub fn gradient_f64(from: f64, to: f64, step_count: usize) -> Vec<f64> {
     let step_size = (to - from) / step_count as f64;
     let mut v = vec![from];
@@ -75,12 +122,22 @@ pub fn gradient_f64(from: f64, to: f64, step_count: usize) -> Vec<f64> {
     v
 }
 
+// A random number generator for the generators that take one, so that a run can be repeated
+// exactly by passing the same seed again. Without a seed one is picked at random. To be able to
+// repeat a run that wasn't given a seed, get one from pick_seed() first and show it.
+pub fn seeded_rng(seed: Option<u64>) -> StdRng {
+    StdRng::seed_from_u64(pick_seed(seed))
+}
+
+pub fn pick_seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| rand::thread_rng().gen())
+}
+
+// See progress::time_phase() to report the time somewhere other than the console.
 pub fn print_elapsed_time<F>(label: &str, operation: F)
     where F: FnOnce()
 {
-    let start_time = Instant::now();
-    operation();
-    println!("{}: {:?}", label, Instant::now() - start_time);
+    progress::time_phase(&mut progress::ConsoleProgress, label, operation);
 }
 
 #[inline]
@@ -23,6 +23,14 @@ fn main() {
     // draw::voronoi::parabola::main();
     // file::write_file("D:\\Test.txt", "abc");
     // draw::image_util::main();
+    // draw::software_renderer::main();
+    // draw::gif_export::main();
+    // draw::svg_export::main();
+    // draw::camera::main();
+    // draw::palette::main();
+    // draw::gradient::main();
+    // draw::timeline::main();
+    // draw::scene::main();
 
     // let mut file = File::create("D:\\Test.txt").unwrap();
     // let content = "def";
@@ -0,0 +1,68 @@
+// Where to send an animation: a window, or a file in a format chosen by its extension. A PNG or SVG
+// file holds how the last frame looks after the whole animation has played, so with additive or
+// persistent playback it includes everything drawn before. A GIF holds every frame.
+
+use std::fs;
+use std::path::Path;
+
+use crate::*;
+use crate::render_backend::FramePlayer;
+use crate::software_renderer::SoftwareRenderer;
+use crate::svg_export::SvgBackend;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Output {
+    Window,
+    Png(String),
+    Gif(String),
+    Svg(String),
+}
+
+impl Output {
+    // A window if there's no file name, otherwise a file of the type given by the extension.
+    pub fn from_file_name(file_name: Option<&str>) -> DrawResult<Self> {
+        let file_name = match file_name {
+            Some(file_name) => file_name,
+            None => return Ok(Output::Window),
+        };
+        let extension = Path::new(file_name).extension().and_then(|extension| extension.to_str()).unwrap_or("").to_lowercase();
+        match extension.as_str() {
+            "png" => Ok(Output::Png(file_name.to_string())),
+            "gif" => Ok(Output::Gif(file_name.to_string())),
+            "svg" => Ok(Output::Svg(file_name.to_string())),
+            _ => Err(DrawError::UnknownFormat { file_name: file_name.to_string() }),
+        }
+    }
+
+    pub fn display(&self, title: &str, width: f64, height: f64, back_color: Color1, frames: Vec<Frame>) {
+        self.try_display(title, width, height, back_color, frames).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    // For additive or persistent playback or a palette, set up a FramePlayer and pass it here.
+    pub fn display_player(&self, title: &str, width: f64, height: f64, back_color: Color1, player: FramePlayer) {
+        self.try_display_player(title, width, height, back_color, player).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    // The try_ versions return an error if the window can't be opened or the file can't be written.
+
+    pub fn try_display(&self, title: &str, width: f64, height: f64, back_color: Color1, frames: Vec<Frame>) -> DrawResult<()> {
+        self.try_display_player(title, width, height, back_color, FramePlayer::new(back_color, frames, false, vec![]))
+    }
+
+    pub fn try_display_player(&self, title: &str, width: f64, height: f64, back_color: Color1, mut player: FramePlayer) -> DrawResult<()> {
+        match self {
+            Output::Window => renderer_3::Renderer::try_display_player(title, width, height, player),
+            Output::Gif(file_name) => gif_export::save_gif_player(file_name, width, height, back_color, player),
+            Output::Png(file_name) => {
+                let mut rend = SoftwareRenderer::new(width, height, back_color);
+                player.play_all(&mut rend, |_, _| {});
+                rend.to_image().save(file_name).map_err(|e| image_util::image_error(file_name, e))
+            },
+            Output::Svg(file_name) => {
+                let mut backend = SvgBackend::new(width, height);
+                player.play_all(&mut backend, |_, _| {});
+                fs::write(file_name, backend.to_svg()).map_err(|e| DrawError::Io { file_name: file_name.to_string(), source: e })
+            },
+        }
+    }
+}
@@ -0,0 +1,263 @@
+// A list of colors for the *Fast shapes, which refer to colors by index. The first slots are
+// reserved for colors with a fixed meaning, such as the background or the percolating cluster, and
+// can be looked up by name. After them come any number of extra colors, either random or sampled
+// from a colormap, which are used for things like groups or values in a range.
+
+use std::collections::BTreeMap;
+use rand::Rng;
+
+use crate::*;
+
+// Ten evenly spaced samples of the matplotlib colormaps of the same names. Colors in between are
+// interpolated.
+const VIRIDIS: [[u8; 3]; 10] = [
+    [0x44, 0x01, 0x54],
+    [0x48, 0x28, 0x78],
+    [0x3e, 0x49, 0x89],
+    [0x31, 0x68, 0x8e],
+    [0x26, 0x82, 0x8e],
+    [0x1f, 0x9e, 0x89],
+    [0x35, 0xb7, 0x79],
+    [0x6e, 0xce, 0x58],
+    [0xb5, 0xde, 0x2b],
+    [0xfd, 0xe7, 0x25],
+];
+
+const MAGMA: [[u8; 3]; 10] = [
+    [0x00, 0x00, 0x04],
+    [0x18, 0x0f, 0x3d],
+    [0x44, 0x0f, 0x76],
+    [0x72, 0x1f, 0x81],
+    [0x9e, 0x2f, 0x7f],
+    [0xcd, 0x40, 0x71],
+    [0xf1, 0x60, 0x5d],
+    [0xfd, 0x96, 0x68],
+    [0xfe, 0xca, 0x8d],
+    [0xfc, 0xfd, 0xbf],
+];
+
+// The golden angle in radians, and how far from gray the colors from add_distinct_colors() are in
+// Oklab.
+const DISTINCT_HUE_STEP: f32 = 2.399_963;
+const DISTINCT_CHROMA: f32 = 0.12;
+
+#[derive(Clone, Debug, Default)]
+pub struct Palette {
+    colors: Vec<Color1>,
+    reserved: BTreeMap<String, usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Colormap {
+    // Dark blue through green to yellow, evenly increasing in lightness.
+    Viridis,
+    // Black through purple and orange to pale yellow.
+    Magma,
+    Grayscale,
+    // Wraps around so that 0.0 and 1.0 are the same color, for values like angles or phases.
+    Cyclic,
+}
+
+pub fn main() {
+    try_palette();
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // A palette with the given reserved slots in order, so the first one is index 0.
+    pub fn with_reserved(slots: &[(&str, Color1)]) -> Self {
+        let mut palette = Self::new();
+        for (name, color) in slots.iter() {
+            palette.reserve(name, *color);
+        }
+        palette
+    }
+
+    // A palette with no reserved slots and the given number of colors sampled evenly from the
+    // colormap.
+    pub fn from_colormap(colormap: Colormap, count: usize) -> Self {
+        let mut palette = Self::new();
+        palette.add_colormap(colormap, count);
+        palette
+    }
+
+    // Add a named slot and return its index. Reserved slots have to be added before any extra
+    // colors.
+    pub fn reserve(&mut self, name: &str, color: Color1) -> usize {
+        assert_eq!(self.reserved.len(), self.colors.len(), "Reserved slots must come before extra colors.");
+        assert!(!self.reserved.contains_key(name), "Palette slot \"{}\" is already reserved.", name);
+        let index = self.colors.len();
+        self.colors.push(color);
+        self.reserved.insert(name.to_string(), index);
+        index
+    }
+
+    // Add colors with each channel chosen at random between color_min and color_max.
+    pub fn add_random_colors<R: Rng>(&mut self, rng: &mut R, count: usize, color_min: f32, color_max: f32) {
+        for _ in 0..count {
+            self.colors.push(Color1::from_rgb(rng.gen_range(color_min..color_max), rng.gen_range(color_min..color_max), rng.gen_range(color_min..color_max)));
+        }
+    }
+
+    // Add colors that are easier to tell apart than random ones. Each hue is a golden angle around
+    // the Oklab color wheel from the one before, so any run of neighboring colors is spread out,
+    // and the lightness is chosen at random between lightness_min and lightness_max (0.0 to 1.0).
+    pub fn add_distinct_colors<R: Rng>(&mut self, rng: &mut R, count: usize, lightness_min: f32, lightness_max: f32) {
+        for _ in 0..count {
+            let hue = self.extra_count() as f32 * DISTINCT_HUE_STEP;
+            let lightness = if lightness_max > lightness_min { rng.gen_range(lightness_min..lightness_max) } else { lightness_min };
+            let (a, b) = (DISTINCT_CHROMA * hue.cos(), DISTINCT_CHROMA * hue.sin());
+            self.colors.push(Color1::from_space(ColorSpace::Oklab, [lightness, a, b], 1.0));
+        }
+    }
+
+    // Add colors sampled evenly from the colormap, from one end to the other. For a cyclic
+    // colormap the last sample stops short of wrapping around to the first color.
+    pub fn add_colormap(&mut self, colormap: Colormap, count: usize) {
+        let divisor = if colormap == Colormap::Cyclic { count } else { count.max(2) - 1 };
+        for index in 0..count {
+            self.colors.push(colormap.color(index as f64 / divisor as f64));
+        }
+    }
+
+    pub fn add_color(&mut self, color: Color1) -> usize {
+        self.colors.push(color);
+        self.colors.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    // Also the index of the first extra color.
+    pub fn reserved_count(&self) -> usize {
+        self.reserved.len()
+    }
+
+    pub fn extra_count(&self) -> usize {
+        self.colors.len() - self.reserved.len()
+    }
+
+    // The index of a reserved slot. Panics if there's no such slot, so for names that don't come
+    // from one of the SLOT_* constants next to the palette functions use try_index() instead.
+    pub fn index(&self, name: &str) -> usize {
+        match self.try_index(name) {
+            Some(index) => index,
+            None => panic!("No palette slot named \"{}\".", name),
+        }
+    }
+
+    pub fn try_index(&self, name: &str) -> Option<usize> {
+        self.reserved.get(name).copied()
+    }
+
+    pub fn color(&self, index: usize) -> Color1 {
+        self.colors[index]
+    }
+
+    pub fn named_color(&self, name: &str) -> Color1 {
+        self.colors[self.index(name)]
+    }
+
+    pub fn set_color(&mut self, index: usize, color: Color1) {
+        self.colors[index] = color;
+    }
+
+    pub fn colors(&self) -> &Vec<Color1> {
+        &self.colors
+    }
+
+    // The index of the extra color for a value between min and max, where min gets the first
+    // extra color and max gets the last. Values outside the range are clamped.
+    pub fn index_for_value(&self, value: f64, min: f64, max: f64) -> usize {
+        let extra_count = self.extra_count();
+        assert!(extra_count > 0, "The palette has no extra colors.");
+        let fraction = if max > min { ((value - min) / (max - min)).clamp(0.0, 1.0) } else { 0.0 };
+        let offset = ((fraction * extra_count as f64) as usize).min(extra_count - 1);
+        self.reserved_count() + offset
+    }
+
+    pub fn color_for_value(&self, value: f64, min: f64, max: f64) -> Color1 {
+        self.colors[self.index_for_value(value, min, max)]
+    }
+
+    pub fn color_256(&self, index: usize) -> Color256 {
+        self.colors[index].into()
+    }
+}
+
+pub const SLOT_CLOSED: &str = "closed";
+pub const SLOT_OPEN: &str = "open";
+
+// Black for closed cells and white for open ones, for the cellular automata in cave_cell and
+// cell_auto.
+pub fn closed_open_palette() -> Palette {
+    Palette::with_reserved(&[(SLOT_CLOSED, Color1::black()), (SLOT_OPEN, Color1::white())])
+}
+
+impl From<Palette> for Vec<Color1> {
+    fn from(palette: Palette) -> Self {
+        palette.colors
+    }
+}
+
+impl From<&Palette> for Vec<Color1> {
+    fn from(palette: &Palette) -> Self {
+        palette.colors.clone()
+    }
+}
+
+impl Colormap {
+    // The color at a position from 0.0 to 1.0 along the colormap. Positions outside that range are
+    // clamped, except for a cyclic colormap where they wrap around.
+    pub fn color(&self, position: f64) -> Color1 {
+        match self {
+            Colormap::Viridis => interpolate_samples(&VIRIDIS, position),
+            Colormap::Magma => interpolate_samples(&MAGMA, position),
+            Colormap::Grayscale => {
+                let value = position.clamp(0.0, 1.0) as f32;
+                Color1::from_rgb(value, value, value)
+            },
+            Colormap::Cyclic => {
+                // Three cosine waves a third of a turn apart.
+                let angle = position.rem_euclid(1.0) * std::f64::consts::PI * 2.0;
+                let channel = |offset: f64| (0.5 + (0.5 * (angle - (offset * std::f64::consts::PI * 2.0)).cos())) as f32;
+                Color1::from_rgb(channel(0.0), channel(1.0 / 3.0), channel(2.0 / 3.0))
+            },
+        }
+    }
+}
+
+fn interpolate_samples(samples: &[[u8; 3]], position: f64) -> Color1 {
+    let scaled = position.clamp(0.0, 1.0) * (samples.len() - 1) as f64;
+    let from_index = (scaled as usize).min(samples.len() - 2);
+    let t = (scaled - from_index as f64) as f32;
+    let (from, to) = (samples[from_index], samples[from_index + 1]);
+    let channel = |index: usize| ((from[index] as f32 * (1.0 - t)) + (to[index] as f32 * t)) / 255.0;
+    Color1::from_rgb(channel(0), channel(1), channel(2))
+}
+
+fn try_palette() {
+    // One strip per colormap.
+    let colormaps = [Colormap::Viridis, Colormap::Magma, Colormap::Grayscale, Colormap::Cyclic];
+    let (width, strip_height) = (256, 20);
+    let mut grid = grid::Grid::new(width, strip_height * colormaps.len(), 0);
+    let mut palette = Palette::with_reserved(&[("background", Color1::black())]);
+    for (strip_index, colormap) in colormaps.iter().enumerate() {
+        let first_index = palette.len();
+        palette.add_colormap(*colormap, width);
+        for y in (strip_index * strip_height)..((strip_index + 1) * strip_height) {
+            for x in 0..width {
+                grid.set_xy(x, y, first_index + x);
+            }
+        }
+    }
+    image_util::save_grid_palette(&grid, "Test_palette.png", &palette, 2, None).unwrap();
+}
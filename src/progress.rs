@@ -0,0 +1,181 @@
+// Where long-running operations report what they're doing: phases starting and ending, counts,
+// timings and how far along they are. They take a &mut dyn Progress rather than printing, so that
+// the caller decides whether the reports go to the console (ConsoleProgress), nowhere
+// (SilentProgress) or into a list that can be looked at afterward (CollectingProgress), as a
+// benchmark would want.
+//
+//   let mut progress = CollectingProgress::new();
+//   let unions = perc.run_to_completion(&mut rng, 30, &mut progress);
+//   let elapsed = progress.phase_elapsed("run_to_completion").unwrap();
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::*;
+
+pub trait Progress {
+    fn phase_start(&mut self, name: &str);
+    fn phase_end(&mut self, name: &str, elapsed: Duration);
+    // A number that has been counted or measured, like the number of steps taken.
+    fn counter(&mut self, name: &str, value: f64);
+    fn timing(&mut self, name: &str, elapsed: Duration);
+    // How much of the work is done, from 0.0 to 1.0.
+    fn percent_complete(&mut self, name: &str, fraction: f64);
+    fn message(&mut self, text: &str);
+}
+
+// Prints everything as it's reported, which is how these operations behaved before there were
+// other choices.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConsoleProgress;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentProgress;
+
+// Keeps every report in order. Clones share the same list, so one clone can be handed to something
+// that takes ownership, like FramePlayer::set_progress(), and the other used to read the results.
+#[derive(Clone, Debug, Default)]
+pub struct CollectingProgress {
+    events: Arc<Mutex<Vec<ProgressEvent>>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgressEvent {
+    PhaseStart { name: String },
+    PhaseEnd { name: String, elapsed: Duration },
+    Counter { name: String, value: f64 },
+    Timing { name: String, elapsed: Duration },
+    PercentComplete { name: String, fraction: f64 },
+    Message { text: String },
+}
+
+impl Progress for ConsoleProgress {
+    fn phase_start(&mut self, _name: &str) {
+    }
+
+    fn phase_end(&mut self, name: &str, elapsed: Duration) {
+        println!("{}: {:?}", name, elapsed);
+    }
+
+    fn counter(&mut self, name: &str, value: f64) {
+        println!("{} = {}", name, value);
+    }
+
+    fn timing(&mut self, name: &str, elapsed: Duration) {
+        println!("{} = {:?}", name, elapsed);
+    }
+
+    fn percent_complete(&mut self, name: &str, fraction: f64) {
+        println!("{}: {}%", name, ff(fraction * 100.0, 1));
+    }
+
+    fn message(&mut self, text: &str) {
+        println!("{}", text);
+    }
+}
+
+impl Progress for SilentProgress {
+    fn phase_start(&mut self, _name: &str) {
+    }
+
+    fn phase_end(&mut self, _name: &str, _elapsed: Duration) {
+    }
+
+    fn counter(&mut self, _name: &str, _value: f64) {
+    }
+
+    fn timing(&mut self, _name: &str, _elapsed: Duration) {
+    }
+
+    fn percent_complete(&mut self, _name: &str, _fraction: f64) {
+    }
+
+    fn message(&mut self, _text: &str) {
+    }
+}
+
+impl CollectingProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<ProgressEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    // How long the most recent phase with this name took.
+    pub fn phase_elapsed(&self, name: &str) -> Option<Duration> {
+        self.events().into_iter().rev().find_map(|event| match event {
+            ProgressEvent::PhaseEnd { name: event_name, elapsed } if event_name == name => Some(elapsed),
+            _ => None,
+        })
+    }
+
+    // The most recent value of the counter.
+    pub fn counter_value(&self, name: &str) -> Option<f64> {
+        self.events().into_iter().rev().find_map(|event| match event {
+            ProgressEvent::Counter { name: event_name, value } if event_name == name => Some(value),
+            _ => None,
+        })
+    }
+
+    // Every timing reported with this name, in order.
+    pub fn timings(&self, name: &str) -> Vec<Duration> {
+        self.events().into_iter().filter_map(|event| match event {
+            ProgressEvent::Timing { name: event_name, elapsed } if event_name == name => Some(elapsed),
+            _ => None,
+        }).collect()
+    }
+
+    pub fn messages(&self) -> Vec<String> {
+        self.events().into_iter().filter_map(|event| match event {
+            ProgressEvent::Message { text } => Some(text),
+            _ => None,
+        }).collect()
+    }
+
+    fn push(&self, event: ProgressEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+impl Progress for CollectingProgress {
+    fn phase_start(&mut self, name: &str) {
+        self.push(ProgressEvent::PhaseStart { name: name.to_string() });
+    }
+
+    fn phase_end(&mut self, name: &str, elapsed: Duration) {
+        self.push(ProgressEvent::PhaseEnd { name: name.to_string(), elapsed });
+    }
+
+    fn counter(&mut self, name: &str, value: f64) {
+        self.push(ProgressEvent::Counter { name: name.to_string(), value });
+    }
+
+    fn timing(&mut self, name: &str, elapsed: Duration) {
+        self.push(ProgressEvent::Timing { name: name.to_string(), elapsed });
+    }
+
+    fn percent_complete(&mut self, name: &str, fraction: f64) {
+        self.push(ProgressEvent::PercentComplete { name: name.to_string(), fraction });
+    }
+
+    fn message(&mut self, text: &str) {
+        self.push(ProgressEvent::Message { text: text.to_string() });
+    }
+}
+
+// Run the operation as a phase with the given name, reporting when it starts and how long it took.
+pub fn time_phase<T, F>(progress: &mut dyn Progress, name: &str, operation: F) -> T
+    where F: FnOnce() -> T
+{
+    progress.phase_start(name);
+    let start_time = Instant::now();
+    let result = operation();
+    progress.phase_end(name, Instant::now() - start_time);
+    result
+}
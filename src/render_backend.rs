@@ -0,0 +1,774 @@
+// Frame playback separated from the drawing surface. FramePlayer decides which frame is current,
+// when to move to the next one, and whether to clear the background, while a RenderBackend does the
+// actual drawing. renderer_3::Renderer drives a FramePlayer with an OpenGL backend in a window,
+// SoftwareRenderer is an in-memory image backend, and RecordingBackend just keeps a list of calls.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use std::ops::Add;
+
+use crate::*;
+use crate::camera::{Camera, CameraBackend};
+use crate::frame_source::{self, FrameSource};
+use crate::progress::{Progress, SilentProgress};
+
+pub trait RenderBackend {
+    fn clear(&mut self, color: [f32; 4]);
+    fn circle(&mut self, center_x: f64, center_y: f64, radius: f64, color: [f32; 4]);
+    // As with graphics::line(), the thickness is the distance the line extends on either side of
+    // the segment.
+    fn line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4]);
+    fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]);
+    // A filled polygon whose edges don't cross, though it may be concave.
+    fn polygon(&mut self, points: &[PointF64], color: [f32; 4]);
+    // Move everything drawn so far toward the color by the amount, from 0.0 (no change) to 1.0
+    // (the same as clear()).
+    fn fade(&mut self, color: [f32; 4], amount: f32);
+    // The blend mode for everything drawn from now on until it's changed again. Clearing the
+    // background isn't affected.
+    fn blend_mode(&self) -> BlendMode;
+    fn set_blend_mode(&mut self, mode: BlendMode);
+
+    // By default each segment is drawn as a separate line.
+    fn polyline(&mut self, points: &[PointF64], thickness: f64, color: [f32; 4]) {
+        for pair in points.windows(2) {
+            self.line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, thickness, color);
+        }
+    }
+
+    // By default the curves are flattened into straight segments and drawn as a polygon or
+    // polyline. Backends with their own curves, like SVG, can do better.
+    fn path(&mut self, start: PointF64, segments: &[PathSegment], style: PathStyle, color: [f32; 4]) {
+        let points = path_points(start, segments);
+        match style {
+            PathStyle::Fill => self.polygon(&points, color),
+            PathStyle::Stroke(thickness) => self.polyline(&points, thickness, color),
+        }
+    }
+
+    // Draw the text in the built-in bitmap font with its top left corner at x, y. By default this
+    // is done with rectangles so that every backend can draw text.
+    fn text(&mut self, x: f64, y: f64, size: f64, text: &str, color: [f32; 4]) {
+        bitmap_font::for_each_rectangle(text, x, y, size, |rect_x, rect_y, rect_width, rect_height| {
+            self.rectangle(rect_x, rect_y, rect_width, rect_height, color);
+        });
+    }
+}
+
+pub struct FramePlayer {
+    additive: bool,
+    back_color: Color1,
+    colors: Vec<Color1>,
+    source: FrameSource,
+    source_finished: bool,
+    // Frames pulled from the source so far, where frames[0] is frame number first_frame_index.
+    // Frames before the current one are dropped as playback moves on unless retain_frames is set,
+    // in which case they can be revisited.
+    frames: VecDeque<Frame>,
+    first_frame_index: usize,
+    retain_frames: bool,
+    frame_index: Option<usize>,
+    state: PlaybackState,
+    speed: f64,
+    looping: bool,
+    camera: Camera,
+    start_time: Instant,
+    next_frame_time: Instant,
+    // While paused, how much time was left before the next frame.
+    paused_remaining: Duration,
+    // When set, each frame is drawn over a faded copy of what was there before instead of over a
+    // cleared background. This is the fraction of the old output that fades each second.
+    persistence: Option<f64>,
+    // The index and seconds_to_next of the last frame drawn since the background was cleared.
+    last_drawn: Option<(usize, f64)>,
+    // Set when an additive or persistent display has to be rebuilt from the first frame, as after
+    // seeking.
+    redraw_from_start: bool,
+    // False for a backend like a double-buffered window, where what was drawn before isn't still
+    // there at the next draw().
+    backend_keeps_output: bool,
+    // Where the counts and timings are reported once the last frame is reached.
+    progress: Box<dyn Progress>,
+    last_frame_reported: bool,
+    remove_count: usize,
+    remove_seconds: f32,
+    draw_count: usize,
+    draw_seconds: f32,
+    draw_shape_count: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+}
+
+#[derive(Clone, Debug)]
+pub enum DrawCall {
+    Clear { color: [f32; 4] },
+    Circle { center_x: f64, center_y: f64, radius: f64, color: [f32; 4] },
+    Line { x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4] },
+    Rectangle { x: f64, y: f64, width: f64, height: f64, color: [f32; 4] },
+    Text { x: f64, y: f64, size: f64, text: String, color: [f32; 4] },
+    Polygon { points: Vec<PointF64>, color: [f32; 4] },
+    Fade { color: [f32; 4], amount: f32 },
+    SetBlendMode { mode: BlendMode },
+}
+
+// A backend that draws nothing and only remembers what it was asked to draw.
+#[derive(Debug, Default)]
+pub struct RecordingBackend {
+    pub calls: Vec<DrawCall>,
+    blend_mode: BlendMode,
+}
+
+impl FramePlayer {
+    // Since the frames are already in memory they're all kept so that playback can go back to
+    // earlier ones.
+    pub fn new(back_color: Color1, frames: Vec<Frame>, additive: bool, colors: Vec<Color1>) -> Self {
+        let mut player = Self::from_source(back_color, frame_source::from_vec(frames), additive, colors);
+        player.retain_frames = true;
+        player
+    }
+
+    // By default frames are dropped once they've been shown so that a long animation from a
+    // streaming source runs in constant memory. Call set_retain_frames(true) to be able to step
+    // back and seek to earlier frames.
+    pub fn from_source(back_color: Color1, source: FrameSource, additive: bool, colors: Vec<Color1>) -> Self {
+        let now = Instant::now();
+        Self {
+            additive,
+            back_color,
+            colors,
+            source,
+            source_finished: false,
+            frames: VecDeque::new(),
+            first_frame_index: 0,
+            retain_frames: false,
+            frame_index: None,
+            state: PlaybackState::Playing,
+            speed: 1.0,
+            looping: false,
+            camera: Camera::identity(),
+            start_time: now,
+            next_frame_time: now,
+            paused_remaining: Duration::from_secs(0),
+            persistence: None,
+            last_drawn: None,
+            redraw_from_start: false,
+            backend_keeps_output: true,
+            progress: Box::new(SilentProgress),
+            last_frame_reported: false,
+            remove_count: 0,
+            remove_seconds: 0.0,
+            draw_count: 0,
+            draw_seconds: 0.0,
+            draw_shape_count: 0,
+        }
+    }
+
+    pub fn set_retain_frames(&mut self, retain_frames: bool) {
+        self.retain_frames = retain_frames;
+    }
+
+    // The default is SilentProgress, so that saving to a file doesn't print anything.
+    pub fn set_progress(&mut self, progress: Box<dyn Progress>) {
+        self.progress = progress;
+    }
+
+    pub fn frame_index(&self) -> Option<usize> {
+        self.frame_index
+    }
+
+    // None before the first frame has been shown.
+    pub fn current_frame(&self) -> Option<&Frame> {
+        self.frame_index.and_then(|frame_index| self.retained_frame(frame_index))
+    }
+
+    // The total number of frames, which is only known once the source has run out.
+    pub fn frame_count(&self) -> Option<usize> {
+        if self.source_finished { Some(self.first_frame_index + self.frames.len()) } else { None }
+    }
+
+    pub fn state(&self) -> PlaybackState {
+        self.state
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state == PlaybackState::Paused
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    // Looping only goes back to the start if the first frame is still around, meaning the frames
+    // came from a Vec or retain_frames is set.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn persistence(&self) -> Option<f64> {
+        self.persistence
+    }
+
+    // With Some(decay_per_second), each frame is drawn over the previous output faded toward the
+    // background color, so that moving shapes leave trails. A decay of 0.9 means that after one
+    // second of animation time, 90% of what was drawn has faded. The fading is based on the frames'
+    // seconds_to_next rather than the wall clock, so it's the same in a window and in exported
+    // files. This overrides the additive flag.
+    pub fn set_persistence(&mut self, decay_per_second: Option<f64>) {
+        if let Some(decay_per_second) = decay_per_second {
+            assert!(decay_per_second > 0.0 && decay_per_second <= 1.0);
+        }
+        self.persistence = decay_per_second;
+        self.redraw_from_start = true;
+    }
+
+    // A window that swaps between two buffers shows a different buffer after each draw(), so an
+    // additive or persistent display can't just draw the new frames over the old output. Instead
+    // every frame since the first one is drawn again each time, which means they're all kept.
+    pub fn set_backend_keeps_output(&mut self, backend_keeps_output: bool) {
+        self.backend_keeps_output = backend_keeps_output;
+    }
+
+    pub fn camera(&self) -> Camera {
+        self.camera
+    }
+
+    // In an additive display the frames drawn so far are drawn again with the new camera, so this
+    // is only complete if they're still around.
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = camera;
+        if self.accumulates() {
+            self.redraw_from_start = true;
+        }
+    }
+
+    // This may have to wait for the source to produce the next frame. Finding out that there is
+    // no next frame is how the player learns that the last frame has been reached.
+    pub fn has_next_frame(&mut self) -> bool {
+        let next_frame_index = self.frame_index.map_or(0, |frame_index| frame_index + 1);
+        let has_next_frame = self.fill_to(next_frame_index);
+        if !has_next_frame {
+            self.report_last_frame();
+        }
+        has_next_frame
+    }
+
+    // Move to the next frame if it's playing and the time has come according to the wall clock.
+    // At the end of the frames this goes back to the first one if looping. Returns true if the
+    // current frame changed.
+    pub fn update(&mut self, now: Instant) -> bool {
+        if self.is_paused() || now < self.next_frame_time {
+            return false;
+        }
+        if self.has_next_frame() {
+            self.next_frame();
+            true
+        } else if self.looping && self.frame_index.is_some_and(|frame_index| frame_index > 0) {
+            // Returns false if the first frame has already been dropped.
+            self.seek(0, now)
+        } else {
+            false
+        }
+    }
+
+    // Move to the next frame regardless of the time.
+    pub fn next_frame(&mut self) {
+        assert!(self.has_next_frame());
+        let frame_index = self.frame_index.map_or(0, |frame_index| frame_index + 1);
+        self.frame_index = Some(frame_index);
+        self.remove_count += 1;
+        self.drop_old_frames();
+        let seconds_to_next = self.retained_frame(frame_index).unwrap().seconds_to_next;
+        self.next_frame_time = self.next_frame_time.add(self.scaled_duration(seconds_to_next));
+    }
+
+    pub fn play(&mut self, now: Instant) {
+        if self.is_paused() {
+            self.next_frame_time = now.add(self.paused_remaining);
+            self.state = PlaybackState::Playing;
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        if !self.is_paused() {
+            self.paused_remaining = self.next_frame_time.saturating_duration_since(now);
+            self.state = PlaybackState::Paused;
+        }
+    }
+
+    pub fn toggle_pause(&mut self, now: Instant) {
+        if self.is_paused() { self.play(now) } else { self.pause(now) }
+    }
+
+    // Pause and show the next frame, going around to the first frame if looping. Returns true if
+    // the current frame changed.
+    pub fn step_forward(&mut self, now: Instant) -> bool {
+        self.pause(now);
+        let changed = if self.has_next_frame() {
+            self.next_frame();
+            true
+        } else if self.looping {
+            self.seek(0, now)
+        } else {
+            false
+        };
+        self.reset_paused_remaining();
+        changed
+    }
+
+    // Pause and show the previous frame if it's still available.
+    pub fn step_back(&mut self, now: Instant) -> bool {
+        self.pause(now);
+        match self.frame_index {
+            Some(frame_index) if frame_index > 0 => self.seek(frame_index - 1, now),
+            _ => false,
+        }
+    }
+
+    // Show the given frame, pulling frames from the source as needed. Returns false if the frame
+    // doesn't exist or has already been dropped.
+    pub fn seek(&mut self, frame_index: usize, now: Instant) -> bool {
+        if frame_index < self.first_frame_index || !self.fill_to(frame_index) {
+            return false;
+        }
+        self.go_to(frame_index, now)
+    }
+
+    // Show the last frame, which means pulling every remaining frame from the source.
+    pub fn seek_last(&mut self, now: Instant) -> bool {
+        while self.fill_to(self.first_frame_index + self.frames.len()) {}
+        match self.frame_count() {
+            Some(frame_count) if frame_count > 0 => self.seek(frame_count - 1, now),
+            _ => false,
+        }
+    }
+
+    // A speed of 2.0 plays twice as fast as the frames' seconds_to_next values call for.
+    pub fn set_speed(&mut self, speed: f64, now: Instant) {
+        assert!(speed > 0.0);
+        let remaining = self.next_frame_time.saturating_duration_since(now).mul_f64(self.speed / speed);
+        self.next_frame_time = now.add(remaining);
+        self.paused_remaining = self.paused_remaining.mul_f64(self.speed / speed);
+        self.speed = speed;
+    }
+
+    // Draw the current frame. The background is cleared first unless this is an additive display,
+    // in which case it's only cleared for the first frame and each draw adds the frames that
+    // haven't been drawn yet, so that none are skipped or drawn twice. After jumping around in an
+    // additive display, or on every draw if the backend doesn't keep its output, the frames up to
+    // the current one are drawn again to rebuild the picture.
+    pub fn draw<B: RenderBackend>(&mut self, backend: &mut B) {
+        let start_time = Instant::now();
+        let redraw_all = self.accumulates() && (self.redraw_from_start || !self.backend_keeps_output);
+        let clear_background = !self.accumulates() || self.frame_index == Some(0) || self.last_drawn.is_none() || redraw_all;
+        if clear_background {
+            backend.clear(self.back_color.for_render);
+            self.last_drawn = None;
+        }
+        if let Some(frame_index) = self.frame_index {
+            let first_draw_frame_index = if !self.accumulates() {
+                frame_index
+            } else {
+                self.last_drawn.map_or(self.first_frame_index, |(last_frame_index, _)| last_frame_index + 1).max(self.first_frame_index)
+            };
+            for draw_frame_index in first_draw_frame_index..=frame_index {
+                if let (Some(decay_per_second), Some((_, seconds))) = (self.persistence, self.last_drawn) {
+                    let amount = 1.0 - (1.0 - decay_per_second).powf(seconds);
+                    backend.fade(self.back_color.for_render, amount as f32);
+                }
+                let frame = &self.frames[draw_frame_index - self.first_frame_index];
+                self.last_drawn = Some((draw_frame_index, frame.seconds_to_next));
+                self.draw_shape_count += frame.shapes.len();
+                if self.camera.is_identity() {
+                    draw_frame(backend, frame, &self.colors);
+                } else {
+                    draw_frame(&mut CameraBackend::new(backend, self.camera), frame, &self.colors);
+                }
+            }
+        }
+        self.redraw_from_start = false;
+        self.draw_count += 1;
+        self.draw_seconds += (Instant::now() - start_time).as_secs_f32();
+    }
+
+    // Step through every remaining frame as fast as possible without regard to the wall clock or
+    // the playback controls, calling after_draw with the backend and the frame's seconds_to_next
+    // once each frame has been drawn. This is the path used by offline backends such as image and
+    // file exporters.
+    pub fn play_all<B, F>(&mut self, backend: &mut B, mut after_draw: F)
+        where B: RenderBackend,
+              F: FnMut(&mut B, f64)
+    {
+        while self.has_next_frame() {
+            self.next_frame();
+            self.draw(backend);
+            let seconds_to_next = self.current_frame().unwrap().seconds_to_next;
+            after_draw(backend, seconds_to_next);
+        }
+    }
+
+    // Pull frames from the source until the given frame is available. Returns false if the source
+    // runs out first.
+    fn fill_to(&mut self, frame_index: usize) -> bool {
+        while !self.source_finished && self.first_frame_index + self.frames.len() <= frame_index {
+            let start_time = Instant::now();
+            match self.source.next() {
+                Some(frame) => self.frames.push_back(frame),
+                None => self.source_finished = true,
+            }
+            self.remove_seconds += (Instant::now() - start_time).as_secs_f32();
+        }
+        frame_index < self.first_frame_index + self.frames.len()
+    }
+
+    fn report_last_frame(&mut self) {
+        if !self.last_frame_reported && self.frame_index.is_some() {
+            self.last_frame_reported = true;
+            self.progress.timing("last frame reached", Instant::now() - self.start_time);
+            self.progress.counter("remove_count", self.remove_count as f64);
+            self.progress.timing("remove", Duration::from_secs_f32(self.remove_seconds));
+            self.progress.counter("draw_count", self.draw_count as f64);
+            self.progress.timing("draw", Duration::from_secs_f32(self.draw_seconds));
+            self.progress.counter("draw_shape_count", self.draw_shape_count as f64);
+        }
+    }
+
+    fn retained_frame(&self, frame_index: usize) -> Option<&Frame> {
+        if frame_index < self.first_frame_index {
+            None
+        } else {
+            self.frames.get(frame_index - self.first_frame_index)
+        }
+    }
+
+    // An additive or persistent display keeps the frames that haven't been drawn yet, and all of
+    // them if the backend doesn't keep its output.
+    fn drop_old_frames(&mut self) {
+        if !self.retain_frames && (self.backend_keeps_output || !self.accumulates()) {
+            if let Some(frame_index) = self.frame_index {
+                let keep_from = if self.accumulates() {
+                    self.last_drawn.map_or(self.first_frame_index, |(last_frame_index, _)| last_frame_index + 1).min(frame_index)
+                } else {
+                    frame_index
+                };
+                while self.first_frame_index < keep_from {
+                    self.frames.pop_front();
+                    self.first_frame_index += 1;
+                }
+            }
+        }
+    }
+
+    fn go_to(&mut self, frame_index: usize, now: Instant) -> bool {
+        debug_assert!(self.retained_frame(frame_index).is_some());
+        if self.accumulates() && self.frame_index.is_none_or(|current| frame_index != current + 1) {
+            self.redraw_from_start = true;
+        }
+        self.frame_index = Some(frame_index);
+        self.drop_old_frames();
+        let seconds_to_next = self.retained_frame(frame_index).unwrap().seconds_to_next;
+        self.next_frame_time = now.add(self.scaled_duration(seconds_to_next));
+        self.reset_paused_remaining();
+        true
+    }
+
+    // True if frames are drawn over earlier output rather than over a cleared background.
+    fn accumulates(&self) -> bool {
+        self.additive || self.persistence.is_some()
+    }
+
+    // While paused, the next play() show the current frame for its full time.
+    fn reset_paused_remaining(&mut self) {
+        if let Some(frame) = self.current_frame() {
+            self.paused_remaining = self.scaled_duration(frame.seconds_to_next);
+        }
+    }
+
+    fn scaled_duration(&self, seconds: f64) -> Duration {
+        Duration::from_secs_f64(seconds / self.speed)
+    }
+
+    pub fn remove_count(&self) -> usize {
+        self.remove_count
+    }
+
+    pub fn remove_seconds(&self) -> f32 {
+        self.remove_seconds
+    }
+
+    pub fn draw_count(&self) -> usize {
+        self.draw_count
+    }
+
+    pub fn draw_seconds(&self) -> f32 {
+        self.draw_seconds
+    }
+
+    pub fn draw_shape_count(&self) -> usize {
+        self.draw_shape_count
+    }
+}
+
+// Draw the frame's shapes with its blend mode, leaving the backend in BlendMode::Normal afterward.
+pub fn draw_frame<B: RenderBackend>(backend: &mut B, frame: &Frame, colors: &Vec<Color1>) {
+    backend.set_blend_mode(frame.blend_mode);
+    draw_shapes(backend, &frame.shapes, colors);
+    backend.set_blend_mode(BlendMode::Normal);
+}
+
+pub fn draw_shapes<B: RenderBackend>(backend: &mut B, shapes: &ShapeList, colors: &Vec<Color1>) {
+    for shape in shapes.iter() {
+        draw_shape(backend, shape, colors);
+    }
+}
+
+// Turn one shape into backend calls, looking up the palette for the *Fast variants.
+pub fn draw_shape<B: RenderBackend>(backend: &mut B, shape: &Shape, colors: &Vec<Color1>) {
+    match shape {
+        Shape::Circle { center, radius, color } => {
+            backend.circle(center.x, center.y, *radius, color.for_render);
+        },
+        Shape::CircleFast { center_x, center_y, radius, color_index } => {
+            backend.circle(*center_x, *center_y, *radius, colors[*color_index].for_render);
+        },
+        Shape::Line { top_left, bottom_right, thickness, color } => {
+            backend.line(top_left.x, top_left.y, bottom_right.x, bottom_right.y, *thickness, color.for_render);
+        },
+        Shape::LineFast { x0, y0, x1, y1, thickness, color_index } => {
+            backend.line(*x0, *y0, *x1, *y1, *thickness, colors[*color_index].for_render);
+        },
+        Shape::Rectangle { top_left, bottom_right, color } => {
+            backend.rectangle(top_left.x, top_left.y, bottom_right.x - top_left.x, bottom_right.y - top_left.y, color.for_render);
+        },
+        Shape::RectangleFast { x, y, width, height, color_index } => {
+            // Color index 0 is treated as the background and not drawn.
+            if *color_index != 0 {
+                backend.rectangle(*x, *y, *width, *height, colors[*color_index].for_render);
+            }
+        },
+        Shape::Text { top_left, size, color, text } => {
+            backend.text(top_left.x, top_left.y, *size, text, color.for_render);
+        },
+        Shape::Polygon { points, color } => {
+            backend.polygon(points, color.for_render);
+        },
+        Shape::PolygonFast { points, color_index } => {
+            backend.polygon(points, colors[*color_index].for_render);
+        },
+        Shape::Polyline { points, thickness, color } => {
+            backend.polyline(points, *thickness, color.for_render);
+        },
+        Shape::PolylineFast { points, thickness, color_index } => {
+            backend.polyline(points, *thickness, colors[*color_index].for_render);
+        },
+        Shape::Path { start, segments, style, color } => {
+            backend.path(*start, segments, *style, color.for_render);
+        },
+        Shape::PathFast { start, segments, style, color_index } => {
+            backend.path(*start, segments, *style, colors[*color_index].for_render);
+        },
+        Shape::Blend { mode, shapes } => {
+            let previous_mode = backend.blend_mode();
+            backend.set_blend_mode(*mode);
+            draw_shapes(backend, shapes, colors);
+            backend.set_blend_mode(previous_mode);
+        },
+    }
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self {
+            calls: vec![],
+            blend_mode: BlendMode::Normal,
+        }
+    }
+}
+
+impl RenderBackend for RecordingBackend {
+    fn clear(&mut self, color: [f32; 4]) {
+        self.calls.push(DrawCall::Clear { color });
+    }
+
+    fn circle(&mut self, center_x: f64, center_y: f64, radius: f64, color: [f32; 4]) {
+        self.calls.push(DrawCall::Circle { center_x, center_y, radius, color });
+    }
+
+    fn line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4]) {
+        self.calls.push(DrawCall::Line { x0, y0, x1, y1, thickness, color });
+    }
+
+    fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]) {
+        self.calls.push(DrawCall::Rectangle { x, y, width, height, color });
+    }
+
+    fn polygon(&mut self, points: &[PointF64], color: [f32; 4]) {
+        self.calls.push(DrawCall::Polygon { points: points.to_vec(), color });
+    }
+
+    fn fade(&mut self, color: [f32; 4], amount: f32) {
+        self.calls.push(DrawCall::Fade { color, amount });
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+        self.calls.push(DrawCall::SetBlendMode { mode });
+    }
+
+    fn text(&mut self, x: f64, y: f64, size: f64, text: &str, color: [f32; 4]) {
+        self.calls.push(DrawCall::Text { x, y, size, text: text.to_string(), color });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered_frames(count: usize) -> Vec<Frame> {
+        (0..count).map(|index| Frame::new(vec![Shape::circle(PointF64::new(index as f64, 0.0), 1.0, Color1::white())], 0.1)).collect()
+    }
+
+    // Call update() with a time late enough that a frame is always due.
+    fn update_later(player: &mut FramePlayer, seconds: &mut u64) -> bool {
+        *seconds += 1;
+        player.update(Instant::now() + Duration::from_secs(*seconds))
+    }
+
+    fn streaming_player(count: usize) -> FramePlayer {
+        let mut player = FramePlayer::from_source(Color1::black(), frame_source::from_vec(numbered_frames(count)), false, vec![]);
+        player.set_progress(Box::new(SilentProgress));
+        player
+    }
+
+    fn streaming_player_additive(count: usize) -> FramePlayer {
+        let mut player = FramePlayer::from_source(Color1::black(), frame_source::from_vec(numbered_frames(count)), true, vec![]);
+        player.set_progress(Box::new(SilentProgress));
+        player
+    }
+
+    #[test]
+    fn looping_goes_back_to_the_first_frame_when_frames_are_retained() {
+        let mut player = FramePlayer::new(Color1::black(), numbered_frames(3), false, vec![]);
+        player.set_progress(Box::new(SilentProgress));
+        player.set_looping(true);
+        let mut seconds = 0;
+        for expected_frame_index in [0, 1, 2, 0, 1].iter() {
+            assert!(update_later(&mut player, &mut seconds));
+            assert_eq!(Some(*expected_frame_index), player.frame_index());
+        }
+    }
+
+    #[test]
+    fn looping_stops_at_the_end_of_a_streaming_source() {
+        let mut player = streaming_player(3);
+        player.set_looping(true);
+        let mut seconds = 0;
+        for _ in 0..3 {
+            assert!(update_later(&mut player, &mut seconds));
+        }
+        // The first frame has been dropped so there's nothing to go back to.
+        assert!(!update_later(&mut player, &mut seconds));
+        assert_eq!(Some(2), player.frame_index());
+        assert!(!player.step_forward(Instant::now()));
+        assert_eq!(Some(2), player.frame_index());
+    }
+
+    #[test]
+    fn step_back_fails_past_dropped_frames() {
+        let mut player = streaming_player(4);
+        player.next_frame();
+        player.next_frame();
+        assert!(!player.step_back(Instant::now()));
+        assert_eq!(Some(1), player.frame_index());
+        assert!(player.is_paused());
+        assert!(!player.seek(0, Instant::now()));
+    }
+
+    #[test]
+    fn step_back_works_with_retained_frames() {
+        let mut player = streaming_player(4);
+        player.set_retain_frames(true);
+        player.next_frame();
+        player.next_frame();
+        assert!(player.step_back(Instant::now()));
+        assert_eq!(Some(0), player.frame_index());
+        assert!(!player.step_back(Instant::now()));
+    }
+
+    #[test]
+    fn seek_last_pulls_every_frame() {
+        let mut player = streaming_player(5);
+        assert_eq!(None, player.frame_count());
+        assert!(player.seek_last(Instant::now()));
+        assert_eq!(Some(4), player.frame_index());
+        assert_eq!(Some(5), player.frame_count());
+        assert!(!player.has_next_frame());
+    }
+
+    fn circle_x(backend: &RecordingBackend) -> Vec<f64> {
+        backend.calls.iter().filter_map(|call| match call {
+            DrawCall::Circle { center_x, .. } => Some(*center_x),
+            _ => None,
+        }).collect()
+    }
+
+    fn clear_count(backend: &RecordingBackend) -> usize {
+        backend.calls.iter().filter(|call| matches!(call, DrawCall::Clear { .. })).count()
+    }
+
+    #[test]
+    fn additive_display_draws_each_frame_once() {
+        let mut player = streaming_player_additive(4);
+        let mut backend = RecordingBackend::new();
+        // Frames passed over between draws are still drawn.
+        player.next_frame();
+        player.next_frame();
+        // A window renders the same frame many times before moving on.
+        for _ in 0..3 {
+            player.draw(&mut backend);
+        }
+        player.next_frame();
+        player.draw(&mut backend);
+        player.next_frame();
+        player.draw(&mut backend);
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0], circle_x(&backend));
+        assert_eq!(1, clear_count(&backend));
+    }
+
+    #[test]
+    fn additive_display_redraws_everything_when_the_backend_doesnt_keep_output() {
+        let mut player = streaming_player_additive(3);
+        player.set_backend_keeps_output(false);
+        let mut backend = RecordingBackend::new();
+        player.next_frame();
+        player.draw(&mut backend);
+        player.draw(&mut backend);
+        player.next_frame();
+        player.next_frame();
+        player.draw(&mut backend);
+        assert_eq!(vec![0.0, 0.0, 0.0, 1.0, 2.0], circle_x(&backend));
+        assert_eq!(3, clear_count(&backend));
+    }
+
+    #[test]
+    fn play_all_draws_every_frame_over_a_cleared_background() {
+        let mut player = streaming_player(3);
+        let mut backend = RecordingBackend::new();
+        let mut seconds = vec![];
+        player.play_all(&mut backend, |_, seconds_to_next| seconds.push(seconds_to_next));
+        assert_eq!(vec![0.1, 0.1, 0.1], seconds);
+        assert_eq!(3, clear_count(&backend));
+        assert_eq!(vec![0.0, 1.0, 2.0], circle_x(&backend));
+    }
+}
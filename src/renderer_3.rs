@@ -1,32 +1,35 @@
 use glutin_window::GlutinWindow as Window;
 use opengl_graphics::{GlGraphics, OpenGL};
 use piston::event_loop::{EventSettings, Events};
-use piston::input::{RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
+use piston::input::{Button, Key, MouseButton, MouseCursorEvent, MouseScrollEvent, PressEvent, ReleaseEvent, RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
 use piston::window::WindowSettings;
-use std::time::{Duration, Instant};
-use std::ops::Add;
+use graphics::{Context, DrawState, Ellipse, Line, Polygon, Rectangle};
+use graphics::draw_state::Blend;
+use std::time::Instant;
 
 use crate::*;
+use crate::camera::Camera;
+use crate::frame_source::FrameSource;
+use crate::render_backend::{FramePlayer, RenderBackend};
 
 pub struct Renderer {
     gl: GlGraphics, // OpenGL drawing backend.
-    additive: bool,
-    back_color: crate::Color1,
-    colors: Vec<Color1>,
-    frames: Vec<Frame>,
+    player: FramePlayer,
+    // Last known mouse position, and whether the view is being dragged with the left button.
+    cursor: [f64; 2],
+    dragging: bool,
     // glyph_cache: GlyphCache<'static>,
-    start_time: Instant,
-    next_frame_time: Instant,
-    frame_index: usize,
-    current_frame: Frame,
-    _shapes: Vec<Shape>,
-    remove_count: usize,
-    remove_seconds: f32,
-    swap_count: usize,
-    swap_seconds: f32,
-    draw_count: usize,
-    draw_seconds: f32,
-    draw_shape_count: usize,
+}
+
+// How much one click of the mouse wheel zooms in or out.
+const ZOOM_STEP: f64 = 1.25;
+
+// The OpenGL implementation of RenderBackend, valid for the duration of one GlGraphics::draw() call.
+pub struct GlBackend<'a> {
+    context: Context,
+    gl: &'a mut GlGraphics,
+    blend_mode: BlendMode,
+    draw_state: DrawState,
 }
 
 impl Renderer {
@@ -38,7 +41,32 @@ impl Renderer {
         Self::display_additive_with_colors(title, width, height, back_color, frames, additive, vec![]);
     }
 
-    pub fn display_additive_with_colors(title: &str, width: f64, height: f64, back_color: crate::Color1, frames: Vec<Frame>, additive: bool, colors: Vec<Color1>) {
+    pub fn display_additive_with_colors(title: &str, width: f64, height: f64, back_color: crate::Color1, frames: Vec<Frame>, additive: bool, colors: impl Into<Vec<Color1>>) {
+        Self::display_player(title, width, height, FramePlayer::new(back_color, frames, additive, colors.into()));
+    }
+
+    // Draw each frame over a faded copy of the previous output. See FramePlayer::set_persistence().
+    pub fn display_persistent(title: &str, width: f64, height: f64, back_color: crate::Color1, frames: Vec<Frame>, decay_per_second: f64, colors: impl Into<Vec<Color1>>) {
+        let mut player = FramePlayer::new(back_color, frames, false, colors.into());
+        player.set_persistence(Some(decay_per_second));
+        Self::display_player(title, width, height, player);
+    }
+
+    pub fn display_source(title: &str, width: f64, height: f64, back_color: crate::Color1, source: FrameSource, additive: bool, colors: impl Into<Vec<Color1>>) {
+        Self::display_player(title, width, height, FramePlayer::from_source(back_color, source, additive, colors.into()));
+    }
+
+    pub fn display_player(title: &str, width: f64, height: f64, player: FramePlayer) {
+        Self::try_display_player(title, width, height, player).unwrap();
+    }
+
+    // Like display_player() but returns an error instead of panicking if the window can't be
+    // created, such as when there's no display or the OpenGL version isn't available.
+    pub fn try_display_player(title: &str, width: f64, height: f64, mut player: FramePlayer) -> DrawResult<()> {
+        // The window swaps between two buffers, so each render starts from whatever was in the
+        // other one rather than from the last render.
+        player.set_backend_keeps_output(false);
+
         // Change this to OpenGL::V2_1 if not working.
         let opengl = OpenGL::V3_2;
 
@@ -47,11 +75,7 @@ impl Renderer {
             .graphics_api(opengl)
             .exit_on_esc(true)
             .build()
-            .unwrap();
-
-        // Start with a dummy frame to make the loop in render() simpler.
-        let current_frame = Frame::new(vec![], 0.001);
-        let next_frame_time = Instant::now();
+            .map_err(|e| DrawError::Backend { message: e.to_string() })?;
 
         /*
         let glyph_cache = GlyphCache::new(
@@ -64,23 +88,10 @@ impl Renderer {
 
         let mut rend = Renderer {
             gl: GlGraphics::new(opengl),
-            additive,
-            back_color,
-            colors,
-            frames,
+            player,
+            cursor: [0.0, 0.0],
+            dragging: false,
             // glyph_cache,
-            start_time: Instant::now(),
-            next_frame_time,
-            frame_index: usize::max_value(),
-            current_frame,
-            _shapes: vec![],
-            remove_count: 0,
-            remove_seconds: 0.0,
-            swap_count: 0,
-            swap_seconds: 0.0,
-            draw_count: 0,
-            draw_seconds: 0.0,
-            draw_shape_count: 0
         };
 
         let mut events = Events::new(EventSettings::new());
@@ -92,112 +103,71 @@ impl Renderer {
             if let Some(args) = e.update_args() {
                 rend.update(&args);
             }
-        }
-    }
 
-    fn render(&mut self, args: &RenderArgs) {
-
-        //rintln!("Animator::render()");
+            match e.press_args() {
+                Some(Button::Keyboard(key)) => rend.key_press(key),
+                Some(Button::Mouse(MouseButton::Left)) => rend.dragging = true,
+                _ => {},
+            }
 
-        use graphics::*;
+            if let Some(Button::Mouse(MouseButton::Left)) = e.release_args() {
+                rend.dragging = false;
+            }
 
-        let back_color = self.back_color.clone();
+            if let Some(cursor) = e.mouse_cursor_args() {
+                rend.mouse_move(cursor);
+            }
 
-        // let mut frame_index = (args.ext_dt / self.frame_seconds).floor() as usize;
-        let now = Instant::now();
-        if now >= self.next_frame_time {
-            if !self.frames.is_empty() {
-                self.frame_index = if self.frame_index == usize::max_value() { 0 } else { self.frame_index + 1 };
-                //rintln!("\nnow = {}", now.duration_since(self.start_time).as_secs_f32());
-                //rintln!("next_frame_time = {}", self.next_frame_time.duration_since(self.start_time).as_secs_f32());
-                //rintln!("frame_index = {}", self.frame_index);
-                let start_time = Instant::now();
-                self.current_frame = self.frames.remove(0);
-                self.remove_seconds += (Instant::now() - start_time).as_secs_f32();
-                self.remove_count += 1;
-                //rintln!("shapes in this frame = {}", self.current_frame.shapes.len());
-                self.next_frame_time = self.next_frame_time.add(Duration::from_secs_f64(self.current_frame.seconds_to_next));
-                if self.frames.is_empty() {
-                    println!("Last frame reached at elapsed seconds = {}", (Instant::now() - self.start_time).as_secs_f32());
-                    println!("remove_count = {}; remove_seconds = {}; swap_count = {}; swap_seconds = {}; draw_count = {}; draw_seconds = {}", self.remove_count, self.remove_seconds, self.swap_count, self.swap_seconds, self.draw_count, self.draw_seconds);
-                }
-                //rintln!("seconds_to_next = {}", self.frames[self.current_frame].seconds_to_next);
-                //rintln!("next_frame_time = {}", self.next_frame_time.duration_since(self.start_time).as_secs_f32());
-                //bg!(self.current_frame, self.next_frame_time, self.frames[self.current_frame].seconds_to_next);
+            if let Some([_, scroll_y]) = e.mouse_scroll_args() {
+                rend.mouse_scroll(scroll_y);
             }
         }
-        // frame_index = min(frame_index, self.frames.len() - 1);
-        //let shapes = self.frames[self.current_frame].shapes.clone();
-        //bg!(&shapes);
-
-        let mut shapes = vec![];
-        let start_time = Instant::now();
-        std::mem::swap(&mut shapes, &mut self.current_frame.shapes);
-        self.swap_seconds += (Instant::now() - start_time).as_secs_f32();
-        self.swap_count += 1;
-
-        let clear_background= !self.additive || self.frame_index == 0;
-
-        self.draw_count += 1;
-        self.draw_shape_count += shapes.len();
-        let start_time = Instant::now();
-        let colors = self.colors.clone();
-        self.gl.draw(args.viewport(), |c, gl| {
-            // Clear the screen.
-            if clear_background {
-                clear(back_color.into(), gl);
-            }
+        Ok(())
+    }
 
-            for shape in shapes.iter() {
-                //bg!(&shape);
-                match shape {
-                    Shape::Circle { center, radius, color} => {
-                        let rect = [center.x - radius, center.y - radius, radius * 2.0, radius * 2.0];
-                        let transform = c.transform;
-                        ellipse((*color).into(), rect, transform, gl);
-                        //ellipse([1.0, 1.0, 1.0, 1.0], *rect, transform, gl);
-                    },
-                    Shape::CircleFast { center_x, center_y, radius, color_index} => {
-                        let rect = [center_x - radius, center_y - radius, radius * 2.0, radius * 2.0];
-                        let transform = c.transform;
-                        ellipse(colors[*color_index].for_render, rect, transform, gl);
-                    },
-                    Shape::Line { top_left, bottom_right, color, thickness} => {
-                        let coord = [top_left.x, top_left.y, bottom_right.x, bottom_right.y];
-                        let transform = c.transform;
-                        line((*color).into(), *thickness, coord, transform, gl);
-                    },
-                    Shape::LineFast { x0,y0,x1, y1, color_index, thickness} => {
-                        let coord = [*x0, *y0, *x1, *y1];
-                        let transform = c.transform;
-                        line(colors[*color_index].for_render, *thickness, coord, transform, gl);
-                    },
-                    Shape::Rectangle { top_left, bottom_right, color} => {
-                        // if *color != back_color {
-                        let rect = [top_left.x, top_left.y, bottom_right.x - top_left.x, bottom_right.y - top_left.y];
-                        let transform = c.transform;
-                        rectangle((*color).into(), rect, transform, gl);
-                        // }
-                    },
-                    Shape::RectangleFast { x, y, width, height, color_index} => {
-                        // if self.additive || *color_index != 0 {
-                        if *color_index != 0 {
-                            let rect = [*x, *y, *width, *height];
-                            let transform = c.transform;
-                            rectangle(colors[*color_index].for_render, rect, transform, gl);
-                        }
-                    },
-                    // _ => unimplemented!(),
-                }
-            }
+    // Dragging with the left mouse button pans the view.
+    fn mouse_move(&mut self, cursor: [f64; 2]) {
+        if self.dragging {
+            let mut camera = self.player.camera();
+            camera.pan(cursor[0] - self.cursor[0], cursor[1] - self.cursor[1]);
+            self.player.set_camera(camera);
+        }
+        self.cursor = cursor;
+    }
 
-        });
-        self.draw_seconds += (Instant::now() - start_time).as_secs_f32();
+    // The mouse wheel zooms around the point under the mouse.
+    fn mouse_scroll(&mut self, scroll_y: f64) {
+        let mut camera = self.player.camera();
+        camera.zoom_at(self.cursor[0], self.cursor[1], ZOOM_STEP.powf(scroll_y));
+        self.player.set_camera(camera);
+    }
 
-        let start_time = Instant::now();
-        std::mem::swap(&mut self.current_frame.shapes, &mut shapes);
-        self.swap_seconds += (Instant::now() - start_time).as_secs_f32();
-        self.swap_count += 1;
+    // Space pauses and resumes, Left and Right step one frame, Home and End go to the first and
+    // last frames, Up and Down double or halve the speed, L turns looping on and off, and 0 resets
+    // the view after panning and zooming.
+    fn key_press(&mut self, key: Key) {
+        let now = Instant::now();
+        let player = &mut self.player;
+        match key {
+            Key::Space => player.toggle_pause(now),
+            Key::Right => { player.step_forward(now); },
+            Key::Left => { player.step_back(now); },
+            Key::Home => { player.seek(0, now); },
+            Key::End => { player.seek_last(now); },
+            Key::Up => player.set_speed(player.speed() * 2.0, now),
+            Key::Down => player.set_speed(player.speed() / 2.0, now),
+            Key::L => player.set_looping(!player.is_looping()),
+            Key::D0 => player.set_camera(Camera::identity()),
+            _ => {},
+        }
+    }
+
+    fn render(&mut self, args: &RenderArgs) {
+        self.player.update(Instant::now());
+        let player = &mut self.player;
+        self.gl.draw(args.viewport(), |context, gl| {
+            player.draw(&mut GlBackend::new(context, gl));
+        });
     }
 
     fn update(&mut self, _args: &UpdateArgs) {
@@ -207,6 +177,70 @@ impl Renderer {
     }
 }
 
+impl<'a> GlBackend<'a> {
+    pub fn new(context: Context, gl: &'a mut GlGraphics) -> Self {
+        Self {
+            context,
+            gl,
+            blend_mode: BlendMode::Normal,
+            draw_state: DrawState::default(),
+        }
+    }
+}
+
+impl RenderBackend for GlBackend<'_> {
+    fn clear(&mut self, color: [f32; 4]) {
+        graphics::clear(color, self.gl);
+    }
+
+    fn circle(&mut self, center_x: f64, center_y: f64, radius: f64, color: [f32; 4]) {
+        let rect = [center_x - radius, center_y - radius, radius * 2.0, radius * 2.0];
+        Ellipse::new(color).draw(rect, &self.draw_state, self.context.transform, self.gl);
+    }
+
+    fn line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4]) {
+        let coord = [x0, y0, x1, y1];
+        Line::new(color, thickness).draw(coord, &self.draw_state, self.context.transform, self.gl);
+    }
+
+    fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]) {
+        let rect = [x, y, width, height];
+        Rectangle::new(color).draw(rect, &self.draw_state, self.context.transform, self.gl);
+    }
+
+    // graphics::polygon() draws a triangle fan, which is only right for convex polygons, so split
+    // the polygon into triangles first.
+    fn polygon(&mut self, points: &[PointF64], color: [f32; 4]) {
+        for [a, b, c] in triangulate(points).iter() {
+            Polygon::new(color).draw(&[[a.x, a.y], [b.x, b.y], [c.x, c.y]], &self.draw_state, self.context.transform, self.gl);
+        }
+    }
+
+    // Cover the whole window with a translucent background, using normal alpha blending whatever
+    // the current blend mode.
+    fn fade(&mut self, color: [f32; 4], amount: f32) {
+        let [width, height] = self.context.get_view_size();
+        let [r, g, b, _] = color;
+        Rectangle::new([r, g, b, amount]).draw([0.0, 0.0, width, height], &DrawState::default(), self.context.transform, self.gl);
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    // OpenGL blending through the graphics crate has no screen or max equations, so those use
+    // additive blending, which also only lightens.
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+        let blend = match mode {
+            BlendMode::Normal => Blend::Alpha,
+            BlendMode::Add | BlendMode::Screen | BlendMode::Max => Blend::Lighter,
+            BlendMode::Multiply => Blend::Multiply,
+        };
+        self.draw_state = DrawState::default().blend(blend);
+    }
+}
+
 
 // use piston_window::*;
 /*
@@ -0,0 +1,850 @@
+// Animations described in a JSON file rather than in code, so they can be tweaked and played again
+// without recompiling. A scene is a list of items drawn in order, so later items appear on top.
+// Each item is either a track, which moves one shape through keyframes as with timeline::Track, or
+// a grid of values colored through a gradient or a list of colors.
+//
+//   {
+//     "title": "Bounce",
+//     "width": 600, "height": 200, "back_color": "black", "fps": 60,
+//     "items": [
+//       { "type": "grid", "top_left": [0, 150], "cell_size": 50,
+//         "values": [[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]],
+//         "colors": { "gradient": { "colormap": "viridis" } } },
+//       { "type": "track", "shape": { "type": "circle", "center": [30, 30], "radius": 20, "color": "#ff8000" },
+//         "repeat": "ping_pong",
+//         "keyframes": [
+//           { "over": 2.0, "easing": "bounce_out", "center": [570, 30] },
+//           { "hold": 0.5 } ] }
+//     ]
+//   }
+//
+// Points are [x, y]. Colors are a name like "red", a hex string like "#ff8000" or "#ff800080", an
+// array of three or four numbers from 0.0 to 1.0, or a number which is an index into the scene's
+// palette for the *Fast shapes.
+//
+// Each keyframe has exactly one of "over" (seconds since the previous keyframe), "at" (seconds
+// since the start of the track) or "hold" (seconds to stay still), along with any of "easing",
+// "center", "radius", "color", "thickness", "endpoints", "corners", "points" and "shape" to say what
+// changes. An easing stays in effect for the following keyframes until it's changed again. A track
+// with no keyframes is a shape that stays still.
+//
+// Everything is checked when the scene is loaded, including color names, keyframe timings, grid
+// files and whether each keyframe's changes fit its shape, so a scene that loads will play. Grid cells are drawn with palette colors that come after
+// the scene's own palette, so anything that draws a scene's shapes needs its colors().
+
+use std::collections::HashMap;
+use std::fs;
+use serde::Deserialize;
+
+use crate::*;
+use crate::animator::Animator;
+use crate::easing::Easing;
+use crate::gradient::Gradient;
+use crate::grid::Grid;
+use crate::palette::Colormap;
+use crate::svg_export::merge_rectangles_fast;
+use crate::timeline::{Timeline, Track};
+
+const EXAMPLE_FILE_NAME: &str = "scenes/example.json";
+
+#[derive(Clone, Debug)]
+pub struct Scene {
+    title: String,
+    width: f64,
+    height: f64,
+    back_color: Color1,
+    fps: f64,
+    seconds: Option<f64>,
+    // The scene's palette followed by the colors of any grids.
+    colors: Vec<Color1>,
+    timeline: Timeline,
+}
+
+// The scene as it appears in the file.
+#[derive(Clone, Debug, Deserialize)]
+struct SceneSpec {
+    #[serde(default = "default_title")]
+    title: String,
+    width: f64,
+    height: f64,
+    #[serde(default = "default_back_color")]
+    back_color: ColorSpec,
+    #[serde(default = "default_fps")]
+    fps: f64,
+    // How long to play. If left out the scene ends when the last track finishes.
+    #[serde(default)]
+    seconds: Option<f64>,
+    // The colors for shapes whose color is given as an index.
+    #[serde(default)]
+    palette: Vec<ColorSpec>,
+    #[serde(default)]
+    items: Vec<ItemSpec>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ItemSpec {
+    Track {
+        shape: ShapeSpec,
+        #[serde(default)]
+        delay: f64,
+        #[serde(default)]
+        repeat: RepeatSpec,
+        #[serde(default)]
+        show_before_start: bool,
+        #[serde(default)]
+        hide_at_end: bool,
+        #[serde(default)]
+        keyframes: Vec<KeyframeSpec>,
+    },
+    Grid {
+        #[serde(default)]
+        top_left: [f64; 2],
+        cell_size: f64,
+        // The values row by row, or else the name of a file saved with Grid::write().
+        #[serde(default)]
+        values: Option<Vec<Vec<f64>>>,
+        #[serde(default)]
+        file: Option<String>,
+        colors: GridColorSpec,
+        // Cells with this value aren't drawn, so whatever is behind them shows through.
+        #[serde(default)]
+        skip_value: Option<f64>,
+        // Seconds until the grid appears.
+        #[serde(default)]
+        delay: f64,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RepeatSpec {
+    #[default]
+    Once,
+    Loop,
+    PingPong,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct KeyframeSpec {
+    #[serde(default)]
+    over: Option<f64>,
+    #[serde(default)]
+    at: Option<f64>,
+    #[serde(default)]
+    hold: Option<f64>,
+    #[serde(default)]
+    easing: Option<EasingSpec>,
+    #[serde(default)]
+    center: Option<[f64; 2]>,
+    #[serde(default)]
+    radius: Option<f64>,
+    #[serde(default)]
+    color: Option<ColorSpec>,
+    #[serde(default)]
+    thickness: Option<f64>,
+    #[serde(default)]
+    endpoints: Option<[[f64; 2]; 2]>,
+    #[serde(default)]
+    corners: Option<[[f64; 2]; 2]>,
+    #[serde(default)]
+    points: Option<Vec<[f64; 2]>>,
+    #[serde(default)]
+    shape: Option<ShapeSpec>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ShapeSpec {
+    Circle {
+        center: [f64; 2],
+        radius: f64,
+        color: ColorSpec,
+    },
+    Line {
+        from: [f64; 2],
+        to: [f64; 2],
+        thickness: f64,
+        color: ColorSpec,
+    },
+    Rectangle {
+        top_left: [f64; 2],
+        bottom_right: [f64; 2],
+        color: ColorSpec,
+    },
+    Polygon {
+        points: Vec<[f64; 2]>,
+        color: ColorSpec,
+    },
+    Polyline {
+        points: Vec<[f64; 2]>,
+        thickness: f64,
+        color: ColorSpec,
+    },
+    // Filled unless a stroke thickness is given.
+    Path {
+        start: [f64; 2],
+        segments: Vec<SegmentSpec>,
+        #[serde(default)]
+        stroke: Option<f64>,
+        color: ColorSpec,
+    },
+    Text {
+        top_left: [f64; 2],
+        size: f64,
+        color: ColorSpec,
+        text: String,
+    },
+    Group {
+        #[serde(default)]
+        blend: BlendSpec,
+        shapes: Vec<ShapeSpec>,
+    },
+}
+
+// The names match PathSegment's and are what appear in the file.
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SegmentSpec {
+    LineTo([f64; 2]),
+    // The control point and then the end point.
+    QuadraticTo([[f64; 2]; 2]),
+    // Both control points and then the end point.
+    CubicTo([[f64; 2]; 3]),
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BlendSpec {
+    #[default]
+    Normal,
+    Add,
+    Multiply,
+    Screen,
+    Max,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum ColorSpec {
+    Index(usize),
+    Rgb([f32; 3]),
+    Rgba([f32; 4]),
+    // A name or a hex string.
+    Text(String),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum EasingSpec {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ElasticIn,
+    ElasticOut,
+    BounceIn,
+    BounceOut,
+    CssEase,
+    CssEaseInOut,
+    CubicBezier([f64; 4]),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum GridColorSpec {
+    Gradient(GradientSpec),
+    // Each value is rounded and used as an index into the list.
+    Colors(Vec<ColorSpec>),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct GradientSpec {
+    // Either a named colormap or a list of [position, color] stops.
+    #[serde(default)]
+    colormap: Option<ColormapSpec>,
+    #[serde(default)]
+    stops: Option<Vec<(f64, ColorSpec)>>,
+    #[serde(default)]
+    space: Option<ColorSpaceSpec>,
+    #[serde(default)]
+    mapping: MappingSpec,
+    // The range for a linear or log mapping, which defaults to the lowest and highest values in the
+    // grid.
+    #[serde(default)]
+    min: Option<f64>,
+    #[serde(default)]
+    max: Option<f64>,
+    // Only for a modular mapping.
+    #[serde(default)]
+    period: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ColormapSpec {
+    Viridis,
+    Magma,
+    Grayscale,
+    Cyclic,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ColorSpaceSpec {
+    Srgb,
+    LinearRgb,
+    Hsv,
+    Hsl,
+    Lab,
+    Oklab,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MappingSpec {
+    #[default]
+    Linear,
+    Log,
+    Histogram,
+    Modular,
+}
+
+pub fn main() {
+    Scene::load(EXAMPLE_FILE_NAME).unwrap_or_else(|e| panic!("{}", e)).show();
+}
+
+impl Scene {
+    pub fn from_json(json: &str) -> DrawResult<Self> {
+        Self::from_json_named(json, "<json>")
+    }
+
+    pub fn load(file_name: &str) -> DrawResult<Self> {
+        let json = fs::read_to_string(file_name).map_err(|e| DrawError::from_io(file_name, e))?;
+        Self::from_json_named(&json, file_name)
+    }
+
+    // The file name is only for the error messages.
+    fn from_json_named(json: &str, file_name: &str) -> DrawResult<Self> {
+        let spec: SceneSpec = serde_json::from_str(json)
+            .map_err(|e| DrawError::Serialization { file_name: file_name.to_string(), message: e.to_string() })?;
+        spec.scene().map_err(|message| DrawError::Scene { file_name: file_name.to_string(), message })
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    pub fn back_color(&self) -> Color1 {
+        self.back_color
+    }
+
+    pub fn colors(&self) -> Vec<Color1> {
+        self.colors.clone()
+    }
+
+    pub fn timeline(&self) -> Timeline {
+        self.timeline.clone()
+    }
+
+    // The frames for the length of the scene at its own frame rate.
+    pub fn frames(&self) -> Vec<Frame> {
+        self.timeline.frames_for(self.fps, self.seconds.unwrap_or_else(|| self.timeline.duration()))
+    }
+
+    // Add the scene to an animator starting at the given frame, at the animator's frame rate.
+    pub fn add_to_animator(&self, animator: &mut Animator, start_frame_index: usize) {
+        animator.add_timeline(start_frame_index, &self.timeline);
+    }
+
+    pub fn show(&self) {
+        renderer_3::Renderer::display_additive_with_colors(&self.title, self.width, self.height, self.back_color(), self.frames(), false, self.colors());
+    }
+
+    pub fn save_gif(&self, file_name: &str) -> DrawResult<()> {
+        gif_export::save_gif_additive_with_colors(file_name, self.width, self.height, self.back_color(), self.frames(), false, self.colors())
+    }
+}
+
+impl SceneSpec {
+    fn scene(&self) -> Result<Scene, String> {
+        if self.fps.is_nan() || self.fps <= 0.0 {
+            return Err(format!("\"fps\" must be greater than zero, not {}.", self.fps));
+        }
+        let mut colors = self.palette.iter().map(|color| color.color()).collect::<Result<Vec<_>, _>>()?;
+        let mut timeline = Timeline::new();
+        for item in self.items.iter() {
+            timeline.add_track(item.track(&mut colors)?);
+        }
+        Ok(Scene {
+            title: self.title.clone(),
+            width: self.width,
+            height: self.height,
+            back_color: self.back_color.color()?,
+            fps: self.fps,
+            seconds: self.seconds,
+            colors,
+            timeline,
+        })
+    }
+}
+
+impl ItemSpec {
+    // A grid adds its colors to the end of scene_colors.
+    fn track(&self, scene_colors: &mut Vec<Color1>) -> Result<Track, String> {
+        match self {
+            ItemSpec::Track { shape, delay, repeat, show_before_start, hide_at_end, keyframes } => {
+                let mut track = Track::new(&shape.shape()?).delay(check_delay(*delay)?);
+                track = match repeat {
+                    RepeatSpec::Once => track,
+                    RepeatSpec::Loop => track.looping(),
+                    RepeatSpec::PingPong => track.ping_pong(),
+                };
+                if *show_before_start {
+                    track = track.show_before_start();
+                }
+                if *hide_at_end {
+                    track = track.hide_at_end();
+                }
+                for keyframe in keyframes.iter() {
+                    track = keyframe.apply(track)?;
+                }
+                Ok(track)
+            },
+            ItemSpec::Grid { top_left, cell_size, values, file, colors, skip_value, delay } => {
+                let values = match (values, file) {
+                    (Some(values), None) => values.clone(),
+                    (None, Some(file)) => {
+                        let grid: Grid<usize> = Grid::read(file).map_err(|e| e.to_string())?;
+                        grid.rows().map(|row| row.iter().map(|value| *value as f64).collect()).collect()
+                    },
+                    _ => return Err("A grid needs either \"values\" or \"file\", but not both.".to_string()),
+                };
+                let color_func = colors.color_func(&values)?;
+                // The track copies its shape for every frame, so keep it small: each distinct value
+                // gets one palette slot and the cells are RectangleFast shapes merged into runs.
+                let mut value_color_indexes = HashMap::new();
+                let mut shapes = vec![];
+                for (y, row) in values.iter().enumerate() {
+                    for (x, value) in row.iter().enumerate() {
+                        if Some(*value) == *skip_value {
+                            continue;
+                        }
+                        let color_index = match value_color_indexes.get(&value.to_bits()) {
+                            Some(color_index) => *color_index,
+                            None => {
+                                let color_index = scene_colors.len();
+                                scene_colors.push(color_func(*value)?);
+                                value_color_indexes.insert(value.to_bits(), color_index);
+                                color_index
+                            },
+                        };
+                        shapes.push(Shape::rectangle_fast(top_left[0] + (x as f64 * cell_size), top_left[1] + (y as f64 * cell_size), *cell_size, *cell_size, color_index));
+                    }
+                }
+                Ok(Track::new(&Shape::blend(BlendMode::Normal, merge_rectangles_fast(&shapes))).delay(check_delay(*delay)?))
+            },
+        }
+    }
+}
+
+impl KeyframeSpec {
+    fn apply(&self, track: Track) -> Result<Track, String> {
+        let mut track = track;
+        if let Some(easing) = self.easing {
+            track = track.ease(easing.easing());
+        }
+        if let Some(shape) = &self.shape {
+            track = track.shape(&shape.shape()?);
+        }
+        if let Some(center) = self.center {
+            track = track.center(point(center));
+        }
+        if let Some(radius) = self.radius {
+            if !track.working_shape().can_set_radius() {
+                return Err("A \"radius\" can only be given for a circle or a shape with some size.".to_string());
+            }
+            track = track.radius(radius);
+        }
+        if let Some(color) = &self.color {
+            track = match color {
+                ColorSpec::Index(index) => {
+                    if !track.working_shape().has_color_index() {
+                        return Err(format!("Palette index {} given for a shape that has a color rather than a palette index.", index));
+                    }
+                    track.color_index(*index)
+                },
+                _ => {
+                    if !track.working_shape().has_color() {
+                        return Err("A color given for a shape that uses a palette index.".to_string());
+                    }
+                    track.color(color.color()?)
+                },
+            };
+        }
+        if let Some(thickness) = self.thickness {
+            if !track.working_shape().has_thickness() {
+                return Err("Only lines, polylines and stroked paths have a \"thickness\".".to_string());
+            }
+            track = track.thickness(thickness);
+        }
+        if let Some([start, end]) = self.endpoints {
+            if !track.working_shape().has_endpoints() {
+                return Err("Only lines have \"endpoints\".".to_string());
+            }
+            track = track.endpoints(point(start), point(end));
+        }
+        if let Some([top_left, bottom_right]) = self.corners {
+            if !track.working_shape().has_corners() {
+                return Err("Only rectangles have \"corners\".".to_string());
+            }
+            track = track.corners(point(top_left), point(bottom_right));
+        }
+        if let Some(points) = &self.points {
+            if !track.working_shape().has_points() {
+                return Err("Only polygons and polylines have \"points\".".to_string());
+            }
+            track = track.points(points.iter().map(|one_point| point(*one_point)).collect());
+        }
+        // A hold goes back to the previous shape, so only the other keyframes move to a new one.
+        if self.hold.is_none() && !track.last_shape().can_morph(track.working_shape()) {
+            return Err("A keyframe's \"shape\" can't be morphed into from the one before. Both have to be closed or both open, and both have a color or both a palette index.".to_string());
+        }
+        let seconds = self.over.or(self.at).or(self.hold).unwrap_or(0.0);
+        if seconds.is_nan() || seconds < 0.0 {
+            return Err(format!("A keyframe can't take {} seconds.", seconds));
+        }
+        match (self.over, self.at, self.hold) {
+            (Some(seconds), None, None) => Ok(track.over(seconds)),
+            (None, Some(seconds), None) => {
+                let previous_seconds = track.keyframes_seconds();
+                if seconds < previous_seconds {
+                    return Err(format!("A keyframe at {} seconds comes before the previous one at {} seconds.", seconds, previous_seconds));
+                }
+                Ok(track.at(seconds))
+            },
+            (None, None, Some(seconds)) => Ok(track.hold(seconds)),
+            _ => Err("Each keyframe needs exactly one of \"over\", \"at\" or \"hold\".".to_string()),
+        }
+    }
+}
+
+impl ShapeSpec {
+    fn shape(&self) -> Result<Shape, String> {
+        let shape = match self {
+            ShapeSpec::Circle { center, radius, color } => match color {
+                ColorSpec::Index(index) => Shape::circle_fast(center[0], center[1], *radius, *index),
+                _ => Shape::circle(point(*center), *radius, color.color()?),
+            },
+            ShapeSpec::Line { from, to, thickness, color } => match color {
+                ColorSpec::Index(index) => Shape::line_fast(from[0], from[1], to[0], to[1], *thickness, *index),
+                _ => Shape::line(point(*from), point(*to), *thickness, color.color()?),
+            },
+            ShapeSpec::Rectangle { top_left, bottom_right, color } => match color {
+                ColorSpec::Index(index) => Shape::rectangle_fast(top_left[0], top_left[1], bottom_right[0] - top_left[0], bottom_right[1] - top_left[1], *index),
+                _ => Shape::rectangle(point(*top_left), point(*bottom_right), color.color()?),
+            },
+            ShapeSpec::Polygon { points, color } => {
+                let points = points.iter().map(|one_point| point(*one_point)).collect();
+                match color {
+                    ColorSpec::Index(index) => Shape::polygon_fast(points, *index),
+                    _ => Shape::polygon(points, color.color()?),
+                }
+            },
+            ShapeSpec::Polyline { points, thickness, color } => {
+                let points = points.iter().map(|one_point| point(*one_point)).collect();
+                match color {
+                    ColorSpec::Index(index) => Shape::polyline_fast(points, *thickness, *index),
+                    _ => Shape::polyline(points, *thickness, color.color()?),
+                }
+            },
+            ShapeSpec::Path { start, segments, stroke, color } => {
+                let segments = segments.iter().map(|segment| segment.segment()).collect();
+                let style = match stroke {
+                    Some(thickness) => PathStyle::Stroke(*thickness),
+                    None => PathStyle::Fill,
+                };
+                match color {
+                    ColorSpec::Index(index) => Shape::path_fast(point(*start), segments, style, *index),
+                    _ => Shape::path(point(*start), segments, style, color.color()?),
+                }
+            },
+            ShapeSpec::Text { top_left, size, color, text } => match color {
+                ColorSpec::Index(index) => return Err(format!("Text has no palette version, so it can't use palette index {}.", index)),
+                _ => Shape::text(point(*top_left), *size, color.color()?, text),
+            },
+            ShapeSpec::Group { blend, shapes } => Shape::blend(blend.blend_mode(), shapes.iter().map(|shape| shape.shape()).collect::<Result<_, _>>()?),
+        };
+        Ok(shape)
+    }
+}
+
+impl SegmentSpec {
+    fn segment(&self) -> PathSegment {
+        match self {
+            SegmentSpec::LineTo(to) => PathSegment::LineTo { to: point(*to) },
+            SegmentSpec::QuadraticTo([control, to]) => PathSegment::QuadraticTo { control: point(*control), to: point(*to) },
+            SegmentSpec::CubicTo([control_1, control_2, to]) => PathSegment::CubicTo { control_1: point(*control_1), control_2: point(*control_2), to: point(*to) },
+        }
+    }
+}
+
+impl BlendSpec {
+    fn blend_mode(&self) -> BlendMode {
+        match self {
+            BlendSpec::Normal => BlendMode::Normal,
+            BlendSpec::Add => BlendMode::Add,
+            BlendSpec::Multiply => BlendMode::Multiply,
+            BlendSpec::Screen => BlendMode::Screen,
+            BlendSpec::Max => BlendMode::Max,
+        }
+    }
+}
+
+impl ColorSpec {
+    // Colors given as an index are looked up by the renderer, so they can't be turned into a color
+    // here.
+    fn color(&self) -> Result<Color1, String> {
+        match self {
+            ColorSpec::Index(index) => Err(format!("Palette index {} used where a color is needed.", index)),
+            ColorSpec::Rgb([r, g, b]) => Ok(Color1::from_rgb(*r, *g, *b)),
+            ColorSpec::Rgba([r, g, b, a]) => Ok(Color1::from_rgba(*r, *g, *b, *a)),
+            ColorSpec::Text(text) => {
+                if let Some(hex) = text.strip_prefix('#') {
+                    return hex_color(hex).ok_or_else(|| format!("Bad hex color \"{}\".", text));
+                }
+                match text.as_str() {
+                    "black" => Ok(Color1::black()),
+                    "white" => Ok(Color1::white()),
+                    "red" => Ok(Color1::red()),
+                    "green" => Ok(Color1::green()),
+                    "blue" => Ok(Color1::blue()),
+                    "light_gray" => Ok(Color1::light_gray()),
+                    "gray" => Ok(Color1::gray()),
+                    "dark_gray" => Ok(Color1::dark_gray()),
+                    _ => Err(format!("Unknown color \"{}\".", text)),
+                }
+            },
+        }
+    }
+}
+
+impl EasingSpec {
+    fn easing(&self) -> Easing {
+        match *self {
+            EasingSpec::Linear => Easing::Linear,
+            EasingSpec::EaseIn => Easing::EaseIn,
+            EasingSpec::EaseOut => Easing::EaseOut,
+            EasingSpec::EaseInOut => Easing::EaseInOut,
+            EasingSpec::CubicIn => Easing::CubicIn,
+            EasingSpec::CubicOut => Easing::CubicOut,
+            EasingSpec::CubicInOut => Easing::CubicInOut,
+            EasingSpec::ElasticIn => Easing::ElasticIn,
+            EasingSpec::ElasticOut => Easing::ElasticOut,
+            EasingSpec::BounceIn => Easing::BounceIn,
+            EasingSpec::BounceOut => Easing::BounceOut,
+            EasingSpec::CssEase => Easing::css_ease(),
+            EasingSpec::CssEaseInOut => Easing::css_ease_in_out(),
+            EasingSpec::CubicBezier([x1, y1, x2, y2]) => Easing::CubicBezier(x1, y1, x2, y2),
+        }
+    }
+}
+
+// The color for a grid value, or an error if there's no color for it.
+type ColorFunc = Box<dyn Fn(f64) -> Result<Color1, String>>;
+
+impl GridColorSpec {
+    fn color_func(&self, values: &[Vec<f64>]) -> Result<ColorFunc, String> {
+        match self {
+            GridColorSpec::Gradient(spec) => {
+                let gradient = spec.gradient(values)?;
+                Ok(Box::new(move |value| Ok(gradient.color(value))))
+            },
+            GridColorSpec::Colors(colors) => {
+                let colors = colors.iter().map(|color| color.color()).collect::<Result<Vec<_>, _>>()?;
+                Ok(Box::new(move |value| {
+                    let index = value.round().max(0.0) as usize;
+                    colors.get(index).copied()
+                        .ok_or_else(|| format!("Grid value {} has no color; there are only {} colors.", value, colors.len()))
+                }))
+            },
+        }
+    }
+}
+
+impl GradientSpec {
+    fn gradient(&self, values: &[Vec<f64>]) -> Result<Gradient, String> {
+        let gradient = match (self.colormap, &self.stops) {
+            (Some(colormap), None) => Gradient::from_colormap(colormap.colormap()),
+            (None, Some(stops)) => {
+                if stops.is_empty() || !stops.windows(2).all(|pair| pair[0].0 <= pair[1].0) {
+                    return Err("A gradient needs at least one stop, with the positions in order.".to_string());
+                }
+                Gradient::new(stops.iter().map(|(position, color)| Ok((*position, color.color()?))).collect::<Result<_, String>>()?)
+            },
+            (None, None) => Gradient::grayscale(),
+            _ => return Err("A gradient can have a colormap or stops, but not both.".to_string()),
+        };
+        let gradient = match self.space {
+            Some(space) => gradient.in_space(space.color_space()),
+            None => gradient,
+        };
+        let all_values = values.iter().flat_map(|row| row.iter().copied());
+        let min = self.min.unwrap_or_else(|| all_values.clone().fold(f64::INFINITY, f64::min));
+        let max = self.max.unwrap_or_else(|| all_values.clone().fold(f64::NEG_INFINITY, f64::max));
+        let gradient = match self.mapping {
+            MappingSpec::Linear => gradient.linear(min, max),
+            MappingSpec::Log => gradient.log(min, max),
+            MappingSpec::Histogram => {
+                if all_values.clone().next().is_none() {
+                    return Err("A histogram gradient needs a grid with at least one value.".to_string());
+                }
+                gradient.histogram(all_values)
+            },
+            MappingSpec::Modular => match self.period {
+                Some(period) if period > 0.0 => gradient.modular(period),
+                Some(period) => return Err(format!("A modular gradient's \"period\" must be greater than zero, not {}.", period)),
+                None => return Err("A modular gradient needs a \"period\".".to_string()),
+            },
+        };
+        Ok(gradient)
+    }
+}
+
+impl ColormapSpec {
+    fn colormap(&self) -> Colormap {
+        match self {
+            ColormapSpec::Viridis => Colormap::Viridis,
+            ColormapSpec::Magma => Colormap::Magma,
+            ColormapSpec::Grayscale => Colormap::Grayscale,
+            ColormapSpec::Cyclic => Colormap::Cyclic,
+        }
+    }
+}
+
+impl ColorSpaceSpec {
+    fn color_space(&self) -> ColorSpace {
+        match self {
+            ColorSpaceSpec::Srgb => ColorSpace::Srgb,
+            ColorSpaceSpec::LinearRgb => ColorSpace::LinearRgb,
+            ColorSpaceSpec::Hsv => ColorSpace::Hsv,
+            ColorSpaceSpec::Hsl => ColorSpace::Hsl,
+            ColorSpaceSpec::Lab => ColorSpace::Lab,
+            ColorSpaceSpec::Oklab => ColorSpace::Oklab,
+        }
+    }
+}
+
+fn check_delay(delay: f64) -> Result<f64, String> {
+    if delay.is_nan() || delay < 0.0 {
+        return Err(format!("A \"delay\" can't be {} seconds.", delay));
+    }
+    Ok(delay)
+}
+
+fn point(xy: [f64; 2]) -> PointF64 {
+    PointF64::new(xy[0], xy[1])
+}
+
+// Six or eight hex digits, without the leading '#'.
+fn hex_color(hex: &str) -> Option<Color1> {
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+    let mut channels = vec![];
+    for index in (0..hex.len()).step_by(2) {
+        channels.push(u8::from_str_radix(&hex[index..index + 2], 16).ok()?);
+    }
+    let a = if channels.len() == 4 { channels[3] } else { 255 };
+    Some(Color256::from_rgba(channels[0], channels[1], channels[2], a).into())
+}
+
+fn default_title() -> String {
+    "Scene".to_string()
+}
+
+fn default_back_color() -> ColorSpec {
+    ColorSpec::Text("black".to_string())
+}
+
+fn default_fps() -> f64 {
+    60.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scene_error(items: &str) -> String {
+        let json = format!(r#"{{ "width": 100, "height": 100, "items": [{}] }}"#, items);
+        match Scene::from_json(&json) {
+            Ok(_) => panic!("Scene loaded: {}", items),
+            Err(DrawError::Scene { message, .. }) => message,
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn load_rejects_what_cant_be_played() {
+        let circle = r#""shape": { "type": "circle", "center": [10, 10], "radius": 5, "color": "#;
+        assert!(scene_error(&format!(r#"{{ "type": "track", {} "mauve" }} }}"#, circle)).contains("Unknown color"));
+        assert!(scene_error(&format!(r##"{{ "type": "track", {} "#12345" }} }}"##, circle)).contains("Bad hex color"));
+        assert!(scene_error(&format!(r#"{{ "type": "track", {} "red" }}, "keyframes": [{{ "radius": 8 }}] }}"#, circle)).contains("exactly one"));
+        assert!(scene_error(&format!(r#"{{ "type": "track", {} "red" }}, "keyframes": [{{ "over": 1, "hold": 1 }}] }}"#, circle)).contains("exactly one"));
+        assert!(scene_error(r#"{ "type": "track", "shape": { "type": "text", "top_left": [0, 0], "size": 10, "color": 2, "text": "A" } }"#).contains("palette index"));
+        assert!(scene_error(r#"{ "type": "grid", "cell_size": 10, "values": [[0, 1]], "colors": { "gradient": { "mapping": "modular" } } }"#).contains("period"));
+        // Keyframe changes that don't fit the shape.
+        let keyframe_error = |keyframe: &str| scene_error(&format!(r#"{{ "type": "track", {} "red" }}, "keyframes": [{{ "over": 1, {} }}] }}"#, circle, keyframe));
+        assert!(keyframe_error(r#""thickness": 2"#).contains("thickness"));
+        assert!(keyframe_error(r#""endpoints": [[0, 0], [5, 5]]"#).contains("endpoints"));
+        assert!(keyframe_error(r#""corners": [[0, 0], [5, 5]]"#).contains("corners"));
+        assert!(keyframe_error(r#""points": [[0, 0], [5, 5]]"#).contains("points"));
+        assert!(keyframe_error(r#""color": 2"#).contains("palette index"));
+        assert!(keyframe_error(r#""shape": { "type": "line", "from": [0, 0], "to": [5, 5], "thickness": 1, "color": "red" }"#).contains("morphed"));
+        assert!(keyframe_error(r#""shape": { "type": "circle", "center": [10, 10], "radius": 5, "color": 1 }"#).contains("morphed"));
+        assert!(keyframe_error(r#""shape": { "type": "text", "top_left": [0, 0], "size": 10, "color": "red", "text": "A" }"#).contains("morphed"));
+        let palette_circle = r#""shape": { "type": "circle", "center": [10, 10], "radius": 5, "color": 1 }"#;
+        assert!(scene_error(&format!(r#"{{ "type": "track", {}, "keyframes": [{{ "over": 1, "color": "red" }}] }}"#, palette_circle)).contains("palette index"));
+        let dot = r#""shape": { "type": "polygon", "points": [[10, 10], [10, 10], [10, 10]], "color": "red" }"#;
+        assert!(scene_error(&format!(r#"{{ "type": "track", {}, "keyframes": [{{ "over": 1, "radius": 5 }}] }}"#, dot)).contains("radius"));
+        assert!(scene_error(&format!(r#"{{ "type": "track", {} "red" }}, "delay": -1 }}"#, circle)).contains("delay"));
+    }
+
+    #[test]
+    fn load_reports_a_missing_file() {
+        assert!(Scene::load("scenes/no_such_scene.json").err().unwrap().is_not_found());
+    }
+
+    #[test]
+    fn grid_cells_are_merged_palette_rectangles() {
+        let json = r#"{ "width": 40, "height": 20, "palette": ["red"], "items": [
+            { "type": "grid", "cell_size": 10, "values": [[0, 0, 1, 1], [0, 0, 1, 1]], "colors": { "colors": ["black", "white"] } } ] }"#;
+        let scene = Scene::from_json(json).unwrap();
+        // The palette's one color, then one for each value in the grid.
+        assert_eq!(3, scene.colors().len());
+        let shapes = scene.timeline().shapes_at(0.0);
+        match &shapes[..] {
+            [Shape::Blend { shapes, .. }] => {
+                assert_eq!(2, shapes.len());
+                assert!(shapes.iter().all(|shape| matches!(shape, Shape::RectangleFast { .. })));
+            },
+            _ => panic!("Expected one group of shapes."),
+        }
+    }
+}
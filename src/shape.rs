@@ -1,8 +1,18 @@
+use serde::{Serialize, Deserialize};
+
 use crate::*;
 
 pub type ShapeList = Vec<Shape>;
 
-#[derive(Clone, Debug)]
+// Curves in a path are drawn as straight segments about this long, in display units.
+const PATH_FLATTEN_LENGTH: f64 = 2.0;
+const PATH_FLATTEN_MAX_STEPS: usize = 256;
+
+// When morphing between different kinds of shapes, the least number of points to give each outline.
+// Circles are turned into polygons with this many points.
+const MORPH_POINT_COUNT: usize = 64;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Shape {
     Circle {
         //center: euclid::Point2D<f64, ScreenSpace>,
@@ -42,11 +52,101 @@ pub enum Shape {
         height: f64,
         color_index: usize,
     },
-    //Text {
+    Text {
+        top_left: PointF64,
+        // Height of a capital letter. See bitmap_font.
+        size: f64,
+        color: Color1,
+        text: String,
+    },
+    // A filled polygon. The last point is joined back to the first.
+    Polygon {
+        points: Vec<PointF64>,
+        color: Color1,
+    },
+    PolygonFast {
+        points: Vec<PointF64>,
+        color_index: usize,
+    },
+    // Connected line segments through the points. As with Line the thickness is a radius.
+    Polyline {
+        points: Vec<PointF64>,
+        thickness: f64,
+        color: Color1,
+    },
+    PolylineFast {
+        points: Vec<PointF64>,
+        thickness: f64,
+        color_index: usize,
+    },
+    // Straight and Bézier segments following on from the start point, either filled or stroked.
+    Path {
+        start: PointF64,
+        segments: Vec<PathSegment>,
+        style: PathStyle,
+        color: Color1,
+    },
+    PathFast {
+        start: PointF64,
+        segments: Vec<PathSegment>,
+        style: PathStyle,
+        color_index: usize,
+    },
+    // Draw the shapes with the given blend mode instead of the frame's.
+    Blend {
+        mode: BlendMode,
+        shapes: ShapeList,
+    },
+}
 
-    // },
+// How a shape's color is combined with what's already been drawn. In each case the shape's alpha
+// (times its coverage of the pixel when anti-aliasing) controls how strong the effect is.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BlendMode {
+    // The usual alpha "over" compositing.
+    Normal,
+    // Add the color to what's there, so overlapping shapes build up like light. Good for density
+    // plots where many faint points land in the same place.
+    Add,
+    // Multiply by the color, which can only darken, like overlapping filters.
+    Multiply,
+    // The opposite of Multiply, which can only lighten.
+    Screen,
+    // Keep the brighter of the color and what's there, channel by channel.
+    Max,
 }
 
+// Each segment starts where the previous one ended.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PathSegment {
+    LineTo {
+        to: PointF64,
+    },
+    QuadraticTo {
+        control: PointF64,
+        to: PointF64,
+    },
+    CubicTo {
+        control_1: PointF64,
+        control_2: PointF64,
+        to: PointF64,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PathStyle {
+    // Closed back to the start point and filled.
+    Fill,
+    // An open line with the given thickness, which is a radius as with Line.
+    Stroke(f64),
+}
+
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::Normal
+    }
+}
 
 impl Shape {
 
@@ -109,47 +209,723 @@ impl Shape {
         }
     }
 
+    pub fn text(top_left: PointF64, size: f64, color: Color1, text: &str) -> Shape {
+        Shape::Text {
+            top_left,
+            size,
+            color,
+            text: text.to_string(),
+        }
+    }
+
+    pub fn text_xy(x: f64, y: f64, size: f64, color: Color1, text: &str) -> Shape {
+        Shape::text(PointF64::new(x, y), size, color, text)
+    }
+
+    pub fn polygon(points: Vec<PointF64>, color: Color1) -> Shape {
+        Shape::Polygon {
+            points,
+            color,
+        }
+    }
+
+    pub fn polygon_fast(points: Vec<PointF64>, color_index: usize) -> Shape {
+        Shape::PolygonFast {
+            points,
+            color_index,
+        }
+    }
+
+    pub fn polyline(points: Vec<PointF64>, thickness: f64, color: Color1) -> Shape {
+        Shape::Polyline {
+            points,
+            thickness,
+            color,
+        }
+    }
+
+    pub fn polyline_fast(points: Vec<PointF64>, thickness: f64, color_index: usize) -> Shape {
+        Shape::PolylineFast {
+            points,
+            thickness,
+            color_index,
+        }
+    }
+
+    pub fn path(start: PointF64, segments: Vec<PathSegment>, style: PathStyle, color: Color1) -> Shape {
+        Shape::Path {
+            start,
+            segments,
+            style,
+            color,
+        }
+    }
+
+    pub fn path_fast(start: PointF64, segments: Vec<PathSegment>, style: PathStyle, color_index: usize) -> Shape {
+        Shape::PathFast {
+            start,
+            segments,
+            style,
+            color_index,
+        }
+    }
+
+    // A single quadratic Bézier curve drawn as a line.
+    pub fn quadratic_fast(from: PointF64, control: PointF64, to: PointF64, thickness: f64, color_index: usize) -> Shape {
+        Shape::path_fast(from, vec![PathSegment::QuadraticTo { control, to }], PathStyle::Stroke(thickness), color_index)
+    }
+
+    // A single cubic Bézier curve drawn as a line.
+    pub fn cubic_fast(from: PointF64, control_1: PointF64, control_2: PointF64, to: PointF64, thickness: f64, color_index: usize) -> Shape {
+        Shape::path_fast(from, vec![PathSegment::CubicTo { control_1, control_2, to }], PathStyle::Stroke(thickness), color_index)
+    }
+
+    pub fn blend(mode: BlendMode, shapes: ShapeList) -> Shape {
+        Shape::Blend {
+            mode,
+            shapes,
+        }
+    }
+
     pub fn gradiant_one(&self, other: &Self, step_count: usize, step_index: usize) -> Self {
         debug_assert!(step_index <= step_count);
+        self.interpolate(other, step_index as f64 / step_count as f64)
+    }
+
+    // The shape a fraction t of the way from this shape to the other one. t may go a little below
+    // 0.0 or above 1.0 when an easing curve overshoots, in which case positions carry on past the
+    // ends while colors are clamped and sizes can't go below zero.
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
         match (self, other) {
             (Self::Circle { center: from_center, radius: from_radius, color: from_color},
                     Self::Circle { center: to_center, radius: to_radius, color: to_color}) => {
-                let center = from_center.gradiant_one(&to_center, step_count, step_index);
-                let radius = gradiant_f64_one(*from_radius, *to_radius, step_count, step_index);
-                let color = from_color.gradiant_one(to_color, step_count, step_index);
+                let center = from_center.interpolate(&to_center, t);
+                let radius = interpolate_f64(*from_radius, *to_radius, t).max(0.0);
+                let color = from_color.interpolate(to_color, t as f32, ColorSpace::Srgb);
                 Self::circle(center, radius, color)
             },
-            _ => unimplemented!(),
+            (Self::CircleFast { center_x: from_x, center_y: from_y, radius: from_radius, color_index: from_index },
+                    Self::CircleFast { center_x: to_x, center_y: to_y, radius: to_radius, color_index: to_index }) => {
+                let radius = interpolate_f64(*from_radius, *to_radius, t).max(0.0);
+                Self::circle_fast(interpolate_f64(*from_x, *to_x, t), interpolate_f64(*from_y, *to_y, t), radius, switch_halfway(from_index, to_index, t))
+            },
+            (Self::Line { top_left: from_start, bottom_right: from_end, thickness: from_thickness, color: from_color },
+                    Self::Line { top_left: to_start, bottom_right: to_end, thickness: to_thickness, color: to_color }) => {
+                let thickness = interpolate_f64(*from_thickness, *to_thickness, t).max(0.0);
+                let color = from_color.interpolate(to_color, t as f32, ColorSpace::Srgb);
+                Self::line(from_start.interpolate(to_start, t), from_end.interpolate(to_end, t), thickness, color)
+            },
+            (Self::LineFast { x0: from_x0, y0: from_y0, x1: from_x1, y1: from_y1, thickness: from_thickness, color_index: from_index },
+                    Self::LineFast { x0: to_x0, y0: to_y0, x1: to_x1, y1: to_y1, thickness: to_thickness, color_index: to_index }) => {
+                let thickness = interpolate_f64(*from_thickness, *to_thickness, t).max(0.0);
+                Self::line_fast(interpolate_f64(*from_x0, *to_x0, t), interpolate_f64(*from_y0, *to_y0, t),
+                                interpolate_f64(*from_x1, *to_x1, t), interpolate_f64(*from_y1, *to_y1, t),
+                                thickness, switch_halfway(from_index, to_index, t))
+            },
+            (Self::Rectangle { top_left: from_top_left, bottom_right: from_bottom_right, color: from_color },
+                    Self::Rectangle { top_left: to_top_left, bottom_right: to_bottom_right, color: to_color }) => {
+                let color = from_color.interpolate(to_color, t as f32, ColorSpace::Srgb);
+                Self::rectangle(from_top_left.interpolate(to_top_left, t), from_bottom_right.interpolate(to_bottom_right, t), color)
+            },
+            (Self::RectangleFast { x: from_x, y: from_y, width: from_width, height: from_height, color_index: from_index },
+                    Self::RectangleFast { x: to_x, y: to_y, width: to_width, height: to_height, color_index: to_index }) => {
+                Self::rectangle_fast(interpolate_f64(*from_x, *to_x, t), interpolate_f64(*from_y, *to_y, t),
+                                     interpolate_f64(*from_width, *to_width, t).max(0.0), interpolate_f64(*from_height, *to_height, t).max(0.0),
+                                     switch_halfway(from_index, to_index, t))
+            },
+            (Self::Text { top_left: from_top_left, size: from_size, color: from_color, text: from_text },
+                    Self::Text { top_left: to_top_left, size: to_size, color: to_color, text: to_text }) => {
+                let size = interpolate_f64(*from_size, *to_size, t).max(0.0);
+                let color = from_color.interpolate(to_color, t as f32, ColorSpace::Srgb);
+                Self::text(from_top_left.interpolate(to_top_left, t), size, color, &switch_halfway(from_text, to_text, t))
+            },
+            (Self::Polygon { points: from_points, color: from_color },
+                    Self::Polygon { points: to_points, color: to_color }) if from_points.len() == to_points.len() => {
+                let points = interpolate_points(from_points, to_points, t);
+                let color = from_color.interpolate(to_color, t as f32, ColorSpace::Srgb);
+                Self::polygon(points, color)
+            },
+            (Self::PolygonFast { points: from_points, color_index: from_index },
+                    Self::PolygonFast { points: to_points, color_index: to_index }) if from_points.len() == to_points.len() => {
+                Self::polygon_fast(interpolate_points(from_points, to_points, t), switch_halfway(from_index, to_index, t))
+            },
+            (Self::Polyline { points: from_points, thickness: from_thickness, color: from_color },
+                    Self::Polyline { points: to_points, thickness: to_thickness, color: to_color }) if from_points.len() == to_points.len() => {
+                let points = interpolate_points(from_points, to_points, t);
+                let thickness = interpolate_f64(*from_thickness, *to_thickness, t).max(0.0);
+                let color = from_color.interpolate(to_color, t as f32, ColorSpace::Srgb);
+                Self::polyline(points, thickness, color)
+            },
+            (Self::PolylineFast { points: from_points, thickness: from_thickness, color_index: from_index },
+                    Self::PolylineFast { points: to_points, thickness: to_thickness, color_index: to_index }) if from_points.len() == to_points.len() => {
+                let thickness = interpolate_f64(*from_thickness, *to_thickness, t).max(0.0);
+                Self::polyline_fast(interpolate_points(from_points, to_points, t), thickness, switch_halfway(from_index, to_index, t))
+            },
+            (Self::Path { start: from_start, segments: from_segments, style: from_style, color: from_color },
+                    Self::Path { start: to_start, segments: to_segments, style: to_style, color: to_color }) if paths_match(from_segments, *from_style, to_segments, *to_style) => {
+                let (start, segments, style) = interpolate_path(*from_start, from_segments, *from_style, *to_start, to_segments, *to_style, t);
+                let color = from_color.interpolate(to_color, t as f32, ColorSpace::Srgb);
+                Self::path(start, segments, style, color)
+            },
+            (Self::PathFast { start: from_start, segments: from_segments, style: from_style, color_index: from_index },
+                    Self::PathFast { start: to_start, segments: to_segments, style: to_style, color_index: to_index }) if paths_match(from_segments, *from_style, to_segments, *to_style) => {
+                let (start, segments, style) = interpolate_path(*from_start, from_segments, *from_style, *to_start, to_segments, *to_style, t);
+                Self::path_fast(start, segments, style, switch_halfway(from_index, to_index, t))
+            },
+            (Self::Blend { mode: from_mode, shapes: from_shapes }, Self::Blend { mode: to_mode, shapes: to_shapes }) if from_shapes.len() == to_shapes.len() => {
+                let shapes = from_shapes.iter().zip(to_shapes.iter())
+                    .map(|(from_shape, to_shape)| from_shape.interpolate(to_shape, t))
+                    .collect();
+                Self::blend(switch_halfway(from_mode, to_mode, t), shapes)
+            },
+            _ => self.morph(other, t),
         }
     }
 
-    pub fn with_center(&self, new_center: PointF64) -> Self {
+    // True if interpolate() moves smoothly from this shape to the other one rather than showing
+    // this shape until halfway and then switching to the other one. Shapes of different kinds can
+    // be morphed if both are closed or both are open and both have a Color1 or both have a color
+    // index. Text only goes smoothly to text, and blend groups to blend groups with the same number
+    // of shapes.
+    pub fn can_morph(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Text { .. }, Self::Text { .. }) => true,
+            (Self::Blend { shapes: from_shapes, .. }, Self::Blend { shapes: to_shapes, .. }) => {
+                from_shapes.len() == to_shapes.len()
+                    && from_shapes.iter().zip(to_shapes.iter()).all(|(from_shape, to_shape)| from_shape.can_morph(to_shape))
+            },
+            _ => match (self.outline(), other.outline()) {
+                (Some((from_outline, from_paint)), Some((to_outline, to_paint))) => {
+                    from_outline.is_closed() == to_outline.is_closed() && from_paint.is_index() == to_paint.is_index()
+                },
+                _ => false,
+            },
+        }
+    }
+
+    // Morph between shapes of different kinds, or polygons and the like with different numbers of
+    // points. Both shapes are turned into outlines with the same number of points spaced evenly
+    // around them, then the points are moved from one outline to the other. Closed shapes (circles,
+    // rectangles, polygons and filled paths) become polygons while the morph is underway, and open
+    // ones (lines, polylines and stroked paths) become polylines. Shapes that can't be morphed,
+    // such as a closed shape and an open one, switch from one to the other halfway through.
+    fn morph(&self, other: &Self, t: f64) -> Self {
+        let ((from_outline, from_paint), (to_outline, to_paint)) = match (self.outline(), other.outline()) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return switch_halfway(self, other, t),
+        };
+        match ((from_outline, to_outline), (from_paint, to_paint)) {
+            ((Outline::Closed(from_points), Outline::Closed(to_points)), (Paint::Color(from_color), Paint::Color(to_color))) => {
+                Self::polygon(morph_closed_points(&from_points, &to_points, t), from_color.interpolate(&to_color, t as f32, ColorSpace::Srgb))
+            },
+            ((Outline::Closed(from_points), Outline::Closed(to_points)), (Paint::Index(from_index), Paint::Index(to_index))) => {
+                Self::polygon_fast(morph_closed_points(&from_points, &to_points, t), switch_halfway(&from_index, &to_index, t))
+            },
+            ((Outline::Open(from_points, from_thickness), Outline::Open(to_points, to_thickness)), (Paint::Color(from_color), Paint::Color(to_color))) => {
+                let thickness = interpolate_f64(from_thickness, to_thickness, t).max(0.0);
+                Self::polyline(morph_open_points(&from_points, &to_points, t), thickness, from_color.interpolate(&to_color, t as f32, ColorSpace::Srgb))
+            },
+            ((Outline::Open(from_points, from_thickness), Outline::Open(to_points, to_thickness)), (Paint::Index(from_index), Paint::Index(to_index))) => {
+                let thickness = interpolate_f64(from_thickness, to_thickness, t).max(0.0);
+                Self::polyline_fast(morph_open_points(&from_points, &to_points, t), thickness, switch_halfway(&from_index, &to_index, t))
+            },
+            _ => switch_halfway(self, other, t),
+        }
+    }
+
+    // The shape as a list of points, and its color or color index. Text and blend groups have no
+    // outline.
+    fn outline(&self) -> Option<(Outline, Paint)> {
+        let outline = match self {
+            Self::Circle { center, radius, color } => (Outline::Closed(circle_points(*center, *radius)), Paint::Color(*color)),
+            Self::CircleFast { center_x, center_y, radius, color_index } => (Outline::Closed(circle_points(PointF64::new(*center_x, *center_y), *radius)), Paint::Index(*color_index)),
+            Self::Line { top_left, bottom_right, thickness, color } => (Outline::Open(vec![*top_left, *bottom_right], *thickness), Paint::Color(*color)),
+            Self::LineFast { x0, y0, x1, y1, thickness, color_index } => (Outline::Open(vec![PointF64::new(*x0, *y0), PointF64::new(*x1, *y1)], *thickness), Paint::Index(*color_index)),
+            Self::Rectangle { top_left, bottom_right, color } => (Outline::Closed(rectangle_points(top_left.x, top_left.y, bottom_right.x, bottom_right.y)), Paint::Color(*color)),
+            Self::RectangleFast { x, y, width, height, color_index } => (Outline::Closed(rectangle_points(*x, *y, x + width, y + height)), Paint::Index(*color_index)),
+            Self::Polygon { points, color } => (Outline::Closed(points.clone()), Paint::Color(*color)),
+            Self::PolygonFast { points, color_index } => (Outline::Closed(points.clone()), Paint::Index(*color_index)),
+            Self::Polyline { points, thickness, color } => (Outline::Open(points.clone(), *thickness), Paint::Color(*color)),
+            Self::PolylineFast { points, thickness, color_index } => (Outline::Open(points.clone(), *thickness), Paint::Index(*color_index)),
+            Self::Path { start, segments, style, color } => (path_outline(*start, segments, *style), Paint::Color(*color)),
+            Self::PathFast { start, segments, style, color_index } => (path_outline(*start, segments, *style), Paint::Index(*color_index)),
+            Self::Text { .. } | Self::Blend { .. } => return None,
+        };
+        Some(outline)
+    }
+
+    // The smallest axis-aligned rectangle containing the shape, ignoring line thickness, as the
+    // top left and bottom right corners.
+    pub fn bounds(&self) -> (PointF64, PointF64) {
+        match self {
+            Self::Circle { center, radius, .. } => (PointF64::new(center.x - radius, center.y - radius), PointF64::new(center.x + radius, center.y + radius)),
+            Self::CircleFast { center_x, center_y, radius, .. } => (PointF64::new(center_x - radius, center_y - radius), PointF64::new(center_x + radius, center_y + radius)),
+            Self::Line { top_left, bottom_right, .. } => points_bounds(&[*top_left, *bottom_right]),
+            Self::LineFast { x0, y0, x1, y1, .. } => points_bounds(&[PointF64::new(*x0, *y0), PointF64::new(*x1, *y1)]),
+            Self::Rectangle { top_left, bottom_right, .. } => points_bounds(&[*top_left, *bottom_right]),
+            Self::RectangleFast { x, y, width, height, .. } => (PointF64::new(*x, *y), PointF64::new(x + width, y + height)),
+            Self::Text { top_left, size, text, .. } => (*top_left, PointF64::new(top_left.x + bitmap_font::text_width(text, *size), top_left.y + bitmap_font::text_height(text, *size))),
+            Self::Polygon { points, .. } | Self::PolygonFast { points, .. } | Self::Polyline { points, .. } | Self::PolylineFast { points, .. } => points_bounds(points),
+            Self::Path { start, segments, .. } | Self::PathFast { start, segments, .. } => points_bounds(&path_points(*start, segments)),
+            Self::Blend { shapes, .. } => {
+                let corners: Vec<PointF64> = shapes.iter().flat_map(|shape| { let (top_left, bottom_right) = shape.bounds(); vec![top_left, bottom_right] }).collect();
+                points_bounds(&corners)
+            },
+        }
+    }
+
+    // The same shape with every point passed through the function, and sizes such as radii and
+    // text heights multiplied by size_factor.
+    fn map_points<F>(&self, f: &F, size_factor: f64) -> Self
+        where F: Fn(PointF64) -> PointF64
+    {
+        let map_all = |points: &Vec<PointF64>| points.iter().map(|point| f(*point)).collect::<Vec<PointF64>>();
+        let map_xy = |x: f64, y: f64| { let point = f(PointF64::new(x, y)); (point.x, point.y) };
         match self {
-            Self::Circle { center: _, radius, color} => Self::circle(new_center, *radius, color.clone()),
-            _ => unimplemented!(),
+            Self::Circle { center, radius, color } => Self::circle(f(*center), radius * size_factor, *color),
+            Self::CircleFast { center_x, center_y, radius, color_index } => {
+                let (x, y) = map_xy(*center_x, *center_y);
+                Self::circle_fast(x, y, radius * size_factor, *color_index)
+            },
+            Self::Line { top_left, bottom_right, thickness, color } => Self::line(f(*top_left), f(*bottom_right), *thickness, *color),
+            Self::LineFast { x0, y0, x1, y1, thickness, color_index } => {
+                let ((x0, y0), (x1, y1)) = (map_xy(*x0, *y0), map_xy(*x1, *y1));
+                Self::line_fast(x0, y0, x1, y1, *thickness, *color_index)
+            },
+            Self::Rectangle { top_left, bottom_right, color } => Self::rectangle(f(*top_left), f(*bottom_right), *color),
+            Self::RectangleFast { x, y, width, height, color_index } => {
+                let ((x0, y0), (x1, y1)) = (map_xy(*x, *y), map_xy(x + width, y + height));
+                Self::rectangle_fast(x0, y0, x1 - x0, y1 - y0, *color_index)
+            },
+            Self::Text { top_left, size, color, text } => Self::text(f(*top_left), size * size_factor, *color, text),
+            Self::Polygon { points, color } => Self::polygon(map_all(points), *color),
+            Self::PolygonFast { points, color_index } => Self::polygon_fast(map_all(points), *color_index),
+            Self::Polyline { points, thickness, color } => Self::polyline(map_all(points), *thickness, *color),
+            Self::PolylineFast { points, thickness, color_index } => Self::polyline_fast(map_all(points), *thickness, *color_index),
+            Self::Path { start, segments, style, color } => Self::path(f(*start), segments.iter().map(|segment| segment.map_points(f)).collect(), *style, *color),
+            Self::PathFast { start, segments, style, color_index } => Self::path_fast(f(*start), segments.iter().map(|segment| segment.map_points(f)).collect(), *style, *color_index),
+            Self::Blend { mode, shapes } => Self::blend(*mode, shapes.iter().map(|shape| shape.map_points(f, size_factor)).collect()),
         }
     }
 
+    pub fn translate(&self, dx: f64, dy: f64) -> Self {
+        self.map_points(&|point: PointF64| PointF64::new(point.x + dx, point.y + dy), 1.0)
+    }
+
+    // Grow or shrink the shape around the given point. Line thicknesses stay the same.
+    pub fn scale_about(&self, center: PointF64, factor: f64) -> Self {
+        self.map_points(&|point: PointF64| PointF64::new(center.x + ((point.x - center.x) * factor), center.y + ((point.y - center.y) * factor)), factor)
+    }
+
+    // Move the shape so that the center of its bounds is at the new center.
+    pub fn with_center(&self, new_center: PointF64) -> Self {
+        let (top_left, bottom_right) = self.bounds();
+        self.translate(new_center.x - ((top_left.x + bottom_right.x) / 2.0), new_center.y - ((top_left.y + bottom_right.y) / 2.0))
+    }
+
     pub fn with_center_xy(&self, new_center_x: f64, new_center_y: f64) -> Self {
         self.with_center(PointF64::new(new_center_x, new_center_y))
     }
 
+    // Circles get the new radius. Other shapes are scaled around their center so that half the
+    // larger side of their bounds is the new radius.
     pub fn with_radius(&self, new_radius: f64) -> Self {
         match self {
             Self::Circle { center, radius: _, color} => Self::circle(center.clone(), new_radius, color.clone()),
-            _ => unimplemented!(),
+            Self::CircleFast { center_x, center_y, radius: _, color_index } => Self::circle_fast(*center_x, *center_y, new_radius, *color_index),
+            _ => {
+                let (top_left, bottom_right) = self.bounds();
+                let radius = (bottom_right.x - top_left.x).max(bottom_right.y - top_left.y) / 2.0;
+                assert!(radius > 0.0, "Can't set the radius of a shape with no size.");
+                let center = PointF64::new((top_left.x + bottom_right.x) / 2.0, (top_left.y + bottom_right.y) / 2.0);
+                self.scale_about(center, new_radius / radius)
+            },
+        }
+    }
+
+    // Set the line thickness of a line, polyline or stroked path.
+    pub fn with_thickness(&self, new_thickness: f64) -> Self {
+        match self {
+            Self::Line { top_left, bottom_right, thickness: _, color } => Self::line(*top_left, *bottom_right, new_thickness, *color),
+            Self::LineFast { x0, y0, x1, y1, thickness: _, color_index } => Self::line_fast(*x0, *y0, *x1, *y1, new_thickness, *color_index),
+            Self::Polyline { points, thickness: _, color } => Self::polyline(points.clone(), new_thickness, *color),
+            Self::PolylineFast { points, thickness: _, color_index } => Self::polyline_fast(points.clone(), new_thickness, *color_index),
+            Self::Path { start, segments, style: PathStyle::Stroke(_), color } => Self::path(*start, segments.clone(), PathStyle::Stroke(new_thickness), *color),
+            Self::PathFast { start, segments, style: PathStyle::Stroke(_), color_index } => Self::path_fast(*start, segments.clone(), PathStyle::Stroke(new_thickness), *color_index),
+            Self::Blend { mode, shapes } => Self::blend(*mode, shapes.iter().map(|shape| shape.with_thickness(new_thickness)).collect()),
+            _ => panic!("Only lines, polylines and stroked paths have a thickness."),
+        }
+    }
+
+    // Move the ends of a line.
+    pub fn with_endpoints(&self, start: PointF64, end: PointF64) -> Self {
+        match self {
+            Self::Line { thickness, color, .. } => Self::line(start, end, *thickness, *color),
+            Self::LineFast { thickness, color_index, .. } => Self::line_fast(start.x, start.y, end.x, end.y, *thickness, *color_index),
+            _ => panic!("Only lines have endpoints."),
+        }
+    }
+
+    // Move the corners of a rectangle.
+    pub fn with_corners(&self, top_left: PointF64, bottom_right: PointF64) -> Self {
+        match self {
+            Self::Rectangle { color, .. } => Self::rectangle(top_left, bottom_right, *color),
+            Self::RectangleFast { color_index, .. } => Self::rectangle_fast(top_left.x, top_left.y, bottom_right.x - top_left.x, bottom_right.y - top_left.y, *color_index),
+            _ => panic!("Only rectangles have corners."),
+        }
+    }
+
+    pub fn with_points(&self, new_points: Vec<PointF64>) -> Self {
+        match self {
+            Self::Polygon { color, .. } => Self::polygon(new_points, *color),
+            Self::PolygonFast { color_index, .. } => Self::polygon_fast(new_points, *color_index),
+            Self::Polyline { thickness, color, .. } => Self::polyline(new_points, *thickness, *color),
+            Self::PolylineFast { thickness, color_index, .. } => Self::polyline_fast(new_points, *thickness, *color_index),
+            _ => panic!("Only polygons and polylines have a list of points."),
+        }
+    }
+
+    // The color index of a *Fast shape. The other shapes have a Color1 and use with_color().
+    pub fn with_color_index(&self, new_color_index: usize) -> Self {
+        match self {
+            Self::CircleFast { center_x, center_y, radius, .. } => Self::circle_fast(*center_x, *center_y, *radius, new_color_index),
+            Self::LineFast { x0, y0, x1, y1, thickness, .. } => Self::line_fast(*x0, *y0, *x1, *y1, *thickness, new_color_index),
+            Self::RectangleFast { x, y, width, height, .. } => Self::rectangle_fast(*x, *y, *width, *height, new_color_index),
+            Self::PolygonFast { points, .. } => Self::polygon_fast(points.clone(), new_color_index),
+            Self::PolylineFast { points, thickness, .. } => Self::polyline_fast(points.clone(), *thickness, new_color_index),
+            Self::PathFast { start, segments, style, .. } => Self::path_fast(*start, segments.clone(), *style, new_color_index),
+            Self::Blend { mode, shapes } => Self::blend(*mode, shapes.iter().map(|shape| shape.with_color_index(new_color_index)).collect()),
+            _ => panic!("Only the *Fast shapes have a color index. Use with_color() instead."),
         }
     }
 
     pub fn with_color(&self, new_color: Color1) -> Self {
         match self {
             Self::Circle { center, radius, color: _ } => Self::circle(center.clone(), *radius, new_color),
-            Self::CircleFast { .. } => panic!("Not implemented for CircleFast since it doesn't have a Color1."),
+            Self::CircleFast { .. } => panic!("Not implemented for CircleFast since it doesn't have a Color1. Use with_color_index() instead."),
             Self::Line { top_left, bottom_right, thickness, color: _ } => Self::line(top_left.clone(), bottom_right.clone(), *thickness, new_color),
-            Self::LineFast { .. } => panic!("Not implemented for LineFast since it doesn't have a Color1."),
+            Self::LineFast { .. } => panic!("Not implemented for LineFast since it doesn't have a Color1. Use with_color_index() instead."),
             Self::Rectangle { top_left, bottom_right, color: _ } => Self::rectangle(*top_left, *bottom_right, new_color),
-            Self::RectangleFast { .. } => panic!("Not implemented for RectangleFast since it doesn't have a Color1."),
+            Self::RectangleFast { .. } => panic!("Not implemented for RectangleFast since it doesn't have a Color1. Use with_color_index() instead."),
+            Self::Text { top_left, size, color: _, text } => Self::text(*top_left, *size, new_color, text),
+            Self::Polygon { points, color: _ } => Self::polygon(points.clone(), new_color),
+            Self::PolygonFast { .. } => panic!("Not implemented for PolygonFast since it doesn't have a Color1. Use with_color_index() instead."),
+            Self::Polyline { points, thickness, color: _ } => Self::polyline(points.clone(), *thickness, new_color),
+            Self::PolylineFast { .. } => panic!("Not implemented for PolylineFast since it doesn't have a Color1. Use with_color_index() instead."),
+            Self::Path { start, segments, style, color: _ } => Self::path(*start, segments.clone(), *style, new_color),
+            Self::PathFast { .. } => panic!("Not implemented for PathFast since it doesn't have a Color1. Use with_color_index() instead."),
+            Self::Blend { mode, shapes } => Self::blend(*mode, shapes.iter().map(|shape| shape.with_color(new_color.clone())).collect()),
+        }
+    }
+
+    // Whether with_radius() can be used, which needs a circle or a shape with some size.
+    pub fn can_set_radius(&self) -> bool {
+        match self {
+            Self::Circle { .. } | Self::CircleFast { .. } => true,
+            _ => {
+                let (top_left, bottom_right) = self.bounds();
+                (bottom_right.x - top_left.x).max(bottom_right.y - top_left.y) > 0.0
+            },
+        }
+    }
+
+    // Whether with_thickness() can be used. For a blend group every shape in it needs a thickness.
+    pub fn has_thickness(&self) -> bool {
+        match self {
+            Self::Line { .. } | Self::LineFast { .. } | Self::Polyline { .. } | Self::PolylineFast { .. } => true,
+            Self::Path { style, .. } | Self::PathFast { style, .. } => matches!(style, PathStyle::Stroke(_)),
+            Self::Blend { shapes, .. } => shapes.iter().all(|shape| shape.has_thickness()),
+            _ => false,
+        }
+    }
+
+    // Whether with_endpoints() can be used.
+    pub fn has_endpoints(&self) -> bool {
+        matches!(self, Self::Line { .. } | Self::LineFast { .. })
+    }
+
+    // Whether with_corners() can be used.
+    pub fn has_corners(&self) -> bool {
+        matches!(self, Self::Rectangle { .. } | Self::RectangleFast { .. })
+    }
+
+    // Whether with_points() can be used.
+    pub fn has_points(&self) -> bool {
+        matches!(self, Self::Polygon { .. } | Self::PolygonFast { .. } | Self::Polyline { .. } | Self::PolylineFast { .. })
+    }
+
+    // Whether with_color_index() can be used. For a blend group every shape in it needs a color
+    // index.
+    pub fn has_color_index(&self) -> bool {
+        match self {
+            Self::CircleFast { .. } | Self::LineFast { .. } | Self::RectangleFast { .. } | Self::PolygonFast { .. } | Self::PolylineFast { .. } | Self::PathFast { .. } => true,
+            Self::Blend { shapes, .. } => shapes.iter().all(|shape| shape.has_color_index()),
+            _ => false,
+        }
+    }
+
+    // Whether with_color() can be used. For a blend group every shape in it needs a Color1.
+    pub fn has_color(&self) -> bool {
+        match self {
+            Self::Blend { shapes, .. } => shapes.iter().all(|shape| shape.has_color()),
+            _ => !self.has_color_index(),
+        }
+    }
+}
+
+impl PathSegment {
+    pub fn to(&self) -> PointF64 {
+        match self {
+            Self::LineTo { to } | Self::QuadraticTo { to, .. } | Self::CubicTo { to, .. } => *to,
+        }
+    }
+
+    fn map_points<F>(&self, f: &F) -> Self
+        where F: Fn(PointF64) -> PointF64
+    {
+        match self {
+            Self::LineTo { to } => Self::LineTo { to: f(*to) },
+            Self::QuadraticTo { control, to } => Self::QuadraticTo { control: f(*control), to: f(*to) },
+            Self::CubicTo { control_1, control_2, to } => Self::CubicTo { control_1: f(*control_1), control_2: f(*control_2), to: f(*to) },
         }
     }
+
+    // Whether the two segments are the same kind, so that one can be interpolated into the other.
+    fn matches(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn gradiant_one(&self, other: &Self, step_count: usize, step_index: usize) -> Self {
+        debug_assert!(step_index <= step_count);
+        self.interpolate(other, step_index as f64 / step_count as f64)
+    }
+
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
+        match (self, other) {
+            (Self::LineTo { to: from_to }, Self::LineTo { to: to_to }) => {
+                Self::LineTo { to: from_to.interpolate(to_to, t) }
+            },
+            (Self::QuadraticTo { control: from_control, to: from_to }, Self::QuadraticTo { control: to_control, to: to_to }) => {
+                Self::QuadraticTo {
+                    control: from_control.interpolate(to_control, t),
+                    to: from_to.interpolate(to_to, t),
+                }
+            },
+            (Self::CubicTo { control_1: from_control_1, control_2: from_control_2, to: from_to },
+                    Self::CubicTo { control_1: to_control_1, control_2: to_control_2, to: to_to }) => {
+                Self::CubicTo {
+                    control_1: from_control_1.interpolate(to_control_1, t),
+                    control_2: from_control_2.interpolate(to_control_2, t),
+                    to: from_to.interpolate(to_to, t),
+                }
+            },
+            _ => panic!("Can't morph between different kinds of path segments."),
+        }
+    }
+}
+
+// Approximate the path with straight segments, returning the start point followed by the end of
+// each segment. Curves are split into pieces about PATH_FLATTEN_LENGTH long based on the length of
+// their control polygons, which is never less than the length of the curve.
+pub fn path_points(start: PointF64, segments: &[PathSegment]) -> Vec<PointF64> {
+    let mut points = vec![start];
+    let mut from = start;
+    for segment in segments.iter() {
+        match segment {
+            PathSegment::LineTo { to } => points.push(*to),
+            PathSegment::QuadraticTo { control, to } => {
+                let step_count = path_flatten_step_count(&[from, *control, *to]);
+                for step_index in 1..=step_count {
+                    let t = step_index as f64 / step_count as f64;
+                    let u = 1.0 - t;
+                    let x = (u * u * from.x) + (2.0 * u * t * control.x) + (t * t * to.x);
+                    let y = (u * u * from.y) + (2.0 * u * t * control.y) + (t * t * to.y);
+                    points.push(PointF64::new(x, y));
+                }
+            },
+            PathSegment::CubicTo { control_1, control_2, to } => {
+                let step_count = path_flatten_step_count(&[from, *control_1, *control_2, *to]);
+                for step_index in 1..=step_count {
+                    let t = step_index as f64 / step_count as f64;
+                    let u = 1.0 - t;
+                    let x = (u * u * u * from.x) + (3.0 * u * u * t * control_1.x) + (3.0 * u * t * t * control_2.x) + (t * t * t * to.x);
+                    let y = (u * u * u * from.y) + (3.0 * u * u * t * control_1.y) + (3.0 * u * t * t * control_2.y) + (t * t * t * to.y);
+                    points.push(PointF64::new(x, y));
+                }
+            },
+        }
+        from = segment.to();
+    }
+    points
+}
+
+fn path_flatten_step_count(control_points: &[PointF64]) -> usize {
+    let length: f64 = control_points.windows(2)
+        .map(|pair| ((pair[1].x - pair[0].x).powi(2) + (pair[1].y - pair[0].y).powi(2)).sqrt())
+        .sum();
+    ((length / PATH_FLATTEN_LENGTH).ceil() as usize).max(1).min(PATH_FLATTEN_MAX_STEPS)
+}
+
+fn interpolate_points(from_points: &[PointF64], to_points: &[PointF64], t: f64) -> Vec<PointF64> {
+    debug_assert_eq!(from_points.len(), to_points.len());
+    from_points.iter().zip(to_points.iter())
+        .map(|(from_point, to_point)| from_point.interpolate(to_point, t))
+        .collect()
+}
+
+fn points_bounds(points: &[PointF64]) -> (PointF64, PointF64) {
+    let (x_min, x_max) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), point| (min.min(point.x), max.max(point.x)));
+    let (y_min, y_max) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), point| (min.min(point.y), max.max(point.y)));
+    (PointF64::new(x_min, y_min), PointF64::new(x_max, y_max))
+}
+
+// Colors indexes, text and blend modes can't be partway between two values, so they change from
+// one to the other halfway through the transition.
+fn switch_halfway<T: Clone>(from: &T, to: &T, t: f64) -> T {
+    if t < 0.5 { from.clone() } else { to.clone() }
+}
+
+fn paths_match(from_segments: &[PathSegment], from_style: PathStyle, to_segments: &[PathSegment], to_style: PathStyle) -> bool {
+    let styles_match = match (from_style, to_style) {
+        (PathStyle::Fill, PathStyle::Fill) | (PathStyle::Stroke(_), PathStyle::Stroke(_)) => true,
+        _ => false,
+    };
+    styles_match && from_segments.len() == to_segments.len()
+        && from_segments.iter().zip(to_segments.iter()).all(|(from_segment, to_segment)| from_segment.matches(to_segment))
+}
+
+fn interpolate_path(from_start: PointF64, from_segments: &[PathSegment], from_style: PathStyle, to_start: PointF64, to_segments: &[PathSegment], to_style: PathStyle, t: f64) -> (PointF64, Vec<PathSegment>, PathStyle) {
+    let start = from_start.interpolate(&to_start, t);
+    let segments = from_segments.iter().zip(to_segments.iter())
+        .map(|(from_segment, to_segment)| from_segment.interpolate(to_segment, t))
+        .collect();
+    let style = match (from_style, to_style) {
+        (PathStyle::Stroke(from_thickness), PathStyle::Stroke(to_thickness)) => PathStyle::Stroke(interpolate_f64(from_thickness, to_thickness, t).max(0.0)),
+        _ => PathStyle::Fill,
+    };
+    (start, segments, style)
+}
+
+// A shape reduced to points for morphing. See Shape::morph().
+enum Outline {
+    Closed(Vec<PointF64>),
+    // The points and the line thickness.
+    Open(Vec<PointF64>, f64),
+}
+
+enum Paint {
+    Color(Color1),
+    Index(usize),
+}
+
+impl Outline {
+    fn is_closed(&self) -> bool {
+        matches!(self, Outline::Closed(_))
+    }
+}
+
+impl Paint {
+    fn is_index(&self) -> bool {
+        matches!(self, Paint::Index(_))
+    }
+}
+
+fn morph_closed_points(from_points: &[PointF64], to_points: &[PointF64], t: f64) -> Vec<PointF64> {
+    let point_count = from_points.len().max(to_points.len()).max(MORPH_POINT_COUNT);
+    let from_points = resample_closed(from_points, point_count);
+    let to_points = align_closed(&from_points, resample_closed(to_points, point_count));
+    interpolate_points(&from_points, &to_points, t)
+}
+
+fn morph_open_points(from_points: &[PointF64], to_points: &[PointF64], t: f64) -> Vec<PointF64> {
+    let point_count = from_points.len().max(to_points.len()).max(MORPH_POINT_COUNT);
+    interpolate_points(&resample_open(from_points, point_count), &resample_open(to_points, point_count), t)
+}
+
+fn path_outline(start: PointF64, segments: &[PathSegment], style: PathStyle) -> Outline {
+    let points = path_points(start, segments);
+    match style {
+        PathStyle::Fill => Outline::Closed(points),
+        PathStyle::Stroke(thickness) => Outline::Open(points, thickness),
+    }
+}
+
+// Clockwise on the screen starting from the top, to match rectangle_points().
+fn circle_points(center: PointF64, radius: f64) -> Vec<PointF64> {
+    (0..MORPH_POINT_COUNT)
+        .map(|index| {
+            let angle = (index as f64 / MORPH_POINT_COUNT as f64 * std::f64::consts::PI * 2.0) - std::f64::consts::FRAC_PI_2;
+            PointF64::new(center.x + (radius * angle.cos()), center.y + (radius * angle.sin()))
+        })
+        .collect()
+}
+
+fn rectangle_points(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<PointF64> {
+    vec![PointF64::new(x0, y0), PointF64::new(x1, y0), PointF64::new(x1, y1), PointF64::new(x0, y1)]
+}
+
+// Points spaced evenly by distance along the line through the given points, including both ends.
+fn resample_open(points: &[PointF64], point_count: usize) -> Vec<PointF64> {
+    resample(points, point_count, false)
+}
+
+// Points spaced evenly around the outline of the polygon, starting at its first point.
+fn resample_closed(points: &[PointF64], point_count: usize) -> Vec<PointF64> {
+    resample(points, point_count, true)
+}
+
+fn resample(points: &[PointF64], point_count: usize, closed: bool) -> Vec<PointF64> {
+    assert!(!points.is_empty());
+    let mut path = points.to_vec();
+    if closed {
+        path.push(points[0]);
+    }
+    let distance = |a: &PointF64, b: &PointF64| ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+    let total_length: f64 = path.windows(2).map(|pair| distance(&pair[0], &pair[1])).sum();
+    if total_length == 0.0 {
+        return vec![points[0]; point_count];
+    }
+    // A closed outline doesn't repeat its first point at the end.
+    let step = total_length / if closed { point_count } else { point_count.max(2) - 1 } as f64;
+    let mut resampled = Vec::with_capacity(point_count);
+    let (mut segment_index, mut segment_start_length) = (0, 0.0);
+    for index in 0..point_count {
+        let target_length = (index as f64 * step).min(total_length);
+        // Move on to the segment containing the target length.
+        loop {
+            let segment_length = distance(&path[segment_index], &path[segment_index + 1]);
+            if segment_start_length + segment_length >= target_length || segment_index + 2 >= path.len() {
+                let t = if segment_length == 0.0 { 0.0 } else { (target_length - segment_start_length) / segment_length };
+                resampled.push(path[segment_index].interpolate(&path[segment_index + 1], t.min(1.0)));
+                break;
+            }
+            segment_start_length += segment_length;
+            segment_index += 1;
+        }
+    }
+    resampled
+}
+
+// Reorder the points of the second outline so that it winds the same way as the first one and
+// starts at the point closest to the first one's start, so the morph doesn't twist.
+fn align_closed(from_points: &[PointF64], mut to_points: Vec<PointF64>) -> Vec<PointF64> {
+    if signed_area(from_points).signum() != signed_area(&to_points).signum() {
+        to_points.reverse();
+    }
+    let distance_squared = |point: &PointF64| (point.x - from_points[0].x).powi(2) + (point.y - from_points[0].y).powi(2);
+    let closest_index = (0..to_points.len())
+        .min_by(|a, b| distance_squared(&to_points[*a]).partial_cmp(&distance_squared(&to_points[*b])).unwrap())
+        .unwrap();
+    to_points.rotate_left(closest_index);
+    to_points
+}
+
+fn signed_area(points: &[PointF64]) -> f64 {
+    let mut area = 0.0;
+    for index in 0..points.len() {
+        let (a, b) = (points[index], points[(index + 1) % points.len()]);
+        area += (a.x * b.y) - (b.x * a.y);
+    }
+    area / 2.0
 }
 
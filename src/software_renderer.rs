@@ -0,0 +1,348 @@
+// A pure-CPU equivalent of the OpenGL backend in renderer_3. It draws the same Frames into an
+// in-memory RGBA buffer instead of a Glutin/OpenGL window so that frames can be rendered headless.
+//
+// By default a pixel is either inside a shape or not, depending on its center, which keeps grid
+// output pixel-exact. With anti-aliasing turned on, each pixel is instead blended according to how
+// much of it the shape covers, so shapes land on sub-pixel positions and thin lines and tiny
+// circles fade out rather than breaking up or disappearing.
+
+use image::{RgbaImage, Rgba};
+
+use crate::*;
+use crate::render_backend::{draw_frame, FramePlayer, RenderBackend};
+
+// When anti-aliasing, circles and lines narrower than this are drawn at this size with their alpha
+// reduced in proportion, so their total brightness stays about right.
+const ANTI_ALIAS_MIN_RADIUS: f64 = 0.5;
+
+pub struct SoftwareRenderer {
+    width: usize,
+    height: usize,
+    anti_alias: bool,
+    blend_mode: BlendMode,
+    // Working buffer in the same 0.0..=1.0 range as Color1, one [r, g, b, a] per pixel in
+    // row-major order.
+    pixels: Vec<[f32; 4]>,
+}
+
+pub fn main() {
+    try_render_shapes();
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: f64, height: f64, back_color: Color1) -> Self {
+        let width = width.round() as usize;
+        let height = height.round() as usize;
+        Self {
+            width,
+            height,
+            anti_alias: false,
+            blend_mode: BlendMode::Normal,
+            pixels: vec![back_color.for_render; width * height],
+        }
+    }
+
+    pub fn render_frame_to_image(width: f64, height: f64, back_color: Color1, frame: &Frame, colors: &Vec<Color1>) -> RgbaImage {
+        Self::render_frame_to_image_anti_alias(width, height, back_color, frame, colors, false)
+    }
+
+    pub fn render_frame_to_image_anti_alias(width: f64, height: f64, back_color: Color1, frame: &Frame, colors: &Vec<Color1>, anti_alias: bool) -> RgbaImage {
+        let mut rend = Self::new(width, height, back_color);
+        rend.set_anti_alias(anti_alias);
+        draw_frame(&mut rend, frame, colors);
+        rend.to_image()
+    }
+
+    // Play the frames with the same additive semantics as renderer_3::Renderer and return one image
+    // per frame.
+    pub fn render_frames_to_images(width: f64, height: f64, back_color: Color1, frames: Vec<Frame>, additive: bool, colors: Vec<Color1>) -> Vec<RgbaImage> {
+        Self::render_frames_to_images_anti_alias(width, height, back_color, frames, additive, colors, false)
+    }
+
+    pub fn render_frames_to_images_anti_alias(width: f64, height: f64, back_color: Color1, frames: Vec<Frame>, additive: bool, colors: Vec<Color1>, anti_alias: bool) -> Vec<RgbaImage> {
+        let mut rend = Self::new(width, height, back_color);
+        rend.set_anti_alias(anti_alias);
+        let mut player = FramePlayer::new(back_color, frames, additive, colors);
+        let mut images = vec![];
+        player.play_all(&mut rend, |rend, _seconds_to_next| images.push(rend.to_image()));
+        images
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn anti_alias(&self) -> bool {
+        self.anti_alias
+    }
+
+    // This only affects shapes drawn from now on.
+    pub fn set_anti_alias(&mut self, anti_alias: bool) {
+        self.anti_alias = anti_alias;
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Color1 {
+        let [r, g, b, a] = self.pixels[x_y_to_cell_index_usize(self.width, x, y)];
+        Color1::from_rgba(r, g, b, a)
+    }
+
+    pub fn to_image(&self) -> RgbaImage {
+        let mut img = RgbaImage::new(self.width as u32, self.height as u32);
+        for (cell_index, pixel) in self.pixels.iter().enumerate() {
+            let (x, y) = cell_index_to_x_y_usize(self.width, cell_index);
+            img.put_pixel(x as u32, y as u32, color_f32_to_rgba(pixel));
+        }
+        img
+    }
+
+    pub fn save(&self, file_name: &str) {
+        self.to_image().save(file_name).unwrap();
+    }
+
+    // Without anti-aliasing a pixel is covered when its center is inside the circle. With it, the
+    // coverage ramps from 0 to 1 over the pixel-wide band straddling the edge.
+    fn fill_circle(&mut self, center_x: f64, center_y: f64, radius: f64, color: [f32; 4]) {
+        if self.anti_alias {
+            let (radius, color) = if radius < ANTI_ALIAS_MIN_RADIUS {
+                (ANTI_ALIAS_MIN_RADIUS, scale_alpha(color, (radius / ANTI_ALIAS_MIN_RADIUS).powi(2)))
+            } else {
+                (radius, color)
+            };
+            let reach = radius + 0.5;
+            self.fill_bounds(center_x - reach, center_y - reach, center_x + reach, center_y + reach, color, |x, y| {
+                let distance = ((x - center_x).powi(2) + (y - center_y).powi(2)).sqrt();
+                edge_coverage(radius - distance)
+            });
+        } else {
+            let radius_squared = radius * radius;
+            self.fill_bounds(center_x - radius, center_y - radius, center_x + radius, center_y + radius, color, |x, y| {
+                let (dx, dy) = (x - center_x, y - center_y);
+                if (dx * dx) + (dy * dy) <= radius_squared { 1.0 } else { 0.0 }
+            });
+        }
+    }
+
+    // As with graphics::line() the thickness is used as a radius, so the line extends that far on
+    // either side of the segment. The ends are square and don't extend past the endpoints.
+    fn fill_line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4]) {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length_squared = (dx * dx) + (dy * dy);
+        if length_squared == 0.0 {
+            return;
+        }
+        if self.anti_alias {
+            let (thickness, color) = if thickness < ANTI_ALIAS_MIN_RADIUS {
+                (ANTI_ALIAS_MIN_RADIUS, scale_alpha(color, thickness / ANTI_ALIAS_MIN_RADIUS))
+            } else {
+                (thickness, color)
+            };
+            let length = length_squared.sqrt();
+            let (unit_x, unit_y) = (dx / length, dy / length);
+            let reach = thickness + 0.5;
+            let (x_min, x_max) = (x0.min(x1) - reach, x0.max(x1) + reach);
+            let (y_min, y_max) = (y0.min(y1) - reach, y0.max(y1) + reach);
+            self.fill_bounds(x_min, y_min, x_max, y_max, color, |x, y| {
+                let along = ((x - x0) * unit_x) + ((y - y0) * unit_y);
+                let across = (((y - y0) * unit_x) - ((x - x0) * unit_y)).abs();
+                edge_coverage(thickness - across) * edge_coverage(along.min(length - along))
+            });
+        } else {
+            let (x_min, x_max) = (x0.min(x1) - thickness, x0.max(x1) + thickness);
+            let (y_min, y_max) = (y0.min(y1) - thickness, y0.max(y1) + thickness);
+            self.fill_bounds(x_min, y_min, x_max, y_max, color, |x, y| {
+                let t = (((x - x0) * dx) + ((y - y0) * dy)) / length_squared;
+                if t < 0.0 || t > 1.0 {
+                    return 0.0;
+                }
+                let (near_x, near_y) = (x0 + (t * dx), y0 + (t * dy));
+                let (off_x, off_y) = (x - near_x, y - near_y);
+                if (off_x * off_x) + (off_y * off_y) <= thickness * thickness { 1.0 } else { 0.0 }
+            });
+        }
+    }
+
+    // With anti-aliasing the coverage is the exact fraction of the pixel's area inside the
+    // rectangle.
+    fn fill_rectangle(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, color: [f32; 4]) {
+        let (x_min, x_max) = (x0.min(x1), x0.max(x1));
+        let (y_min, y_max) = (y0.min(y1), y0.max(y1));
+        if self.anti_alias {
+            self.fill_bounds(x_min, y_min, x_max, y_max, color, |x, y| {
+                let overlap_x = (x_max.min(x + 0.5) - x_min.max(x - 0.5)).max(0.0);
+                let overlap_y = (y_max.min(y + 0.5) - y_min.max(y - 0.5)).max(0.0);
+                overlap_x * overlap_y
+            });
+        } else {
+            self.fill_bounds(x_min, y_min, x_max, y_max, color, |x, y| {
+                if x >= x_min && x < x_max && y >= y_min && y < y_max { 1.0 } else { 0.0 }
+            });
+        }
+    }
+
+    // Pixels are inside the polygon by the even-odd rule. With anti-aliasing, pixels near an edge
+    // are covered according to their distance from it as with circles.
+    fn fill_polygon(&mut self, points: &[PointF64], color: [f32; 4]) {
+        if points.len() < 3 {
+            return;
+        }
+        let (x_min, x_max) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), point| (min.min(point.x), max.max(point.x)));
+        let (y_min, y_max) = points.iter().fold((f64::MAX, f64::MIN), |(min, max), point| (min.min(point.y), max.max(point.y)));
+        if self.anti_alias {
+            self.fill_bounds(x_min - 0.5, y_min - 0.5, x_max + 0.5, y_max + 0.5, color, |x, y| {
+                let edge_distance = (0..points.len())
+                    .map(|index| distance_to_segment(x, y, points[index], points[(index + 1) % points.len()]))
+                    .fold(f64::MAX, f64::min);
+                if point_in_polygon(x, y, points) { edge_coverage(edge_distance) } else { edge_coverage(-edge_distance) }
+            });
+        } else {
+            self.fill_bounds(x_min, y_min, x_max, y_max, color, |x, y| {
+                if point_in_polygon(x, y, points) { 1.0 } else { 0.0 }
+            });
+        }
+    }
+
+    // Blend the color into every pixel inside the bounding box according to the coverage (0.0 to
+    // 1.0) returned for the pixel's center.
+    fn fill_bounds<F>(&mut self, x_min: f64, y_min: f64, x_max: f64, y_max: f64, color: [f32; 4], coverage: F)
+        where F: Fn(f64, f64) -> f64
+    {
+        if let Some((px_min, py_min, px_max, py_max)) = self.clip_bounds(x_min, y_min, x_max, y_max) {
+            for py in py_min..py_max {
+                for px in px_min..px_max {
+                    let pixel_coverage = coverage(px as f64 + 0.5, py as f64 + 0.5);
+                    if pixel_coverage >= 1.0 {
+                        self.blend_pixel(px, py, color);
+                    } else if pixel_coverage > 0.0 {
+                        self.blend_pixel(px, py, scale_alpha(color, pixel_coverage));
+                    }
+                }
+            }
+        }
+    }
+
+    // Convert a bounding box in display coordinates to a range of pixel indexes within the buffer.
+    fn clip_bounds(&self, x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> Option<(usize, usize, usize, usize)> {
+        let px_min = x_min.floor().max(0.0);
+        let py_min = y_min.floor().max(0.0);
+        let px_max = x_max.ceil().min(self.width as f64);
+        let py_max = y_max.ceil().min(self.height as f64);
+        if px_min >= px_max || py_min >= py_max {
+            return None;
+        }
+        Some((px_min as usize, py_min as usize, px_max as usize, py_max as usize))
+    }
+
+    // Combine the color with the pixel according to the current blend mode. The alpha channel is
+    // always composited "over" so the pixel becomes more opaque whatever the mode.
+    #[inline]
+    fn blend_pixel(&mut self, x: usize, y: usize, color: [f32; 4]) {
+        let blend_mode = self.blend_mode;
+        let pixel = &mut self.pixels[x_y_to_cell_index_usize(self.width, x, y)];
+        let a = color[3];
+        for channel in 0..3 {
+            let (source, dest) = (color[channel], pixel[channel]);
+            pixel[channel] = match blend_mode {
+                BlendMode::Normal => (source * a) + (dest * (1.0 - a)),
+                BlendMode::Add => (dest + (source * a)).min(1.0),
+                BlendMode::Multiply => dest * ((source * a) + (1.0 - a)),
+                BlendMode::Screen => 1.0 - ((1.0 - dest) * (1.0 - (source * a))),
+                BlendMode::Max => dest + ((source - dest).max(0.0) * a),
+            };
+        }
+        pixel[3] = a + (pixel[3] * (1.0 - a));
+    }
+}
+
+impl RenderBackend for SoftwareRenderer {
+    fn clear(&mut self, color: [f32; 4]) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = color;
+        }
+    }
+
+    fn circle(&mut self, center_x: f64, center_y: f64, radius: f64, color: [f32; 4]) {
+        self.fill_circle(center_x, center_y, radius, color);
+    }
+
+    fn line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4]) {
+        self.fill_line(x0, y0, x1, y1, thickness, color);
+    }
+
+    fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]) {
+        self.fill_rectangle(x, y, x + width, y + height, color);
+    }
+
+    fn polygon(&mut self, points: &[PointF64], color: [f32; 4]) {
+        self.fill_polygon(points, color);
+    }
+
+    fn fade(&mut self, color: [f32; 4], amount: f32) {
+        let keep = 1.0 - amount;
+        for pixel in self.pixels.iter_mut() {
+            for channel in 0..4 {
+                pixel[channel] = (pixel[channel] * keep) + (color[channel] * amount);
+            }
+        }
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+}
+
+pub fn color_f32_to_rgba(color: &[f32; 4]) -> Rgba<u8> {
+    Rgba([color_channel_to_u8(color[0]), color_channel_to_u8(color[1]), color_channel_to_u8(color[2]), color_channel_to_u8(color[3])])
+}
+
+// Approximate coverage of a pixel by a shape whose edge is the given signed distance from the
+// pixel's center, positive meaning the center is inside.
+#[inline]
+fn edge_coverage(distance_inside: f64) -> f64 {
+    (distance_inside + 0.5).max(0.0).min(1.0)
+}
+
+#[inline]
+fn scale_alpha(color: [f32; 4], factor: f64) -> [f32; 4] {
+    [color[0], color[1], color[2], color[3] * factor as f32]
+}
+
+#[inline]
+fn color_channel_to_u8(value: f32) -> u8 {
+    (value.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+fn try_render_shapes() {
+    let width = 400.0;
+    let height = 300.0;
+    let colors = vec![Color1::black(), Color1::white(), Color1::from_rgba(0.0, 0.0, 1.0, 0.5)];
+    let shapes = vec![
+        Shape::rectangle(PointF64::new(20.0, 20.0), PointF64::new(180.0, 120.0), Color1::red()),
+        Shape::rectangle_fast(100.0, 60.0, 160.0, 100.0, 2),
+        Shape::circle_xy(300.0, 200.0, 60.0, Color1::green()),
+        Shape::circle_fast(300.0, 200.0, 20.0, 1),
+        Shape::line(PointF64::new(10.0, 290.0), PointF64::new(390.0, 10.0), 2.0, Color1::white()),
+        Shape::line_fast(10.0, 10.0, 390.0, 290.0, 1.0, 2),
+        Shape::text_xy(20.0, 250.0, 14.0, Color1::white(), "Software renderer 0123456789"),
+        Shape::polygon(vec![PointF64::new(200.0, 20.0), PointF64::new(260.0, 40.0), PointF64::new(230.0, 50.0), PointF64::new(250.0, 100.0), PointF64::new(190.0, 70.0)], Color1::from_rgba(1.0, 1.0, 0.0, 0.7)),
+        Shape::quadratic_fast(PointF64::new(20.0, 290.0), PointF64::new(200.0, 150.0), PointF64::new(380.0, 290.0), 1.0, 1),
+    ];
+    let mut frame = Frame::new(shapes, 0.1);
+    // Fern-sized points and hairlines that break up or vanish without anti-aliasing.
+    for i in 0..40 {
+        let x = 20.0 + (i as f64 * 9.3);
+        frame.shapes.push(Shape::circle_xy(x, 140.0 + (i as f64 * 0.25), 0.5, Color1::white()));
+        frame.shapes.push(Shape::line(PointF64::new(x, 160.0), PointF64::new(x + 7.0, 230.0), 0.2, Color1::white()));
+    }
+    let img = SoftwareRenderer::render_frame_to_image(width, height, Color1::gray(), &frame, &colors);
+    img.save("Test.png").unwrap();
+    let img = SoftwareRenderer::render_frame_to_image_anti_alias(width, height, Color1::gray(), &frame, &colors, true);
+    img.save("Test_anti_alias.png").unwrap();
+}
@@ -0,0 +1,253 @@
+// Write a frame or a list of shapes as an SVG document. Shapes keep their floating point
+// coordinates, so the result can be scaled to any size without the blockiness of a raster image.
+
+use std::fs;
+use itertools::Itertools;
+
+use crate::*;
+use crate::render_backend::{draw_shapes, RenderBackend};
+
+// How close two edges have to be to count as touching when merging rectangles.
+const MERGE_EPSILON: f64 = 0.000_001;
+
+pub struct SvgBackend {
+    width: f64,
+    height: f64,
+    blend_mode: BlendMode,
+    elements: Vec<String>,
+}
+
+pub fn main() {
+    try_save_svg();
+}
+
+impl SvgBackend {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            blend_mode: BlendMode::Normal,
+            elements: vec![],
+        }
+    }
+
+    pub fn element_count(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn to_svg(&self) -> String {
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n{}\n</svg>\n",
+                self.width, self.height, self.width, self.height, self.elements.iter().join("\n"))
+    }
+
+    pub fn save(&self, file_name: &str) {
+        fs::write(file_name, self.to_svg()).unwrap();
+    }
+
+    // Add an element that's drawn with the current blend mode.
+    fn push_element(&mut self, element: String) {
+        match svg_blend_mode(self.blend_mode) {
+            Some(mode) => self.elements.push(format!("{} style=\"mix-blend-mode:{}\"/>", element.trim_end_matches("/>"), mode)),
+            None => self.elements.push(element),
+        }
+    }
+}
+
+impl RenderBackend for SvgBackend {
+    // Anything drawn so far would be hidden by the background, so start over.
+    fn clear(&mut self, color: [f32; 4]) {
+        self.elements.clear();
+        self.elements.push(format!("<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" {}/>", self.width, self.height, svg_fill(&color)));
+    }
+
+    fn circle(&mut self, center_x: f64, center_y: f64, radius: f64, color: [f32; 4]) {
+        self.push_element(format!("<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>", center_x, center_y, radius, svg_fill(&color)));
+    }
+
+    // The thickness is a radius as in graphics::line(), so the stroke is twice as wide.
+    fn line(&mut self, x0: f64, y0: f64, x1: f64, y1: f64, thickness: f64, color: [f32; 4]) {
+        self.push_element(format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke-width=\"{}\" stroke-linecap=\"butt\" {}/>", x0, y0, x1, y1, thickness * 2.0, svg_stroke(&color)));
+    }
+
+    fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64, color: [f32; 4]) {
+        self.push_element(format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>", x, y, width, height, svg_fill(&color)));
+    }
+
+    fn polygon(&mut self, points: &[PointF64], color: [f32; 4]) {
+        self.push_element(format!("<polygon points=\"{}\" {}/>", svg_points(points), svg_fill(&color)));
+    }
+
+    fn polyline(&mut self, points: &[PointF64], thickness: f64, color: [f32; 4]) {
+        self.push_element(format!("<polyline points=\"{}\" fill=\"none\" stroke-width=\"{}\" stroke-linecap=\"butt\" {}/>", svg_points(points), thickness * 2.0, svg_stroke(&color)));
+    }
+
+    // Cover everything so far with a translucent background.
+    fn fade(&mut self, color: [f32; 4], amount: f32) {
+        let [r, g, b, _] = color;
+        self.elements.push(format!("<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" {}/>", self.width, self.height, svg_fill(&[r, g, b, amount])));
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    // Keep the curves as SVG path commands instead of flattening them.
+    fn path(&mut self, start: PointF64, segments: &[PathSegment], style: PathStyle, color: [f32; 4]) {
+        let mut data = format!("M {} {}", start.x, start.y);
+        for segment in segments.iter() {
+            data.push_str(&match segment {
+                PathSegment::LineTo { to } => format!(" L {} {}", to.x, to.y),
+                PathSegment::QuadraticTo { control, to } => format!(" Q {} {} {} {}", control.x, control.y, to.x, to.y),
+                PathSegment::CubicTo { control_1, control_2, to } => format!(" C {} {} {} {} {} {}", control_1.x, control_1.y, control_2.x, control_2.y, to.x, to.y),
+            });
+        }
+        let paint = match style {
+            PathStyle::Fill => {
+                data.push_str(" Z");
+                svg_fill(&color)
+            },
+            PathStyle::Stroke(thickness) => format!("fill=\"none\" stroke-width=\"{}\" stroke-linecap=\"butt\" {}", thickness * 2.0, svg_stroke(&color)),
+        };
+        self.push_element(format!("<path d=\"{}\" {}/>", data, paint));
+    }
+}
+
+pub fn frame_to_svg(width: f64, height: f64, back_color: Color1, frame: &Frame, colors: &Vec<Color1>, merge_rectangles: bool) -> String {
+    if frame.blend_mode == BlendMode::Normal {
+        shapes_to_svg(width, height, back_color, &frame.shapes, colors, merge_rectangles)
+    } else {
+        let shapes = vec![Shape::blend(frame.blend_mode, frame.shapes.clone())];
+        shapes_to_svg(width, height, back_color, &shapes, colors, merge_rectangles)
+    }
+}
+
+pub fn shapes_to_svg(width: f64, height: f64, back_color: Color1, shapes: &ShapeList, colors: &Vec<Color1>, merge_rectangles: bool) -> String {
+    let mut backend = SvgBackend::new(width, height);
+    backend.clear(back_color.for_render);
+    if merge_rectangles {
+        draw_shapes(&mut backend, &merge_rectangles_fast(shapes), colors);
+    } else {
+        draw_shapes(&mut backend, shapes, colors);
+    }
+    backend.to_svg()
+}
+
+pub fn save_svg(file_name: &str, width: f64, height: f64, back_color: Color1, frame: &Frame, colors: &Vec<Color1>, merge_rectangles: bool) {
+    fs::write(file_name, frame_to_svg(width, height, back_color, frame, colors, merge_rectangles)).unwrap();
+}
+
+// Combine neighboring RectangleFast shapes that have the same color index into larger rectangles
+// so that a frame made from a grid doesn't need one SVG element per cell. First, rectangles that
+// follow each other in the list and touch along a row are joined. Then within each unbroken run of
+// RectangleFast shapes, a rectangle is joined to one directly above it with the same left edge,
+// width and color. The second step changes the drawing order, so it assumes the rectangles within
+// a run don't overlap, which is true for the frames produced by Grid. The shapes inside a Blend
+// group are merged among themselves, which includes a frame with its own blend mode.
+pub fn merge_rectangles_fast(shapes: &ShapeList) -> ShapeList {
+    let mut merged = vec![];
+    let mut run: Vec<(f64, f64, f64, f64, usize)> = vec![];
+    for shape in shapes.iter() {
+        match shape {
+            Shape::RectangleFast { x, y, width, height, color_index } => {
+                if let Some(last) = run.last_mut() {
+                    if last.4 == *color_index
+                        && (last.1 - y).abs() < MERGE_EPSILON
+                        && (last.3 - height).abs() < MERGE_EPSILON
+                        && (last.0 + last.2 - x).abs() < MERGE_EPSILON {
+                        last.2 = (x + width) - last.0;
+                        continue;
+                    }
+                }
+                run.push((*x, *y, *width, *height, *color_index));
+            },
+            Shape::Blend { mode, shapes } => {
+                merge_run_vertically(&mut run, &mut merged);
+                merged.push(Shape::blend(*mode, merge_rectangles_fast(shapes)));
+            },
+            _ => {
+                merge_run_vertically(&mut run, &mut merged);
+                merged.push(shape.clone());
+            },
+        }
+    }
+    merge_run_vertically(&mut run, &mut merged);
+    merged
+}
+
+fn merge_run_vertically(run: &mut Vec<(f64, f64, f64, f64, usize)>, merged: &mut ShapeList) {
+    let mut output: Vec<(f64, f64, f64, f64, usize)> = vec![];
+    for rect in run.drain(..) {
+        let (x, y, width, height, color_index) = rect;
+        let above = output.iter_mut().rev().find(|other| {
+            other.4 == color_index
+                && (other.0 - x).abs() < MERGE_EPSILON
+                && (other.2 - width).abs() < MERGE_EPSILON
+                && (other.1 + other.3 - y).abs() < MERGE_EPSILON
+        });
+        match above {
+            Some(other) => other.3 = (y + height) - other.1,
+            None => output.push(rect),
+        }
+    }
+    for (x, y, width, height, color_index) in output.into_iter() {
+        merged.push(Shape::rectangle_fast(x, y, width, height, color_index));
+    }
+}
+
+fn svg_fill(color: &[f32; 4]) -> String {
+    format!("fill=\"{}\"{}", svg_rgb(color), svg_opacity("fill-opacity", color[3]))
+}
+
+fn svg_stroke(color: &[f32; 4]) -> String {
+    format!("stroke=\"{}\"{}", svg_rgb(color), svg_opacity("stroke-opacity", color[3]))
+}
+
+// The closest CSS blend mode, where None means the default of drawing normally.
+fn svg_blend_mode(mode: BlendMode) -> Option<&'static str> {
+    match mode {
+        BlendMode::Normal => None,
+        BlendMode::Add => Some("plus-lighter"),
+        BlendMode::Multiply => Some("multiply"),
+        BlendMode::Screen => Some("screen"),
+        BlendMode::Max => Some("lighten"),
+    }
+}
+
+fn svg_points(points: &[PointF64]) -> String {
+    points.iter().map(|point| format!("{},{}", point.x, point.y)).join(" ")
+}
+
+fn svg_rgb(color: &[f32; 4]) -> String {
+    let rgba = software_renderer::color_f32_to_rgba(color);
+    format!("#{:02x}{:02x}{:02x}", rgba[0], rgba[1], rgba[2])
+}
+
+// Leave out the attribute for opaque colors, which is the SVG default.
+fn svg_opacity(attribute: &str, alpha: f32) -> String {
+    if alpha >= 1.0 {
+        "".to_string()
+    } else {
+        format!(" {}=\"{}\"", attribute, alpha)
+    }
+}
+
+fn try_save_svg() {
+    let mut grid = grid::Grid::new(40, 20, 0);
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if (x / 10 + y / 5) % 2 == 0 {
+                grid.set_xy(x, y, 1);
+            }
+        }
+    }
+    let (width, height) = (400.0, 200.0);
+    let mut frame = grid.as_frame_color_index(width, height, 0.1);
+    frame.shapes.push(Shape::circle_xy(200.0, 100.0, 50.0, Color1::from_rgba(1.0, 0.0, 0.0, 0.5)));
+    frame.shapes.push(Shape::line(PointF64::new(0.0, 0.0), PointF64::new(400.0, 200.0), 1.0, Color1::green()));
+    let colors = vec![Color1::black(), Color1::white()];
+    save_svg("Test.svg", width, height, Color1::black(), &frame, &colors, true);
+}
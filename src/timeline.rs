@@ -0,0 +1,346 @@
+// Animations described in seconds rather than frames. A timeline holds any number of tracks that
+// play at the same time, each one moving a single shape through a list of keyframes. Tracks can
+// start after a delay and can loop or go back and forth. The timeline is then sampled at whatever
+// frame rate is wanted to produce the frames.
+//
+//   let dot = Track::new(&Shape::circle_xy(100.0, 100.0, 20.0, Color1::blue()))
+//       .delay(0.5)
+//       .ease(Easing::EaseInOut)
+//       .center_xy(500.0, 100.0).over(2.0)
+//       .hold(1.0)
+//       .color(Color1::red()).over(0.5)
+//       .ping_pong();
+//   let frames = Timeline::new().track(dot).frames(60.0);
+
+use crate::*;
+use crate::easing::Easing;
+
+#[derive(Clone, Debug)]
+pub struct Timeline {
+    tracks: Vec<Track>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Track {
+    start_shape: Shape,
+    keyframes: Vec<Keyframe>,
+    delay: f64,
+    repeat: Repeat,
+    show_before_start: bool,
+    hide_at_end: bool,
+    working_shape: Shape,
+    working_easing: Easing,
+}
+
+// The shape to reach by the end of a stretch of the given number of seconds, starting from the
+// previous keyframe's shape.
+#[derive(Clone, Debug)]
+pub struct Keyframe {
+    seconds: f64,
+    shape: Shape,
+    easing: Easing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Repeat {
+    // Play the keyframes once, then stay on the last shape unless the track hides at the end.
+    Once,
+    // Jump back to the start shape after the last keyframe and play again.
+    Loop,
+    // Play the keyframes forward and then backward, and again.
+    PingPong,
+}
+
+pub fn main() {
+    try_carpet_timeline();
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            tracks: vec![],
+        }
+    }
+
+    pub fn track(mut self, track: Track) -> Self {
+        self.add_track(track);
+        self
+    }
+
+    // Tracks are drawn in the order they're added, so later tracks appear on top.
+    pub fn add_track(&mut self, track: Track) {
+        self.tracks.push(track);
+    }
+
+    // The time when the last track that plays once is finished, or when every looping track has
+    // been through one cycle, whichever is later.
+    pub fn duration(&self) -> f64 {
+        self.tracks.iter().map(|track| track.delay + track.cycle_seconds()).fold(0.0, f64::max)
+    }
+
+    // Every shape visible at the given number of seconds from the start.
+    pub fn shapes_at(&self, seconds: f64) -> ShapeList {
+        self.tracks.iter().filter_map(|track| track.shape_at(seconds)).collect()
+    }
+
+    // Sample the whole timeline, from the start through to the end of its duration.
+    pub fn frames(&self, fps: f64) -> Vec<Frame> {
+        self.frames_for(fps, self.duration())
+    }
+
+    // Sample the given number of seconds of the timeline, which may be longer than its duration so
+    // that looping tracks keep going.
+    pub fn frames_for(&self, fps: f64, seconds: f64) -> Vec<Frame> {
+        assert!(fps > 0.0);
+        let frame_seconds = 1.0 / fps;
+        // Include a frame at the very end so that each track is seen in its final state.
+        let frame_count = (seconds * fps).round() as usize + 1;
+        (0..frame_count)
+            .map(|frame_index| Frame::new(self.shapes_at(frame_index as f64 * frame_seconds), frame_seconds))
+            .collect()
+    }
+}
+
+impl Track {
+    pub fn new(start_shape: &Shape) -> Self {
+        Self {
+            start_shape: start_shape.clone(),
+            keyframes: vec![],
+            delay: 0.0,
+            repeat: Repeat::Once,
+            show_before_start: false,
+            hide_at_end: false,
+            working_shape: start_shape.clone(),
+            working_easing: Easing::Linear,
+        }
+    }
+
+    // Seconds from the start of the timeline until the track starts. Until then the shape isn't
+    // drawn unless show_before_start() is used.
+    pub fn delay(mut self, seconds: f64) -> Self {
+        assert!(seconds >= 0.0);
+        self.delay = seconds;
+        self
+    }
+
+    pub fn looping(mut self) -> Self {
+        self.repeat = Repeat::Loop;
+        self
+    }
+
+    pub fn ping_pong(mut self) -> Self {
+        self.repeat = Repeat::PingPong;
+        self
+    }
+
+    // Draw the start shape during the delay.
+    pub fn show_before_start(mut self) -> Self {
+        self.show_before_start = true;
+        self
+    }
+
+    // Stop drawing the shape once the last keyframe is reached. Only applies to tracks that play
+    // once.
+    pub fn hide_at_end(mut self) -> Self {
+        self.hide_at_end = true;
+        self
+    }
+
+    // The easing curve for the following keyframes until it's changed again. The default is
+    // Easing::Linear.
+    pub fn ease(mut self, easing: Easing) -> Self {
+        self.working_easing = easing;
+        self
+    }
+
+    pub fn center(mut self, center: PointF64) -> Self {
+        self.working_shape = self.working_shape.with_center(center);
+        self
+    }
+
+    pub fn center_xy(mut self, center_x: f64, center_y: f64) -> Self {
+        self.working_shape = self.working_shape.with_center_xy(center_x, center_y);
+        self
+    }
+
+    pub fn radius(mut self, radius: f64) -> Self {
+        self.working_shape = self.working_shape.with_radius(radius);
+        self
+    }
+
+    pub fn color(mut self, color: Color1) -> Self {
+        self.working_shape = self.working_shape.with_color(color);
+        self
+    }
+
+    pub fn color_index(mut self, color_index: usize) -> Self {
+        self.working_shape = self.working_shape.with_color_index(color_index);
+        self
+    }
+
+    pub fn thickness(mut self, thickness: f64) -> Self {
+        self.working_shape = self.working_shape.with_thickness(thickness);
+        self
+    }
+
+    pub fn endpoints(mut self, start: PointF64, end: PointF64) -> Self {
+        self.working_shape = self.working_shape.with_endpoints(start, end);
+        self
+    }
+
+    pub fn corners(mut self, top_left: PointF64, bottom_right: PointF64) -> Self {
+        self.working_shape = self.working_shape.with_corners(top_left, bottom_right);
+        self
+    }
+
+    pub fn points(mut self, points: Vec<PointF64>) -> Self {
+        self.working_shape = self.working_shape.with_points(points);
+        self
+    }
+
+    // Morph into a different shape, which may be of another kind. See Shape::interpolate().
+    pub fn shape(mut self, shape: &Shape) -> Self {
+        self.working_shape = shape.clone();
+        self
+    }
+
+    // Move from the previous keyframe to the shape as it's been set up so far, taking the given
+    // number of seconds.
+    pub fn over(mut self, seconds: f64) -> Self {
+        assert!(seconds >= 0.0);
+        self.keyframes.push(Keyframe {
+            seconds,
+            shape: self.working_shape.clone(),
+            easing: self.working_easing,
+        });
+        self
+    }
+
+    // Like over(), but with the time given from the start of the track (not counting the delay)
+    // instead of from the previous keyframe.
+    pub fn at(self, seconds: f64) -> Self {
+        let previous_seconds = self.keyframes_seconds();
+        assert!(seconds >= previous_seconds, "Keyframe at {} seconds comes before the previous one at {} seconds.", seconds, previous_seconds);
+        self.over(seconds - previous_seconds)
+    }
+
+    // Stay still for the given number of seconds.
+    pub fn hold(mut self, seconds: f64) -> Self {
+        self.working_shape = self.last_shape().clone();
+        self.over(seconds)
+    }
+
+    // The shape at the last keyframe added so far, or the start shape if there are none yet. The
+    // next keyframe moves from this shape.
+    pub fn last_shape(&self) -> &Shape {
+        self.keyframes.last().map_or(&self.start_shape, |keyframe| &keyframe.shape)
+    }
+
+    // The shape for the next keyframe as it's been set up so far.
+    pub fn working_shape(&self) -> &Shape {
+        &self.working_shape
+    }
+
+    // How long the keyframes added so far take to play once.
+    pub fn keyframes_seconds(&self) -> f64 {
+        self.keyframes.iter().map(|keyframe| keyframe.seconds).sum()
+    }
+
+    // How long it takes to play the keyframes once, or forward and back for ping-pong.
+    fn cycle_seconds(&self) -> f64 {
+        match self.repeat {
+            Repeat::PingPong => self.keyframes_seconds() * 2.0,
+            _ => self.keyframes_seconds(),
+        }
+    }
+
+    // The shape at the given number of seconds from the start of the timeline, if it's visible.
+    pub fn shape_at(&self, seconds: f64) -> Option<Shape> {
+        let local_seconds = seconds - self.delay;
+        if local_seconds < 0.0 {
+            return if self.show_before_start { Some(self.start_shape.clone()) } else { None };
+        }
+        let total_seconds = self.keyframes_seconds();
+        if total_seconds == 0.0 {
+            return Some(self.keyframes.last().map_or(&self.start_shape, |keyframe| &keyframe.shape).clone());
+        }
+        let local_seconds = match self.repeat {
+            Repeat::Once => {
+                if local_seconds >= total_seconds {
+                    return if self.hide_at_end { None } else { Some(self.keyframes.last().unwrap().shape.clone()) };
+                }
+                local_seconds
+            },
+            Repeat::Loop => local_seconds % total_seconds,
+            Repeat::PingPong => {
+                let cycle_seconds = local_seconds % (total_seconds * 2.0);
+                if cycle_seconds > total_seconds { (total_seconds * 2.0) - cycle_seconds } else { cycle_seconds }
+            },
+        };
+
+        // Find the keyframe being moved toward.
+        let mut from_shape = &self.start_shape;
+        let mut keyframe_start_seconds = 0.0;
+        for keyframe in self.keyframes.iter() {
+            let keyframe_end_seconds = keyframe_start_seconds + keyframe.seconds;
+            if local_seconds < keyframe_end_seconds {
+                let t = keyframe.easing.apply((local_seconds - keyframe_start_seconds) / keyframe.seconds);
+                return Some(from_shape.interpolate(&keyframe.shape, t));
+            }
+            from_shape = &keyframe.shape;
+            keyframe_start_seconds = keyframe_end_seconds;
+        }
+        Some(from_shape.clone())
+    }
+}
+
+fn try_carpet_timeline() {
+    // Build a Sierpinski carpet by cutting out the middle square at each level. Each level's holes
+    // grow out of their centers one after another, then a dot bounces along the top while the last
+    // level fills in.
+    let size = 810.0;
+    let back_color = Color1::black();
+    let fps = 60.0;
+    let level_count = 4;
+    let grow_seconds = 0.6;
+
+    let mut timeline = Timeline::new()
+        .track(Track::new(&Shape::rectangle(PointF64::new(0.0, 0.0), PointF64::new(size, size), Color1::white())).show_before_start());
+
+    let mut level_start_seconds = 0.5;
+    let mut cell_size = size;
+    let mut cell_corners = vec![PointF64::new(0.0, 0.0)];
+    for level_index in 0..level_count {
+        let hole_size = cell_size / 3.0;
+        let stagger_seconds = 1.0 / cell_corners.len() as f64;
+        for (cell_index, corner) in cell_corners.iter().enumerate() {
+            let center = PointF64::new(corner.x + (cell_size / 2.0), corner.y + (cell_size / 2.0));
+            let hole_top_left = PointF64::new(center.x - (hole_size / 2.0), center.y - (hole_size / 2.0));
+            let hole_bottom_right = PointF64::new(center.x + (hole_size / 2.0), center.y + (hole_size / 2.0));
+            let color = Color1::from_space(ColorSpace::Oklab, [0.3 + (0.15 * level_index as f32), 0.1, -0.1], 1.0);
+            timeline.add_track(Track::new(&Shape::rectangle(center, center, color))
+                .delay(level_start_seconds + (cell_index as f64 * stagger_seconds))
+                .ease(Easing::CubicOut)
+                .corners(hole_top_left, hole_bottom_right).over(grow_seconds));
+        }
+        level_start_seconds += 1.0 + grow_seconds + 0.5;
+
+        // Each cell other than the middle one becomes a cell of the next level.
+        cell_corners = cell_corners.iter()
+            .flat_map(|corner| {
+                let corner = *corner;
+                (0..9).filter(|index| *index != 4).map(move |index| PointF64::new(corner.x + ((index % 3) as f64 * hole_size), corner.y + ((index / 3) as f64 * hole_size)))
+            })
+            .collect();
+        cell_size = hole_size;
+    }
+
+    timeline.add_track(Track::new(&Shape::circle_xy(20.0, 20.0, 12.0, Color1::red()))
+        .delay(level_start_seconds - 2.0)
+        .ease(Easing::BounceOut)
+        .center_xy(size - 20.0, 20.0).over(2.0)
+        .ping_pong());
+
+    let frames = timeline.frames_for(fps, timeline.duration() + 2.0);
+    renderer_3::Renderer::display("Carpet Timeline", size, size, back_color, frames);
+}
@@ -1,4 +1,6 @@
 use crate::*;
+use crate::output::Output;
+use crate::render_backend::FramePlayer;
 use rand::prelude::*;
 
 pub fn main() {
@@ -8,12 +10,6 @@ pub fn main() {
 fn first() {
     let additive = false;
     let width = 1000.0;
-    let height = 1000.0;
-    let back_color = Color1::black();
-    let anchor_radius = 5.0;
-    let anchor_color = Color1::blue();
-    let point_radius = 0.5;
-    let point_color = Color1::white();
     // let anchor_count = 3;
     // let point_count: usize = 1000;
     // let total_seconds = 10.0;
@@ -21,10 +17,6 @@ fn first() {
     let total_seconds = 10.0;
     // let batch_size = 2500;
     let batch_size = 1_000;
-    let frame_count = point_count / batch_size;
-    let frame_seconds = total_seconds / frame_count as f64;
-
-    let mut rng = thread_rng();
 
     let mut anchors: Vec<(f64, f64)> = vec![];
     // for _ in 0..anchor_count {
@@ -35,7 +27,25 @@ fn first() {
     anchors.push((980.0, 980.0));
     //bg!(&anchors);
 
-    let points = gen_points(&mut rng, &anchors, point_count);
+    chaos_game(&mut thread_rng(), width, anchors, 0.5, point_count, batch_size, total_seconds, additive, &Output::Window).unwrap();
+}
+
+// The chaos game: starting from the middle of the anchors, repeatedly move the given fraction of
+// the way toward an anchor chosen at random and plot a point. With three anchors and half the
+// distance this draws a Sierpinski triangle.
+pub fn chaos_game<R: Rng>(rng: &mut R, width: f64, anchors: Vec<(f64, f64)>, distance_to_anchor: f64, point_count: usize, batch_size: usize, total_seconds: f64, additive: bool, output: &Output) -> DrawResult<()> {
+    let height = width;
+    let back_color = Color1::black();
+    let anchor_radius = 5.0;
+    let anchor_color = Color1::blue();
+    let point_radius = 0.5;
+    // Faint points drawn with additive blending, so the brightness shows how many points landed
+    // in each spot.
+    let point_color = Color1::from_rgba(1.0, 1.0, 1.0, 0.3);
+    let frame_count = point_count / batch_size;
+    let frame_seconds = total_seconds / frame_count as f64;
+
+    let points = gen_points(rng, &anchors, distance_to_anchor, point_count);
 
     let mut frames = vec![];
     for frame_index in 0..frame_count {
@@ -51,13 +61,27 @@ fn first() {
             let (x, y) = points[point_index];
             shapes.push(Shape::circle_xy(x, y, point_radius, point_color.clone()));
         }
-        frames.push(Frame::new(shapes, frame_seconds));
+        let mut frame = Frame::new(shapes, frame_seconds);
+        frame.set_blend_mode(BlendMode::Add);
+        frames.push(frame);
     }
-    crate::renderer_3::Renderer::display_additive("Fractal", width, height, back_color, frames, additive);
+    output.try_display_player("Fractal", width, height, back_color, FramePlayer::new(back_color, frames, additive, vec![]))
+}
+
+// The corners of a regular polygon with the given number of sides, pointing up and inset from the
+// edges of a square of the given size.
+pub fn polygon_anchors(count: usize, size: f64, margin: f64) -> Vec<(f64, f64)> {
+    let radius = (size / 2.0) - margin;
+    let center = size / 2.0;
+    (0..count)
+        .map(|index| {
+            let angle = (-std::f64::consts::FRAC_PI_2) + (index as f64 * std::f64::consts::PI * 2.0 / count as f64);
+            (center + (radius * angle.cos()), center + (radius * angle.sin()))
+        })
+        .collect()
 }
 
-fn gen_points(rng: &mut ThreadRng, anchors: &Vec<(f64, f64)>, point_count: usize) -> Vec<(f64, f64)> {
-    let distance_to_anchor = 0.5;
+fn gen_points<R: Rng>(rng: &mut R, anchors: &Vec<(f64, f64)>, distance_to_anchor: f64, point_count: usize) -> Vec<(f64, f64)> {
     let throwaway_count = 10;
     let anchor_count = anchors.len();
     let mut x_min = f64::MAX;
@@ -1,8 +1,11 @@
 use crate::*;
 use crate::animator::*;
+use crate::easing::Easing;
 
 pub fn main() {
     first();
+    // easings();
+    // shapes();
 }
 
 fn first() {
@@ -41,7 +44,7 @@ fn first() {
     animator.show("Transitions", width, height, back_color);
     */
 
-    // Moving circle with changing colors.
+    // Moving circle with changing colors, leaving a trail.
     let trans_frames = 60;
     let frame_seconds = 1.0 / trans_frames as f64;
     let path = Path::new(&Shape::circle_xy(25.0, 25., 25.0, Color1::blue()))
@@ -51,5 +54,74 @@ fn first() {
     let mut animator = Animator::new(frame_seconds);
     animator.add_path(0, &path);
     //bg!(&animator);
-    animator.show("Transitions", width, height, back_color);
-}
\ No newline at end of file
+    animator.show_persistent("Transitions", width, height, back_color, 0.95);
+}
+
+// One circle per easing curve racing across the window and back, with a linear one at the top to
+// compare against.
+#[allow(dead_code)]
+fn easings() {
+    let width = 1000.0;
+    let height = 1000.0;
+    let back_color = Color1::white();
+    let easings = [Easing::Linear, Easing::EaseInOut, Easing::CubicIn, Easing::CubicOut, Easing::ElasticOut,
+        Easing::BounceOut, Easing::css_ease(), Easing::CubicBezier(0.7, -0.4, 0.3, 1.4)];
+    let trans_frames = 90;
+    let frame_seconds = 1.0 / 60.0;
+    let row_height = height / easings.len() as f64;
+    let radius = row_height / 4.0;
+    let mut animator = Animator::new(frame_seconds);
+    for (row_index, easing) in easings.iter().enumerate() {
+        let y = (row_index as f64 + 0.5) * row_height;
+        let path = Path::new(&Shape::circle_xy(radius * 2.0, y, radius, Color1::blue()))
+            .ease(*easing)
+            .center_xy(width - (radius * 2.0), y).color(Color1::red()).trans(trans_frames)
+            .center_xy(radius * 2.0, y).color(Color1::blue()).trans(trans_frames);
+        animator.add_path(0, &path);
+    }
+    animator.show("Easing", width, height, back_color);
+}
+
+// A line, a rectangle, palette-indexed shapes, and a circle that morphs into a square and then a
+// star.
+#[allow(dead_code)]
+fn shapes() {
+    let width = 1000.0;
+    let height = 1000.0;
+    let back_color = Color1::white();
+    let trans_frames = 60;
+    let frame_seconds = 1.0 / 60.0;
+    let mut animator = Animator::new(frame_seconds);
+
+    let line = Path::new(&Shape::line(PointF64::new(50.0, 50.0), PointF64::new(450.0, 50.0), 2.0, Color1::black()))
+        .ease(Easing::EaseInOut)
+        .endpoints(PointF64::new(50.0, 450.0), PointF64::new(450.0, 50.0)).thickness(10.0).color(Color1::red()).trans(trans_frames)
+        .endpoints(PointF64::new(50.0, 50.0), PointF64::new(450.0, 50.0)).thickness(2.0).color(Color1::black()).trans(trans_frames);
+    animator.add_path(0, &line);
+
+    let rectangle = Path::new(&Shape::rectangle(PointF64::new(550.0, 50.0), PointF64::new(650.0, 150.0), Color1::blue()))
+        .ease(Easing::BounceOut)
+        .corners(PointF64::new(550.0, 50.0), PointF64::new(950.0, 450.0)).color(Color1::green()).trans(trans_frames)
+        .corners(PointF64::new(850.0, 350.0), PointF64::new(950.0, 450.0)).color(Color1::blue()).trans(trans_frames);
+    animator.add_path(0, &rectangle);
+
+    // Shapes that use the renderer's color list. The index changes halfway through each transition.
+    let dot = Path::new(&Shape::circle_fast(100.0, 600.0, 20.0, 1))
+        .center_xy(400.0, 900.0).radius(50.0).color_index(2).trans(trans_frames)
+        .center_xy(100.0, 600.0).radius(20.0).color_index(1).trans(trans_frames);
+    animator.add_path(0, &dot);
+
+    let star_points = (0..10).map(|index| {
+        let angle = (index as f64 * std::f64::consts::PI / 5.0) - std::f64::consts::FRAC_PI_2;
+        let radius = if index % 2 == 0 { 200.0 } else { 80.0 };
+        PointF64::new(750.0 + (radius * angle.cos()), 750.0 + (radius * angle.sin()))
+    }).collect();
+    let morph = Path::new(&Shape::circle_xy(750.0, 750.0, 150.0, Color1::red()))
+        .ease(Easing::CubicInOut)
+        .shape(&Shape::rectangle(PointF64::new(600.0, 600.0), PointF64::new(900.0, 900.0), Color1::green())).trans(trans_frames)
+        .shape(&Shape::polygon(star_points, Color1::blue())).trans(trans_frames);
+    animator.add_path(0, &morph);
+
+    let colors = vec![Color1::white(), Color1::black(), Color1::red()];
+    animator.show_with_colors("Shapes", width, height, back_color, colors);
+}
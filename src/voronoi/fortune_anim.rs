@@ -4,17 +4,34 @@ use rand::Rng;
 use decorum::Finite;
 use std::collections::BTreeMap;
 
-pub const COLOR_BACKGROUND: usize      = 0;
-pub const COLOR_DIRECTRIX: usize       = 1;
-pub const COLOR_PARABOLA: usize        = 2;
-pub const COLOR_FOCUS_PENDING: usize   = 3;
-pub const COLOR_FOCUS_ACTIVE: usize    = 4;
-pub const COLOR_FOCUS_CONTAINED: usize = 5;
+use crate::Color1;
+use crate::palette::Palette;
 
 pub const FOCUS_RADIUS: f64 = 3.0;
 
 pub const DIRECTRIX_THICKNESS: f64 = 1.0;
 
+pub const PARABOLA_THICKNESS: f64 = 0.5;
+
+pub const SLOT_BACKGROUND: &str = "background";
+pub const SLOT_DIRECTRIX: &str = "directrix";
+pub const SLOT_PARABOLA: &str = "parabola";
+pub const SLOT_FOCUS_PENDING: &str = "focus_pending";
+pub const SLOT_FOCUS_ACTIVE: &str = "focus_active";
+pub const SLOT_FOCUS_CONTAINED: &str = "focus_contained";
+
+// The colors for the parts of the animation.
+pub fn palette() -> Palette {
+    Palette::with_reserved(&[
+        (SLOT_BACKGROUND, Color1::white()),
+        (SLOT_DIRECTRIX, Color1::black()),
+        (SLOT_PARABOLA, Color1::black()),
+        (SLOT_FOCUS_PENDING, Color1::light_gray()),
+        (SLOT_FOCUS_ACTIVE, Color1::blue()),
+        (SLOT_FOCUS_CONTAINED, Color1::red()),
+    ])
+}
+
 pub fn main() {
     // try_animate();
 }
@@ -43,18 +60,17 @@ enum FortuneEvent {
 }
 
 impl FortuneAnim {
-    pub fn new(width: usize, height: usize, point_count: usize) -> Self {
+    pub fn new<R: Rng>(rng: &mut R, width: usize, height: usize, point_count: usize) -> Self {
         let mut fortune = Self {
             width,
             height,
             points: vec![],
         };
-        fortune.add_points(point_count);
+        fortune.add_points(rng, point_count);
         fortune
     }
 
-    fn add_points(&mut self, point_count: usize) {
-        let mut rng = rand::thread_rng();
+    fn add_points<R: Rng>(&mut self, rng: &mut R, point_count: usize) {
         let (width, height) = (self.width as f64, self.height as f64);
         for _ in 0..point_count {
             let x= rng.gen::<f64>() * width;
@@ -134,7 +150,7 @@ impl FortuneEvent {
 #[allow(dead_code)]
 fn try_animate() {
     let (width, height, point_count, anim_seconds, frame_count) = (800, 400, 12, 30, 60);
-    let mut fortune = FortuneAnim::new(width, height, point_count);
+    let mut fortune = FortuneAnim::new(&mut rand::thread_rng(), width, height, point_count);
     fortune.animate(anim_seconds, frame_count);
 }
 
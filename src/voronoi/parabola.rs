@@ -1,8 +1,12 @@
 use std::collections::BTreeMap;
 use decorum::Finite;
 use crate::*;
-use crate::voronoi::fortune_anim::{COLOR_FOCUS_ACTIVE, COLOR_FOCUS_CONTAINED, COLOR_FOCUS_PENDING, FOCUS_RADIUS, COLOR_PARABOLA, DIRECTRIX_THICKNESS, COLOR_DIRECTRIX};
+use crate::voronoi::fortune_anim;
+use crate::voronoi::fortune_anim::{FOCUS_RADIUS, DIRECTRIX_THICKNESS, PARABOLA_THICKNESS, SLOT_DIRECTRIX, SLOT_PARABOLA, SLOT_FOCUS_PENDING, SLOT_FOCUS_ACTIVE, SLOT_FOCUS_CONTAINED};
+use crate::palette::Palette;
 use crate::grid::Grid;
+use crate::output::Output;
+use crate::render_backend::FramePlayer;
 use crate::renderer_3::Renderer;
 use rand::{thread_rng, Rng};
 use num::traits::Pow;
@@ -52,7 +56,7 @@ impl ParabolaList {
         }
     }
 
-    pub fn add_to_shapes(&mut self, shapes: &mut Vec<Shape>, width: usize, height: usize, directrix_y: f64) {
+    pub fn add_to_shapes(&mut self, shapes: &mut Vec<Shape>, width: usize, height: usize, directrix_y: f64, palette: &Palette) {
         // Update the parabolas' states.
         self.update_parabolas(directrix_y);
         let ref_parabolas = self.parabolas.clone();
@@ -72,7 +76,7 @@ impl ParabolaList {
             }
         }
         for parabola in self.parabolas.values() {
-            parabola.add_to_shapes(shapes, width, height);
+            parabola.add_to_shapes(shapes, width, height, palette);
         }
     }
 
@@ -198,79 +202,29 @@ impl Parabola {
         contains
     }
 
-    pub fn add_to_shapes(&self, shapes: &mut Vec<Shape>, width: usize, height: usize) {
+    pub fn add_to_shapes(&self, shapes: &mut Vec<Shape>, width: usize, _height: usize, palette: &Palette) {
         // Add the focus circle.
         let color_index = match self.state {
-            ParabolaState::Active => COLOR_FOCUS_ACTIVE,
-            ParabolaState::Contained => COLOR_FOCUS_CONTAINED,
-            ParabolaState::Pending => COLOR_FOCUS_PENDING,
+            ParabolaState::Active => palette.index(SLOT_FOCUS_ACTIVE),
+            ParabolaState::Contained => palette.index(SLOT_FOCUS_CONTAINED),
+            ParabolaState::Pending => palette.index(SLOT_FOCUS_PENDING),
         };
         shapes.push(Shape::circle_fast(self.focus_x, self.focus_y, FOCUS_RADIUS, color_index));
 
         match self.state {
             ParabolaState::Active => {
-                // Draw the right half in the old way.
-                for x in self.focus_x.round() as usize..width {
-                    let x = x as f64;
-                    // (x-h)^2 = 4p(y-k)
-                    // y = (x^2 - 2xh + h^2 + 4pk) / 4p
-                    let num = (x * x) + (-2.0 * x * self.h) + (self.h * self.h) + (4.0 * self.p * self.k);
-                    let denom = 4.0 * self.p;
-                    let y = (num / denom).round();
-                    if y >= 0.0 && y < height as f64 {
-                        shapes.push(Shape::rectangle_fast(x, y, 1.0, 1.0, COLOR_PARABOLA));
-                    }
-                }
-                // Draw the left half using http://members.chello.at/easyfilter/bresenham.pdf, page 10.
-                // Note that the y axis is reversed so this is really an upward slope.
-                let (mut x, mut y) = {
-                    // Try x = 0 and see if y is on the screen.
-                    let x = 0.0;
-                    let y = self.y(x);
-                    if y > 0.0 {
-                        (x as usize, y.round() as usize)
-                    } else {
-                        // Instead start with y = 0 and find x.
-                        let y = 0.0;
-                        let x = self.x_coords(y, false)[0];
-                        (x.round() as usize, y as usize)
-                    }
-                };
-                let x_stop = self.focus_x.round() as usize;
-                while x < x_stop {
-                    //rintln!("x = {}, y = {}", x, y);
-                    shapes.push(Shape::rectangle_fast(x as f64, y as f64, 1.0, 1.0, COLOR_PARABOLA));
-                    let err_xy = self.error(x + 1, y + 1).abs();
-                    let err_x = self.error(x, y + 1).abs();
-                    let err_y = self.error(x + 1, y).abs();
-                    //rintln!("\terr_xy = {}, err_x = {}, err_y = {}, err_x + err_xy = {}, err_y + err_xy = {}", err_xy, err_x, err_y, err_x + err_xy, err_y + err_xy);
-                    /*
-                    let mut inc = false;
-                    if err_x + err_xy > 0.0 {
-                        x += 1;
-                        inc = true;
-                    }
-                    if err_y + err_xy > 0.0 {
-                        y += 1;
-                        inc = true;
-                    }
-                    if !inc {
-                        // panic!();
-                        break;
-                    }*/
-                    if err_xy < err_x {
-                        if err_xy < err_y {
-                            x += 1;
-                            y += 1;
-                        } else {
-                            x += 1;
-                        }
-                    } else {
-                        if err_x < err_y {
-                            y += 1;
-                        } else {
-                            x += 1;
-                        }
+                // The parabola is a quadratic Bézier curve, so draw the part that's on screen as one.
+                // It opens upward and is only visible where it's below y = 0.
+                if self.p != 0.0 && self.y_max > 0.0 {
+                    let x_coords = self.x_coords(0.0, false);
+                    let x_min = x_coords[0].max(0.0);
+                    let x_max = x_coords[x_coords.len() - 1].min(width as f64);
+                    if x_min < x_max {
+                        // The control point is where the tangents at the two ends meet.
+                        let slope_min = (x_min - self.h) / (2.0 * self.p);
+                        let control_x = (x_min + x_max) / 2.0;
+                        let control_y = self.y(x_min) + (slope_min * (control_x - x_min));
+                        shapes.push(Shape::quadratic_fast(PointF64::new(x_min, self.y(x_min)), PointF64::new(control_x, control_y), PointF64::new(x_max, self.y(x_max)), PARABOLA_THICKNESS, palette.index(SLOT_PARABOLA)));
                     }
                 }
 
@@ -279,10 +233,10 @@ impl Parabola {
                 /*
                 let y = self.focus_y;
                 let x_coords = self.x_coords(directrix_y, y, false);
-                shapes.push(Shape::line_fast(x_coords[0], y, x_coords[1], y, 1.0, COLOR_FOCUS_ACTIVE));
+                shapes.push(Shape::line_fast(x_coords[0], y, x_coords[1], y, 1.0, palette.index(SLOT_FOCUS_ACTIVE)));
                 let y = self.focus_y - 30.0;
                 let x_coords = self.x_coords(directrix_y, y, false);
-                shapes.push(Shape::line_fast(x_coords[0], y, x_coords[1], y, 1.0, COLOR_FOCUS_ACTIVE));
+                shapes.push(Shape::line_fast(x_coords[0], y, x_coords[1], y, 1.0, palette.index(SLOT_FOCUS_ACTIVE)));
 
                  */
             },
@@ -323,10 +277,15 @@ fn try_draw_parabola() {
 
 #[allow(dead_code)]
 fn try_draw_parabolas() {
-    let mut rng = thread_rng();
     // let (width, height, parabola_count, anim_seconds, frame_count) = (800, 800, 10, 1, 1);
     // let (width, height, parabola_count, anim_seconds, frame_count) = (1_000, 1_000, 10, 20, 20);
     let (width, height, parabola_count, anim_seconds, frame_count) = (1_600, 800, 100, 30, 400);
+    draw_parabolas(&mut thread_rng(), width, height, parabola_count, anim_seconds, frame_count, &Output::Window).unwrap();
+}
+
+// Sweep a directrix down over randomly placed foci, drawing the parabolas that form the beach line
+// of Fortune's algorithm for Voronoi diagrams.
+pub fn draw_parabolas<R: Rng>(rng: &mut R, width: usize, height: usize, parabola_count: usize, anim_seconds: usize, frame_count: usize, output: &Output) -> DrawResult<()> {
     let (display_width, display_height) = (width as f64, height as f64);
     let frame_seconds = anim_seconds as f64 / frame_count as f64;
 
@@ -335,6 +294,9 @@ fn try_draw_parabolas() {
         parabolas.add_parabola(rng.gen::<f64>() * width as f64, rng.gen::<f64>() * height as f64);
     }
 
+    let palette = fortune_anim::palette();
+    let directrix_color_index = palette.index(SLOT_DIRECTRIX);
+
     let mut frames = vec![];
 
     // let directrix_y = height as f64 * 0.75;
@@ -345,14 +307,13 @@ fn try_draw_parabolas() {
         let directrix_y = (height as f64 / frame_count as f64) * (frame_index + 1) as f64;
         let mut shapes = vec![];
         // Add the directrix as a line shape.
-        shapes.push(Shape::line_fast(0.0, directrix_y, display_width, directrix_y, DIRECTRIX_THICKNESS, COLOR_DIRECTRIX));
-        parabolas.add_to_shapes(&mut shapes, width, height, directrix_y);
+        shapes.push(Shape::line_fast(0.0, directrix_y, display_width, directrix_y, DIRECTRIX_THICKNESS, directrix_color_index));
+        parabolas.add_to_shapes(&mut shapes, width, height, directrix_y, &palette);
         let frame = Frame::new(shapes, frame_seconds);
         frames.push(frame);
     }
 
     let additive = false;
     let back_color = Color1::white();
-    let colors = vec![Color1::white(), Color1::black(), Color1::black(), Color1::light_gray(), Color1::blue(), Color1::red()];
-    Renderer::display_additive_with_colors("Parabolas", display_width, display_height, back_color, frames, additive, colors);
+    output.try_display_player("Parabolas", display_width, display_height, back_color, FramePlayer::new(back_color, frames, additive, palette.into()))
 }